@@ -0,0 +1,111 @@
+use prost_build::Config;
+use proto_builder_trait::{prost::BuilderAttributes, Generator};
+use std::{env, fs, io, path::PathBuf};
+
+const INCLUDES: &[&str] = &["../fixtures/protos"];
+
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed=../fixtures/protos");
+
+    generator("duration_millis")?
+        .with_serde(&["job.Job"], true, true, None)
+        .with_duration_millis("job.Job", &["timeout", "retry_delays", "step_timeouts"])
+        .with_field_attributes(&["job.Job.runs"], &["#[serde(skip)]"])
+        .compile_protos(&["../fixtures/protos/job.proto"], INCLUDES)?;
+
+    generator("enum_fields_as_name")?
+        .with_serde(&["todo.Todo"], true, true, None)
+        .with_enum_fields_as_name(&["todo.Todo"])
+        .with_field_attributes(
+            &["todo.Todo.created_at", "todo.Todo.updated_at"],
+            &["#[serde(skip)]"],
+        )
+        .compile_protos(&["../fixtures/protos/todo.proto"], INCLUDES)?;
+
+    generator("serialize_redacted")?
+        .with_serde(&["todo.Todo"], true, true, None)
+        .with_serialize_redacted("todo.Todo", &["title", "description"])
+        .with_field_attributes(
+            &["todo.Todo.created_at", "todo.Todo.updated_at"],
+            &["#[serde(skip)]"],
+        )
+        .compile_protos(&["../fixtures/protos/todo.proto"], INCLUDES)?;
+
+    generator("vec_skip_errors")?
+        .with_serde(&["todo.GetTodosRequest"], true, true, None)
+        .with_vec_skip_errors("todo.GetTodosRequest", &["id"])
+        .compile_protos(&["../fixtures/protos/todo.proto"], INCLUDES)?;
+
+    generator("timestamp_array")?
+        .with_serde(&["job.Job"], true, true, None)
+        .with_timestamp_array("job.Job", &["runs"])
+        .with_field_attributes(
+            &[
+                "job.Job.timeout",
+                "job.Job.retry_delays",
+                "job.Job.step_timeouts",
+            ],
+            &["#[serde(skip)]"],
+        )
+        .compile_protos(&["../fixtures/protos/job.proto"], INCLUDES)?;
+
+    generator("serde_field_prefix")?
+        .with_serde(&["todo.Todo"], true, true, None)
+        .with_serde_field_prefix("todo.Todo", "todo_")
+        .with_field_attributes(
+            &["todo.Todo.created_at", "todo.Todo.updated_at"],
+            &["#[serde(skip)]"],
+        )
+        .compile_protos(&["../fixtures/protos/todo.proto"], INCLUDES)?;
+
+    generator("decimal_fields")?
+        .with_serde(&["order.Order"], true, true, None)
+        .with_decimal_fields("order.Order", &["amount", "discount", "line_amounts"])
+        .compile_protos(&["../fixtures/protos/order.proto"], INCLUDES)?;
+
+    generator("ip_fields")?
+        .with_serde(&["peer.Peer"], true, true, None)
+        .with_ip_fields("peer.Peer", &["address", "gateway", "allowed"])
+        .compile_protos(&["../fixtures/protos/peer.proto"], INCLUDES)?;
+
+    generator("epoch_seconds")?
+        .with_serde(&["todo.Todo"], true, true, None)
+        .with_epoch_seconds_timestamps("todo.Todo", &["created_at"])
+        .with_field_attributes(&["todo.Todo.updated_at"], &["#[serde(skip)]"])
+        .compile_protos(&["../fixtures/protos/todo.proto"], INCLUDES)?;
+
+    generator("len_prefixed")?
+        .with_serde(&["peer.Peer"], true, true, None)
+        .with_len_prefixed_bytes("peer.Peer", &["raw_address"])
+        .compile_protos(&["../fixtures/protos/peer.proto"], INCLUDES)?;
+
+    generator("percentage_fields")?
+        .with_serde(&["order.Order"], true, true, None)
+        .with_percentage_fields("order.Order", &["discount_rate", "tax_rate"])
+        .compile_protos(&["../fixtures/protos/order.proto"], INCLUDES)?;
+
+    generator("btree_maps")?
+        .with_serde(&["metrics.Sample"], true, true, None)
+        .with_btree_maps(&["metrics.Sample.counters"])
+        .with_serde_as_map(
+            "metrics.Sample",
+            &[(&["counters", "labels"], "serde_with::DisplayFromStr")],
+        )
+        .compile_protos(&["../fixtures/protos/metrics.proto"], INCLUDES)?;
+
+    generator("enum_parse_ci")?
+        .with_enum_parse_ci(&["alias.Level"])
+        .compile_protos(&["../fixtures/protos/alias.proto"], INCLUDES)?;
+
+    Ok(())
+}
+
+/// a generator writing into its own directory under `OUT_DIR`, so every case gets a fresh copy
+/// of the protos it uses
+fn generator(name: &str) -> io::Result<Generator<Config>> {
+    let dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join(name);
+    fs::create_dir_all(&dir)?;
+    let mut generator = Generator::new(Config::default());
+    generator.out_dir(dir);
+    Ok(generator)
+}
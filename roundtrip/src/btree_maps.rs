@@ -0,0 +1,25 @@
+include!(concat!(env!("OUT_DIR"), "/btree_maps/metrics.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn btree_maps_with_serde_as_map_should_round_trip() {
+        let sample = Sample {
+            name: "requests".to_string(),
+            values: vec![1, 2],
+            counters: [("b".to_string(), 2), ("a".to_string(), 1)].into(),
+            labels: [("host".to_string(), "a".to_string())].into(),
+        };
+        let decoded = Sample::decode(sample.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, sample);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["counters"], json!({ "a": "1", "b": "2" }));
+        assert_eq!(value["labels"], json!({ "host": "a" }));
+        assert_eq!(serde_json::from_value::<Sample>(value).unwrap(), sample);
+    }
+}
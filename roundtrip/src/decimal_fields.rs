@@ -0,0 +1,47 @@
+include!(concat!(env!("OUT_DIR"), "/decimal_fields/order.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn decimal_fields_should_round_trip() {
+        let order = Order {
+            id: "1".to_string(),
+            amount: "1.50".to_string(),
+            discount: Some("0.25".to_string()),
+            line_amounts: vec!["1".to_string(), "0.50".to_string()],
+            ..Default::default()
+        };
+        let decoded = Order::decode(order.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, order);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["amount"], json!("1.50"));
+        assert_eq!(value["discount"], json!("0.25"));
+        assert_eq!(value["line_amounts"], json!(["1", "0.50"]));
+        assert_eq!(serde_json::from_value::<Order>(value).unwrap(), order);
+    }
+
+    #[test]
+    fn decimal_fields_should_reject_invalid_decimals() {
+        let valid = json!({
+            "id": "1",
+            "amount": "1",
+            "line_amounts": [],
+            "discount_rate": 0.0,
+        });
+        assert!(serde_json::from_value::<Order>(valid.clone()).is_ok());
+        for (field, value) in [
+            ("amount", json!("abc")),
+            ("discount", json!("1.2.3")),
+            ("line_amounts", json!(["1", "one"])),
+        ] {
+            let mut order = valid.clone();
+            order[field] = value;
+            assert!(serde_json::from_value::<Order>(order).is_err(), "{}", field);
+        }
+    }
+}
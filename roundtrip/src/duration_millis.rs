@@ -0,0 +1,58 @@
+include!(concat!(env!("OUT_DIR"), "/duration_millis/job.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use prost_types::Duration;
+    use serde_json::json;
+
+    #[test]
+    fn duration_millis_should_round_trip() {
+        let job = Job {
+            id: "job".to_string(),
+            timeout: Some(Duration {
+                seconds: 1,
+                nanos: 500_000_000,
+            }),
+            retry_delays: vec![
+                Duration {
+                    seconds: 0,
+                    nanos: 250_000_000,
+                },
+                Duration {
+                    seconds: 2,
+                    nanos: 0,
+                },
+            ],
+            runs: vec![],
+            step_timeouts: [(
+                "fetch".to_string(),
+                Duration {
+                    seconds: 3,
+                    nanos: 0,
+                },
+            )]
+            .into(),
+        };
+        let decoded = Job::decode(job.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, job);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["timeout"], json!(1500));
+        assert_eq!(value["retry_delays"], json!([250, 2000]));
+        assert_eq!(value["step_timeouts"], json!({ "fetch": 3000 }));
+        assert_eq!(serde_json::from_value::<Job>(value).unwrap(), job);
+    }
+
+    #[test]
+    fn duration_millis_should_deserialize_missing_optional_as_none() {
+        let job: Job = serde_json::from_value(json!({
+            "id": "job",
+            "retry_delays": [],
+            "step_timeouts": {},
+        }))
+        .unwrap();
+        assert_eq!(job.timeout, None);
+    }
+}
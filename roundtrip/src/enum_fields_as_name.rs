@@ -0,0 +1,50 @@
+include!(concat!(env!("OUT_DIR"), "/enum_fields_as_name/todo.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    fn todo(status: i32) -> Todo {
+        Todo {
+            id: "1".to_string(),
+            status,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn enum_fields_as_name_should_round_trip() {
+        let todo = todo(TodoStatus::Done as i32);
+        let decoded = Todo::decode(todo.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, todo);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["status"], json!("TODO_STATUS_DONE"));
+        assert_eq!(serde_json::from_value::<Todo>(value).unwrap(), todo);
+    }
+
+    #[test]
+    fn enum_fields_as_name_should_round_trip_unknown_values_as_numbers() {
+        let todo = todo(7);
+        let value = serde_json::to_value(&todo).unwrap();
+        assert_eq!(value["status"], json!(7));
+        assert_eq!(serde_json::from_value::<Todo>(value).unwrap(), todo);
+    }
+
+    #[test]
+    fn enum_fields_as_name_should_reject_unknown_names() {
+        let err = serde_json::from_value::<Todo>(json!({
+            "id": "1",
+            "title": "",
+            "description": "",
+            "status": "TODO_STATUS_LOST",
+        }))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown `todo.TodoStatus` value `TODO_STATUS_LOST`"
+        );
+    }
+}
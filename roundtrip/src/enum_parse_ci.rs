@@ -0,0 +1,15 @@
+include!(concat!(env!("OUT_DIR"), "/enum_parse_ci/alias.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enum_parse_ci_should_parse_aliases_to_the_first_value() {
+        for name in ["warning", "LEVEL_WARNING", "level_warn", "LEVEL_WARN"] {
+            assert_eq!(Level::parse_ci(name), Some(Level::Warning), "{}", name);
+        }
+        assert_eq!(Level::parse_ci("error"), Some(Level::Error));
+        assert_eq!(Level::parse_ci("warn"), None);
+    }
+}
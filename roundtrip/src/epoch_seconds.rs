@@ -0,0 +1,49 @@
+include!(concat!(env!("OUT_DIR"), "/epoch_seconds/todo.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use prost_types::Timestamp;
+    use serde_json::json;
+
+    #[test]
+    fn epoch_seconds_should_round_trip() {
+        let todo = Todo {
+            id: "1".to_string(),
+            created_at: Some(Timestamp {
+                seconds: 1_704_067_200,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        let decoded = Todo::decode(todo.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, todo);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["created_at"], json!(1_704_067_200));
+        assert_eq!(serde_json::from_value::<Todo>(value).unwrap(), todo);
+    }
+
+    #[test]
+    fn epoch_seconds_should_drop_nanos() {
+        let todo = Todo {
+            created_at: Some(Timestamp {
+                seconds: 1_704_067_200,
+                nanos: 500_000_000,
+            }),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&todo).unwrap();
+        assert_eq!(value["created_at"], json!(1_704_067_200));
+
+        let todo = serde_json::from_value::<Todo>(value).unwrap();
+        assert_eq!(
+            todo.created_at,
+            Some(Timestamp {
+                seconds: 1_704_067_200,
+                nanos: 0,
+            })
+        );
+    }
+}
@@ -0,0 +1,43 @@
+include!(concat!(env!("OUT_DIR"), "/ip_fields/peer.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn ip_fields_should_round_trip() {
+        let peer = Peer {
+            id: "1".to_string(),
+            address: "10.0.0.1".to_string(),
+            gateway: Some("::1".to_string()),
+            allowed: vec!["192.168.0.1".to_string()],
+            raw_address: vec![10, 0, 0, 1],
+        };
+        let decoded = Peer::decode(peer.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, peer);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["address"], json!("10.0.0.1"));
+        assert_eq!(value["gateway"], json!("::1"));
+        assert_eq!(value["allowed"], json!(["192.168.0.1"]));
+        assert_eq!(serde_json::from_value::<Peer>(value).unwrap(), peer);
+    }
+
+    #[test]
+    fn ip_fields_should_reject_invalid_addresses() {
+        let valid = json!({
+            "id": "1",
+            "address": "10.0.0.1",
+            "allowed": [],
+            "raw_address": [],
+        });
+        assert!(serde_json::from_value::<Peer>(valid.clone()).is_ok());
+
+        let mut invalid = valid;
+        invalid["address"] = json!("10.0.0.256");
+        let err = serde_json::from_value::<Peer>(invalid).unwrap_err();
+        assert_eq!(err.to_string(), "invalid IP address syntax");
+    }
+}
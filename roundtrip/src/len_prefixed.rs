@@ -0,0 +1,38 @@
+include!(concat!(env!("OUT_DIR"), "/len_prefixed/peer.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn len_prefixed_bytes_should_round_trip() {
+        let value = len_prefixed_bytes::serialize(&vec![1u8, 2], serde_json::value::Serializer);
+        assert_eq!(value.unwrap(), json!([0, 0, 0, 2, 1, 2]));
+        let data: Vec<u8> = len_prefixed_bytes::deserialize(json!([0, 0, 0, 2, 1, 2])).unwrap();
+        assert_eq!(data, [1, 2]);
+
+        let peer = Peer {
+            id: "1".to_string(),
+            raw_address: vec![10, 0, 0, 1],
+            ..Default::default()
+        };
+        let decoded = Peer::decode(peer.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, peer);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["raw_address"], json!([0, 0, 0, 4, 10, 0, 0, 1]));
+        assert_eq!(serde_json::from_value::<Peer>(value).unwrap(), peer);
+    }
+
+    #[test]
+    fn len_prefixed_bytes_should_reject_a_wrong_prefix() {
+        let err =
+            len_prefixed_bytes::deserialize::<Vec<u8>, _>(json!([0, 0, 0, 3, 1, 2])).unwrap_err();
+        assert_eq!(err.to_string(), "length prefix is 3 but 2 bytes follow");
+
+        let err = len_prefixed_bytes::deserialize::<Vec<u8>, _>(json!([0, 0])).unwrap_err();
+        assert_eq!(err.to_string(), "missing length prefix");
+    }
+}
@@ -0,0 +1,18 @@
+//! Round trip tests for the code generated by `proto-builder-trait`. The build script compiles
+//! the fixtures once per case, each into its own directory under `OUT_DIR`; every module below
+//! includes one of them and checks that it compiles and behaves as documented, e.g. that it
+//! encodes, serializes and decodes back to the same value.
+
+pub mod btree_maps;
+pub mod decimal_fields;
+pub mod duration_millis;
+pub mod enum_fields_as_name;
+pub mod enum_parse_ci;
+pub mod epoch_seconds;
+pub mod ip_fields;
+pub mod len_prefixed;
+pub mod percentage_fields;
+pub mod serde_field_prefix;
+pub mod serialize_redacted;
+pub mod timestamp_array;
+pub mod vec_skip_errors;
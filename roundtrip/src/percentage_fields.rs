@@ -0,0 +1,65 @@
+include!(concat!(env!("OUT_DIR"), "/percentage_fields/order.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    fn order(discount_rate: f64, tax_rate: Option<f32>) -> Order {
+        Order {
+            id: "1".to_string(),
+            amount: "10".to_string(),
+            discount_rate,
+            tax_rate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn percentage_fields_should_round_trip() {
+        let order = order(0.425, Some(0.5));
+        let decoded = Order::decode(order.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, order);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["discount_rate"], json!("42.5%"));
+        assert_eq!(value["tax_rate"], json!("50%"));
+        assert_eq!(serde_json::from_value::<Order>(value).unwrap(), order);
+    }
+
+    #[test]
+    fn percentage_fields_should_round_to_4_decimal_places() {
+        let value = serde_json::to_value(order(0.123456789, Some(0.1))).unwrap();
+        assert_eq!(value["discount_rate"], json!("12.3457%"));
+        assert_eq!(value["tax_rate"], json!("10%"));
+
+        let value = serde_json::to_value(order(-0.000_000_1, None)).unwrap();
+        assert_eq!(value["discount_rate"], json!("0%"));
+        assert_eq!(value.get("tax_rate"), None);
+    }
+
+    #[test]
+    fn percentage_fields_should_reject_invalid_input() {
+        let valid = json!({
+            "id": "1",
+            "amount": "10",
+            "line_amounts": [],
+            "discount_rate": "42%",
+        });
+        assert_eq!(
+            serde_json::from_value::<Order>(valid.clone())
+                .unwrap()
+                .discount_rate,
+            0.42
+        );
+        for value in [json!("42"), json!("abc%"), json!("inf%"), json!(0.42)] {
+            let mut order = valid.clone();
+            order["discount_rate"] = value.clone();
+            assert!(serde_json::from_value::<Order>(order).is_err(), "{}", value);
+        }
+
+        let err = serde_json::to_value(order(f64::NAN, None)).unwrap_err();
+        assert_eq!(err.to_string(), "ratio is not finite");
+    }
+}
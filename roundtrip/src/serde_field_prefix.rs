@@ -0,0 +1,33 @@
+include!(concat!(env!("OUT_DIR"), "/serde_field_prefix/todo.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn serde_field_prefix_should_round_trip() {
+        let todo = Todo {
+            id: "1".to_string(),
+            title: "title".to_string(),
+            description: "description".to_string(),
+            status: TodoStatus::Done as i32,
+            ..Default::default()
+        };
+        let decoded = Todo::decode(todo.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, todo);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "todo_id": "1",
+                "todo_title": "title",
+                "todo_description": "description",
+                "todo_status": 1,
+            })
+        );
+        assert_eq!(serde_json::from_value::<Todo>(value).unwrap(), todo);
+    }
+}
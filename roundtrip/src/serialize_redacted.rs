@@ -0,0 +1,26 @@
+include!(concat!(env!("OUT_DIR"), "/serialize_redacted/todo.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn serialize_redacted_should_hide_the_fields() {
+        let todo = Todo {
+            id: "1".to_string(),
+            title: "secret title".to_string(),
+            description: "secret description".to_string(),
+            ..Default::default()
+        };
+        let decoded = Todo::decode(todo.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, todo);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(value["id"], json!("1"));
+        assert_eq!(value["title"], json!("***"));
+        assert_eq!(value["description"], json!("***"));
+        assert!(!value.to_string().contains("secret"));
+    }
+}
@@ -0,0 +1,45 @@
+include!(concat!(env!("OUT_DIR"), "/timestamp_array/job.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use prost_types::Timestamp;
+    use serde_json::json;
+
+    #[test]
+    fn timestamp_array_should_round_trip() {
+        let job = Job {
+            id: "job".to_string(),
+            runs: vec![
+                Timestamp {
+                    seconds: 1_704_067_200,
+                    nanos: 0,
+                },
+                Timestamp {
+                    seconds: 1_704_067_201,
+                    nanos: 500_000_000,
+                },
+            ],
+            ..Default::default()
+        };
+        let decoded = Job::decode(job.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, job);
+
+        let value = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(
+            value["runs"],
+            json!(["2024-01-01T00:00:00Z", "2024-01-01T00:00:01.500Z"])
+        );
+        assert_eq!(serde_json::from_value::<Job>(value).unwrap(), job);
+    }
+
+    #[test]
+    fn timestamp_array_should_reject_invalid_timestamps() {
+        let job = serde_json::from_value::<Job>(json!({
+            "id": "job",
+            "runs": ["2024-01-01T00:00:00Z", "yesterday"],
+        }));
+        assert!(job.is_err());
+    }
+}
@@ -0,0 +1,22 @@
+include!(concat!(env!("OUT_DIR"), "/vec_skip_errors/todo.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use serde_json::json;
+
+    #[test]
+    fn vec_skip_errors_should_keep_the_valid_elements() {
+        let request: GetTodosRequest =
+            serde_json::from_value(json!({ "id": ["a", 1, "b", null, {}] })).unwrap();
+        assert_eq!(request.id, ["a", "b"]);
+
+        let decoded = GetTodosRequest::decode(request.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, request);
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            json!({ "id": ["a", "b"] })
+        );
+    }
+}
@@ -0,0 +1,2691 @@
+//! Extra items appended to the generated code. Each function returns an [`Extension`] that is
+//! run by the [`Generator`](crate::Generator) once prost/tonic wrote their files.
+
+use crate::{
+    generator::{
+        append, append_once, enumeration, field, field_ident, file_of, find_item, insert_after,
+        invalid, message, parse_struct, root_prefix, scalar_type, type_path, type_snake,
+        variant_ident, Attribute, Extension, Files,
+    },
+    parser::{is_scalar, Enum, Field, Label, Message, Protos, Resolved},
+    utils::{derivative_debug_attr, serde_as_attr},
+};
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use std::io;
+
+/// `fn index_<type>_by_<key>(items: Vec<Type>) -> HashMap<Key, Type>`
+pub(crate) fn index_by(path: &str, key_field: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let key_field = key_field.to_string();
+    Extension::generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        let key = field(msg, &path, &key_field)?;
+        let key_ty = match protos.resolve(&path, &key.ty) {
+            _ if key.label == Label::Repeated || key.key.is_some() || key.oneof.is_some() => None,
+            Resolved::Scalar(s) if s != "double" && s != "float" => {
+                Some(scalar_type(&s).to_string())
+            }
+            Resolved::Enum(_) => Some("i32".to_string()),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            invalid(format!(
+                "field `{}` of `{}` cannot be used as a hash map key",
+                key_field, path
+            ))
+        })?;
+        let key_ty = if key.label == Label::Optional {
+            format!("::core::option::Option<{}>", key_ty)
+        } else {
+            key_ty
+        };
+
+        let ty = type_path(protos, &path);
+        let ident = field_ident(&key_field);
+        let code = format!(
+            r#"
+            /// Index a list of [`{ty}`] by its `{ident}` field.
+            pub fn index_{snake}_by_{name}(
+                items: ::prost::alloc::vec::Vec<{ty}>,
+            ) -> ::std::collections::HashMap<{key_ty}, {ty}> {{
+                items.into_iter().map(|item| (item.{ident}.clone(), item)).collect()
+            }}
+            "#,
+            snake = type_snake(protos, &path),
+            name = ident.trim_start_matches("r#"),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+const DURATION_MILLIS: &str = r#"
+/// `serde_with` adapter (de)serializing a [`::prost_types::Duration`] as integer milliseconds.
+pub struct DurationMillis;
+
+impl ::serde_with::SerializeAs<::prost_types::Duration> for DurationMillis {
+    fn serialize_as<S>(
+        source: &::prost_types::Duration,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        let millis = source
+            .seconds
+            .saturating_mul(1000)
+            .saturating_add(i64::from(source.nanos / 1_000_000));
+        serializer.serialize_i64(millis)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost_types::Duration> for DurationMillis {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<::prost_types::Duration, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let millis = <i64 as ::serde::Deserialize>::deserialize(deserializer)?;
+        Ok(::prost_types::Duration {
+            seconds: millis / 1000,
+            nanos: (millis % 1000) as i32 * 1_000_000,
+        })
+    }
+}
+"#;
+
+/// serialize `google.protobuf.Duration` fields as integer milliseconds
+pub(crate) fn duration_millis(path: &str, fields: &[&str]) -> Extension {
+    well_known_adapter_fields(
+        path,
+        fields,
+        "google.protobuf.Duration",
+        "DurationMillis",
+        DURATION_MILLIS,
+    )
+}
+
+const EPOCH_SECONDS: &str = r#"
+/// `serde_with` adapter (de)serializing a [`::prost_types::Timestamp`] as integer seconds since
+/// the Unix epoch. Nanoseconds are dropped.
+pub struct EpochSeconds;
+
+impl ::serde_with::SerializeAs<::prost_types::Timestamp> for EpochSeconds {
+    fn serialize_as<S>(
+        source: &::prost_types::Timestamp,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_i64(source.seconds)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost_types::Timestamp> for EpochSeconds {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<::prost_types::Timestamp, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let seconds = <i64 as ::serde::Deserialize>::deserialize(deserializer)?;
+        Ok(::prost_types::Timestamp { seconds, nanos: 0 })
+    }
+}
+"#;
+
+/// serialize `google.protobuf.Timestamp` fields as integer seconds since the Unix epoch
+pub(crate) fn epoch_seconds(path: &str, fields: &[&str]) -> Extension {
+    well_known_adapter_fields(
+        path,
+        fields,
+        "google.protobuf.Timestamp",
+        "EpochSeconds",
+        EPOCH_SECONDS,
+    )
+}
+
+/// `serde_as` with the `adapter` struct defined in `code` on fields of the well known type
+/// `expected`, wrapped in `Option`, `Vec` or `HashMap` as the field requires
+fn well_known_adapter_fields(
+    path: &str,
+    fields: &[&str],
+    expected: &'static str,
+    adapter: &'static str,
+    code: &'static str,
+) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    let package_path = path.clone();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+        for name in &fields {
+            let f = field(msg, &path, name)?;
+            expect_type(protos, &path, &f.ty, name, expected)?;
+            let ty = format!("{}{}", root_prefix(protos, &path), adapter);
+            let ty = match f.label {
+                _ if f.key.is_some() => format!("::std::collections::HashMap<_, {}>", ty),
+                Label::Repeated => format!("Vec<{}>", ty),
+                _ => format!("Option<{}>", ty),
+            };
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                format!(r#"#[serde_as(as = "{}")]"#, ty),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        let package = protos.package_of(&package_path).unwrap_or_default();
+        append_once(files, package, &format!("pub struct {};", adapter), code)
+    })
+}
+
+const CHRONO_TIMESTAMP: &str = r#"
+/// `serde_with` adapter (de)serializing a [`::prost_types::Timestamp`] through
+/// `chrono::DateTime<chrono::Utc>`, i.e. as an RFC 3339 string.
+pub struct ChronoTimestamp;
+
+impl ::serde_with::SerializeAs<::prost_types::Timestamp> for ChronoTimestamp {
+    fn serialize_as<S>(
+        source: &::prost_types::Timestamp,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        let time = u32::try_from(source.nanos)
+            .ok()
+            .and_then(|nanos| ::chrono::DateTime::from_timestamp(source.seconds, nanos))
+            .ok_or_else(|| <S::Error as ::serde::ser::Error>::custom("timestamp out of range"))?;
+        ::serde::Serialize::serialize(&time, serializer)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost_types::Timestamp> for ChronoTimestamp {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<::prost_types::Timestamp, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let time =
+            <::chrono::DateTime<::chrono::Utc> as ::serde::Deserialize>::deserialize(deserializer)?;
+        Ok(::prost_types::Timestamp {
+            seconds: time.timestamp(),
+            nanos: time.timestamp_subsec_nanos() as i32,
+        })
+    }
+}
+"#;
+
+const TIME_TIMESTAMP: &str = r#"
+/// `serde_with` adapter (de)serializing a [`::prost_types::Timestamp`] through
+/// `time::OffsetDateTime` as an RFC 3339 string.
+pub struct TimeTimestamp;
+
+impl ::serde_with::SerializeAs<::prost_types::Timestamp> for TimeTimestamp {
+    fn serialize_as<S>(
+        source: &::prost_types::Timestamp,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        let nanos = i128::from(source.seconds) * 1_000_000_000 + i128::from(source.nanos);
+        let time = ::time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map_err(<S::Error as ::serde::ser::Error>::custom)?;
+        ::time::serde::rfc3339::serialize(&time, serializer)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost_types::Timestamp> for TimeTimestamp {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<::prost_types::Timestamp, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let time = ::time::serde::rfc3339::deserialize(deserializer)?;
+        Ok(::prost_types::Timestamp {
+            seconds: time.unix_timestamp(),
+            nanos: time.nanosecond() as i32,
+        })
+    }
+}
+"#;
+
+/// serialize every `google.protobuf.Timestamp` field of the protos through `chrono`
+pub(crate) fn chrono_timestamps() -> Extension {
+    well_known_adapter_everywhere(
+        "google.protobuf.Timestamp",
+        "ChronoTimestamp",
+        CHRONO_TIMESTAMP,
+    )
+}
+
+/// serialize every `google.protobuf.Timestamp` field of the protos through `time`
+pub(crate) fn time_timestamps() -> Extension {
+    well_known_adapter_everywhere("google.protobuf.Timestamp", "TimeTimestamp", TIME_TIMESTAMP)
+}
+
+/// `serde_as` with the `adapter` struct defined in `code` on every field of the well known type
+/// `expected` across the protos, wrapped in `Option`, `Vec` or `HashMap` as the field requires.
+/// Oneof members are left alone. The adapter is generated in each package using it.
+fn well_known_adapter_everywhere(
+    expected: &'static str,
+    adapter: &'static str,
+    code: &'static str,
+) -> Extension {
+    let fields = move |protos: &Protos| {
+        let mut out = Vec::new();
+        for (path, msg) in protos.messages() {
+            for f in msg.fields.iter().filter(|f| f.oneof.is_none()) {
+                if expect_type(protos, &path, &f.ty, &f.name, expected).is_ok() {
+                    out.push((path.clone(), f.clone()));
+                }
+            }
+        }
+        out
+    };
+    Extension::attributes(move |protos, attrs| {
+        for (path, f) in fields(protos) {
+            let ty = format!("{}{}", root_prefix(protos, &path), adapter);
+            let ty = match f.label {
+                _ if f.key.is_some() => format!("::std::collections::HashMap<_, {}>", ty),
+                Label::Repeated => format!("Vec<{}>", ty),
+                _ => format!("Option<{}>", ty),
+            };
+            attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, f.name),
+                format!(r#"#[serde_as(as = "{}")]"#, ty),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        for (path, _) in fields(protos) {
+            let package = protos.package_of(&path).unwrap_or_default();
+            append_once(files, package, &format!("pub struct {};", adapter), code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `#[serde_as(as = "Vec<DisplayFromStr>")]` on repeated `google.protobuf.Timestamp` fields,
+/// relying on the RFC 3339 `Display` / `FromStr` impls of `prost_types::Timestamp`
+pub(crate) fn timestamp_array(path: &str, fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+        for name in &fields {
+            let f = field(msg, &path, name)?;
+            expect_type(protos, &path, &f.ty, name, "google.protobuf.Timestamp")?;
+            if f.label != Label::Repeated {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a repeated field",
+                    name, path
+                )));
+            }
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                r#"#[serde_as(as = "Vec<serde_with::DisplayFromStr>")]"#.to_string(),
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// `#[serde_as(as = "<Enum>")]` on the enum fields of the messages, with `serde_with` adapter
+/// impls on the enums (de)serializing the `i32` as the proto name of the value. Values the enum
+/// doesn't know are serialized as numbers.
+pub(crate) fn enum_fields_as_name(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    let fields = paths.clone();
+    Extension::attributes(move |protos, attrs| {
+        for (path, f, e) in enum_fields(protos, &fields)? {
+            let ty = format!("{}{}", root_prefix(protos, &path), type_path(protos, &e));
+            let ty = match f.label {
+                _ if f.key.is_some() => format!("::std::collections::HashMap<_, {}>", ty),
+                Label::Repeated => format!("Vec<{}>", ty),
+                Label::Optional => format!("Option<{}>", ty),
+                _ => ty,
+            };
+            attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, f.name),
+                format!(r#"#[serde_as(as = "{}")]"#, ty),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        for (_, _, e) in enum_fields(protos, &paths)? {
+            let ty = type_path(protos, &e);
+            let package = protos.package_of(&e).unwrap_or_default();
+            let code = format!(
+                r#"
+                impl ::serde_with::SerializeAs<i32> for {ty} {{
+                    fn serialize_as<S>(
+                        source: &i32,
+                        serializer: S,
+                    ) -> ::core::result::Result<S::Ok, S::Error>
+                    where
+                        S: ::serde::Serializer,
+                    {{
+                        match Self::try_from(*source) {{
+                            Ok(value) => serializer.serialize_str(value.as_str_name()),
+                            Err(_) => serializer.serialize_i32(*source),
+                        }}
+                    }}
+                }}
+
+                impl<'de> ::serde_with::DeserializeAs<'de, i32> for {ty} {{
+                    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<i32, D::Error>
+                    where
+                        D: ::serde::Deserializer<'de>,
+                    {{
+                        struct Visitor;
+
+                        impl<'de> ::serde::de::Visitor<'de> for Visitor {{
+                            type Value = i32;
+
+                            fn expecting(
+                                &self,
+                                formatter: &mut ::core::fmt::Formatter,
+                            ) -> ::core::fmt::Result {{
+                                formatter.write_str("a `{e}` name or number")
+                            }}
+
+                            fn visit_str<E>(self, name: &str) -> ::core::result::Result<i32, E>
+                            where
+                                E: ::serde::de::Error,
+                            {{
+                                {ty}::from_str_name(name).map(|value| value as i32).ok_or_else(|| {{
+                                    E::custom(format!("unknown `{e}` value `{{}}`", name))
+                                }})
+                            }}
+
+                            fn visit_i64<E>(self, value: i64) -> ::core::result::Result<i32, E>
+                            where
+                                E: ::serde::de::Error,
+                            {{
+                                i32::try_from(value).map_err(|_| {{
+                                    E::invalid_value(::serde::de::Unexpected::Signed(value), &self)
+                                }})
+                            }}
+
+                            fn visit_u64<E>(self, value: u64) -> ::core::result::Result<i32, E>
+                            where
+                                E: ::serde::de::Error,
+                            {{
+                                i32::try_from(value).map_err(|_| {{
+                                    E::invalid_value(::serde::de::Unexpected::Unsigned(value), &self)
+                                }})
+                            }}
+                        }}
+
+                        deserializer.deserialize_any(Visitor)
+                    }}
+                }}
+                "#
+            );
+            append_once(
+                files,
+                package,
+                &format!("impl ::serde_with::SerializeAs<i32> for {} {{", ty),
+                &code,
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// the non-oneof enum fields of the messages, with the message path and the enum they hold.
+/// Enums of other packages are rejected, as the adapter impls go into the enum's file.
+fn enum_fields<'a>(
+    protos: &'a Protos,
+    paths: &[String],
+) -> io::Result<Vec<(String, &'a Field, String)>> {
+    let mut out = Vec::new();
+    for path in paths {
+        let msg = message(protos, path)?;
+        for f in msg.fields.iter().filter(|f| f.oneof.is_none()) {
+            let Resolved::Enum(name) = protos.resolve(path, &f.ty) else {
+                continue;
+            };
+            if protos.package_of(&name) != protos.package_of(path) {
+                return Err(invalid(format!(
+                    "enum `{}` of field `{}` is not in the package of `{}`",
+                    name, f.name, path
+                )));
+            }
+            out.push((path.clone(), f, name));
+        }
+    }
+    Ok(out)
+}
+
+/// `#[serde(rename = "<prefix>_<field>")]` on every field of the message, oneofs included
+pub(crate) fn serde_field_prefix(path: &str, prefix: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let prefix = prefix.trim_end_matches('_').to_string();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        let fields = msg.fields.iter().filter(|f| f.oneof.is_none());
+        for name in fields.map(|f| &f.name).chain(&msg.oneofs) {
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                format!(r#"#[serde(rename = "{}_{}")]"#, prefix, name),
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// `#[serde_as(as = "VecSkipError<_>")]` on repeated fields, dropping the elements that fail to
+/// deserialize instead of failing the whole message
+pub(crate) fn vec_skip_errors(path: &str, fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+        for name in &fields {
+            let f = field(msg, &path, name)?;
+            if f.label != Label::Repeated || f.key.is_some() {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a repeated field",
+                    name, path
+                )));
+            }
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                r#"#[serde_as(as = "serde_with::VecSkipError<_>")]"#.to_string(),
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// replace the `#[repr(i32)]` prost puts on enums
+pub(crate) fn enum_repr(paths: &[&str], repr: &str) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    let repr = repr.to_string();
+    Extension::generate(move |protos, files| {
+        let range = match repr.as_str() {
+            "u8" => Some((u8::MIN as i64, u8::MAX as i64)),
+            "i8" => Some((i8::MIN as i64, i8::MAX as i64)),
+            "u16" => Some((u16::MIN as i64, u16::MAX as i64)),
+            "i16" => Some((i16::MIN as i64, i16::MAX as i64)),
+            "u32" | "u64" | "usize" => Some((0, u32::MAX as i64)),
+            "i32" | "i64" | "isize" | "C" => None,
+            _ => return Err(invalid(format!("`{}` is not a supported enum repr", repr))),
+        };
+        for path in &paths {
+            let e = enumeration(protos, path)?;
+            if let Some((min, max)) = range {
+                if let Some(v) = e
+                    .values
+                    .iter()
+                    .find(|v| (v.number as i64) < min || (v.number as i64) > max)
+                {
+                    return Err(invalid(format!(
+                        "value `{}` of `{}` doesn't fit in `{}`",
+                        v.name, path, repr
+                    )));
+                }
+            }
+            let ty = type_path(protos, path);
+            let content = file_of(protos, files, path)?;
+            let (offset, indent) = find_item(content, &ty, "enum").ok_or_else(|| {
+                invalid(format!("enum `{}` not found in the generated code", path))
+            })?;
+            let old = format!("{}#[repr(i32)]\n", indent);
+            if !content[..offset].ends_with(&old) {
+                return Err(invalid(format!(
+                    "`{}` has no `#[repr(i32)]` to replace",
+                    path
+                )));
+            }
+            let start = offset - old.len();
+            content.replace_range(start..offset, &format!("{}#[repr({})]\n", indent, repr));
+        }
+        Ok(())
+    })
+}
+
+/// `const PROTO_NAMES: &[(Self, &str)]` pairing each variant with its proto name
+pub(crate) fn enum_name_map(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let e = enumeration(protos, path)?;
+            let entries: String = variants(e)
+                .iter()
+                .map(|(variant, name)| format!(r#"(Self::{}, "{}"),"#, variant, name))
+                .collect();
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// Every variant with its name in the ProtoBuf definition, see `as_str_name`.
+                    pub const PROTO_NAMES: &'static [(Self, &'static str)] = &[{entries}];
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `fn next(&self) -> Option<Self>` and `fn prev(&self) -> Option<Self>` following the order
+/// the variants are declared in
+pub(crate) fn enum_navigation(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let variants = variants(enumeration(protos, path)?);
+            let mut next = String::new();
+            let mut prev = String::new();
+            for (i, (variant, _)) in variants.iter().enumerate() {
+                let after = variants.get(i + 1).map(|(v, _)| v);
+                let before = i.checked_sub(1).map(|i| &variants[i].0);
+                let arm = |other: Option<&String>| match other {
+                    Some(other) => format!("Self::{} => Some(Self::{}),", variant, other),
+                    None => format!("Self::{} => None,", variant),
+                };
+                next.push_str(&arm(after));
+                prev.push_str(&arm(before));
+            }
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// The variant declared after this one, if any.
+                    pub fn next(&self) -> ::core::option::Option<Self> {{
+                        match self {{
+                            {next}
+                        }}
+                    }}
+
+                    /// The variant declared before this one, if any.
+                    pub fn prev(&self) -> ::core::option::Option<Self> {{
+                        match self {{
+                            {prev}
+                        }}
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// the Rust variants of an enum with their proto names, in declaration order. prost only
+/// generates the first value of aliased numbers.
+fn variants(e: &Enum) -> Vec<(String, &str)> {
+    let mut numbers = Vec::new();
+    e.values
+        .iter()
+        .filter(|v| {
+            let first = !numbers.contains(&v.number);
+            numbers.push(v.number);
+            first
+        })
+        .map(|v| (variant_ident(&e.name, &v.name), v.name.as_str()))
+        .collect()
+}
+
+/// make `variant` (its Rust or proto name) the `Default` of the enum. prost's `Enumeration`
+/// derive defaults to the first variant, so the variant is moved to the top of the enum.
+pub(crate) fn enum_default(path: &str, variant: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let variant = variant.to_string();
+    Extension::generate(move |protos, files| {
+        let ident = variants(enumeration(protos, &path)?)
+            .into_iter()
+            .find(|(ident, name)| *ident == variant || *name == variant)
+            .map(|(ident, _)| ident)
+            .ok_or_else(|| invalid(format!("`{}` is not a value of `{}`", variant, path)))?;
+        let ty = type_path(protos, &path);
+        let content = file_of(protos, files, &path)?;
+        let (offset, indent) = find_item(content, &ty, "enum")
+            .ok_or_else(|| invalid(format!("enum `{}` not found in the generated code", path)))?;
+        let start = offset + content[offset..].find('\n').map_or(0, |i| i + 1);
+        let end = start
+            + content[start..]
+                .find(&format!("{}}}\n", indent))
+                .ok_or_else(|| invalid(format!("enum `{}` has no end", path)))?;
+
+        // each variant with the doc comments and attributes above it
+        let mut groups: Vec<String> = Vec::new();
+        let mut group = String::new();
+        for line in content[start..end].lines() {
+            group.push_str(line);
+            group.push('\n');
+            let line = line.trim_start();
+            if line.ends_with(',') && !line.starts_with('#') && !line.starts_with("//") {
+                groups.push(std::mem::take(&mut group));
+            }
+        }
+        let pos = groups
+            .iter()
+            .position(|g| {
+                g.lines()
+                    .last()
+                    .is_some_and(|l| l.trim_start().starts_with(&format!("{} = ", ident)))
+            })
+            .ok_or_else(|| invalid(format!("variant `{}` of `{}` not found", ident, path)))?;
+        let first = groups.remove(pos);
+        groups.insert(0, first);
+        content.replace_range(start..end, &format!("{}{}", groups.concat(), group));
+        Ok(())
+    })
+}
+
+/// `fn to_metadata(&self) -> tonic::metadata::MetadataMap` copying string fields into gRPC
+/// metadata under the given keys
+pub(crate) fn metadata_fields(path: &str, fields: &[(&str, &str)]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<(String, String)> = fields
+        .iter()
+        .map(|(f, k)| (f.to_string(), k.to_string()))
+        .collect();
+    Extension::generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        let mut inserts = String::new();
+        for (name, key) in &fields {
+            let f = field(msg, &path, name)?;
+            if f.ty != "string"
+                || f.label == Label::Repeated
+                || f.key.is_some()
+                || f.oneof.is_some()
+            {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a plain string field",
+                    name, path
+                )));
+            }
+            let valid_key = key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c));
+            if key.is_empty() || !valid_key || key.ends_with("-bin") {
+                return Err(invalid(format!(
+                    "`{}` is not a valid ASCII metadata key",
+                    key
+                )));
+            }
+            let ident = field_ident(name);
+            let insert = format!(
+                r#"if let Ok(value) = value.parse() {{ map.insert("{}", value); }}"#,
+                key
+            );
+            if f.label == Label::Optional {
+                inserts.push_str(&format!(
+                    "if let Some(value) = &self.{} {{ {} }}\n",
+                    ident, insert
+                ));
+            } else {
+                inserts.push_str(&format!("{{ let value = &self.{}; {} }}\n", ident, insert));
+            }
+        }
+        let code = format!(
+            r#"
+            impl {ty} {{
+                /// Copy fields into gRPC metadata, e.g. to propagate them on outgoing calls.
+                /// Values that aren't valid ASCII metadata are left out.
+                pub fn to_metadata(&self) -> ::tonic::metadata::MetadataMap {{
+                    let mut map = ::tonic::metadata::MetadataMap::new();
+                    {inserts}
+                    map
+                }}
+            }}
+            "#,
+            ty = type_path(protos, &path),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// `fn validate(&self) -> Result<(), String>` enforcing the `protoc-gen-validate` rules declared
+/// on the message's fields
+pub(crate) fn pgv_validation(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let msg = message(protos, path)?;
+            let mut checks = String::new();
+            for f in &msg.fields {
+                let rules: Vec<(&str, &str)> = f
+                    .options
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((name.strip_prefix("(validate.rules).")?, value.as_str()))
+                    })
+                    .collect();
+                if rules.is_empty() {
+                    continue;
+                }
+                let field_path = format!("{}.{}", path, f.name);
+                if f.oneof.is_some() || f.key.is_some() {
+                    return Err(invalid(format!(
+                        "PGV rules on oneof and map fields are not supported (`{}`)",
+                        field_path
+                    )));
+                }
+                let ty = match protos.resolve(path, &f.ty) {
+                    Resolved::Scalar(s) => s,
+                    Resolved::Enum(_) => "enum".to_string(),
+                    Resolved::Message(_) | Resolved::External(_) => "message".to_string(),
+                };
+                let ident = field_ident(&f.name);
+                let mut field_checks = String::new();
+                for (rule, value) in rules {
+                    if let Some((cond, reason)) = pgv_check(&ty, f.label, rule, value, &field_path)?
+                    {
+                        field_checks.push_str(&format!(
+                            "if {} {{ return Err({:?}.into()); }}\n",
+                            cond,
+                            format!("{}: {}", f.name, reason)
+                        ));
+                    }
+                }
+                let single = f.label != Label::Repeated && ty != "message";
+                if single && f.label == Label::Optional {
+                    checks.push_str(&format!(
+                        "if let Some(value) = &self.{} {{ {} }}\n",
+                        ident, field_checks
+                    ));
+                } else {
+                    checks.push_str(&format!(
+                        "{{ let value = &self.{}; {} }}\n",
+                        ident, field_checks
+                    ));
+                }
+            }
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// Check the `protoc-gen-validate` rules declared on the fields of the message,
+                    /// returning the first violation.
+                    pub fn validate(&self) -> ::core::result::Result<(), ::prost::alloc::string::String> {{
+                        {checks}
+                        Ok(())
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// the condition failing a PGV rule (as in `string.min_len`) and its explanation, `None` if the
+/// rule holds for any value (e.g. `message.required = false`)
+fn pgv_check(
+    ty: &str,
+    label: Label,
+    rule: &str,
+    value: &str,
+    field_path: &str,
+) -> io::Result<Option<(String, String)>> {
+    let unsupported = || {
+        Err(invalid(format!(
+            "unsupported PGV rule `{}` on `{}`",
+            rule, field_path
+        )))
+    };
+    let check = |cond: String, reason: String| Ok(Some((cond, reason)));
+    let Some((kind, rule)) = rule.split_once('.') else {
+        return unsupported();
+    };
+    if label == Label::Repeated {
+        return match (kind, rule) {
+            ("repeated", "min_items") => check(
+                format!("value.len() < {}", value),
+                format!("must have at least {} items", value),
+            ),
+            ("repeated", "max_items") => check(
+                format!("value.len() > {}", value),
+                format!("must have at most {} items", value),
+            ),
+            _ => unsupported(),
+        };
+    }
+    match (kind, rule) {
+        ("message", "required") if ty == "message" => match value {
+            "true" => check("value.is_none()".to_string(), "is required".to_string()),
+            _ => Ok(None),
+        },
+        ("message", "skip") if ty == "message" => Ok(None),
+        ("string", _) if ty == "string" => {
+            let literal = format!("\"{}\"", value);
+            match rule {
+                "const" => check(
+                    format!("value != {}", literal),
+                    format!("must equal {}", literal),
+                ),
+                "len" => check(
+                    format!("value.chars().count() != {}", value),
+                    format!("must be {} characters long", value),
+                ),
+                "min_len" => check(
+                    format!("value.chars().count() < {}", value),
+                    format!("must be at least {} characters long", value),
+                ),
+                "max_len" => check(
+                    format!("value.chars().count() > {}", value),
+                    format!("must be at most {} characters long", value),
+                ),
+                "prefix" => check(
+                    format!("!value.starts_with({})", literal),
+                    format!("must start with {}", literal),
+                ),
+                "suffix" => check(
+                    format!("!value.ends_with({})", literal),
+                    format!("must end with {}", literal),
+                ),
+                "contains" => check(
+                    format!("!value.contains({})", literal),
+                    format!("must contain {}", literal),
+                ),
+                "pattern" => check(
+                    format!(
+                        r#"{{
+                            static RE: ::std::sync::OnceLock<::regex::Regex> = ::std::sync::OnceLock::new();
+                            !RE.get_or_init(|| ::regex::Regex::new({}).unwrap()).is_match(value)
+                        }}"#,
+                        literal
+                    ),
+                    format!("must match {}", literal),
+                ),
+                _ => unsupported(),
+            }
+        }
+        ("bytes", _) if ty == "bytes" => match rule {
+            "len" => check(
+                format!("value.len() != {}", value),
+                format!("must be {} bytes long", value),
+            ),
+            "min_len" => check(
+                format!("value.len() < {}", value),
+                format!("must be at least {} bytes long", value),
+            ),
+            "max_len" => check(
+                format!("value.len() > {}", value),
+                format!("must be at most {} bytes long", value),
+            ),
+            _ => unsupported(),
+        },
+        _ if kind == ty && ty != "bool" && ty != "message" && ty != "enum" => {
+            let literal = format!("{}{}", value, scalar_type(ty));
+            let (op, reason) = match rule {
+                "const" => ("!=", "equal"),
+                "lt" => (">=", "be less than"),
+                "lte" => (">", "be less than or equal to"),
+                "gt" => ("<=", "be greater than"),
+                "gte" => ("<", "be greater than or equal to"),
+                _ => return unsupported(),
+            };
+            check(
+                format!("*value {} {}", op, literal),
+                format!("must {} {}", reason, value),
+            )
+        }
+        _ => unsupported(),
+    }
+}
+
+/// `Debug` from `derivative` instead of prost, leaving out the ignored fields
+pub(crate) fn derivative_debug(path: &str, ignore_fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = ignore_fields.iter().map(|f| f.to_string()).collect();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        attrs.push(Attribute::SkipDebug(path.clone()));
+        attrs.push(Attribute::Type(
+            path.clone(),
+            derivative_debug_attr().to_string(),
+        ));
+        for name in &fields {
+            field(msg, &path, name)?;
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                r#"#[derivative(Debug = "ignore")]"#.to_string(),
+            ));
+        }
+        Ok(())
+    })
+}
+
+const DECIMAL_STRING: &str = r#"
+/// `serde_with` adapter for decimal numbers kept in a `String`: deserialization rejects values
+/// that aren't a valid [`::rust_decimal::Decimal`] and normalizes the others.
+pub struct DecimalString;
+
+impl ::serde_with::SerializeAs<::prost::alloc::string::String> for DecimalString {
+    fn serialize_as<S>(
+        source: &::prost::alloc::string::String,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_str(source)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost::alloc::string::String> for DecimalString {
+    fn deserialize_as<D>(
+        deserializer: D,
+    ) -> ::core::result::Result<::prost::alloc::string::String, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let value = <::prost::alloc::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+        let decimal: ::rust_decimal::Decimal = value.parse().map_err(::serde::de::Error::custom)?;
+        Ok(decimal.to_string())
+    }
+}
+"#;
+
+/// decimal string fields, validated with `rust_decimal` on deserialization
+pub(crate) fn decimal_fields(path: &str, fields: &[&str]) -> Extension {
+    scalar_adapter_fields(path, fields, &["string"], "DecimalString", DECIMAL_STRING)
+}
+
+const IP_ADDR_STRING: &str = r#"
+/// `serde_with` adapter for IP addresses kept in a `String`: deserialization rejects values
+/// that aren't a valid [`::std::net::IpAddr`] and normalizes the others.
+pub struct IpAddrString;
+
+impl ::serde_with::SerializeAs<::prost::alloc::string::String> for IpAddrString {
+    fn serialize_as<S>(
+        source: &::prost::alloc::string::String,
+        serializer: S,
+    ) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_str(source)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, ::prost::alloc::string::String> for IpAddrString {
+    fn deserialize_as<D>(
+        deserializer: D,
+    ) -> ::core::result::Result<::prost::alloc::string::String, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let value = <::prost::alloc::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+        let ip: ::std::net::IpAddr = value.parse().map_err(::serde::de::Error::custom)?;
+        Ok(ip.to_string())
+    }
+}
+"#;
+
+/// IP address string fields, validated with `std::net::IpAddr` on deserialization
+pub(crate) fn ip_fields(path: &str, fields: &[&str]) -> Extension {
+    scalar_adapter_fields(path, fields, &["string"], "IpAddrString", IP_ADDR_STRING)
+}
+
+const PERCENTAGE: &str = r#"
+/// `serde_with` adapter (de)serializing a ratio (e.g. `0.42`) as a percentage string (`"42%"`).
+/// Percentages are rounded to 4 decimal places. Deserialization rejects strings without a
+/// trailing `%` and numbers that aren't finite.
+pub struct Percentage;
+
+impl Percentage {
+    fn format(ratio: f64) -> ::core::option::Option<::prost::alloc::string::String> {
+        if !ratio.is_finite() {
+            return None;
+        }
+        let percent = ::prost::alloc::format!("{:.4}", ratio * 100.0);
+        let percent = percent.trim_end_matches('0').trim_end_matches('.');
+        let percent = if percent == "-0" { "0" } else { percent };
+        Some(::prost::alloc::format!("{}%", percent))
+    }
+
+    fn parse<E: ::serde::de::Error>(value: &str) -> ::core::result::Result<f64, E> {
+        let percent: f64 = value
+            .strip_suffix('%')
+            .and_then(|number| number.trim().parse().ok())
+            .filter(|percent: &f64| percent.is_finite())
+            .ok_or_else(|| E::custom(::prost::alloc::format!("`{}` is not a percentage", value)))?;
+        Ok(percent / 100.0)
+    }
+}
+
+impl ::serde_with::SerializeAs<f64> for Percentage {
+    fn serialize_as<S>(source: &f64, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        let percent = Self::format(*source)
+            .ok_or_else(|| <S::Error as ::serde::ser::Error>::custom("ratio is not finite"))?;
+        serializer.serialize_str(&percent)
+    }
+}
+
+impl ::serde_with::SerializeAs<f32> for Percentage {
+    fn serialize_as<S>(source: &f32, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        <Self as ::serde_with::SerializeAs<f64>>::serialize_as(&f64::from(*source), serializer)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, f64> for Percentage {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<f64, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let value = <::prost::alloc::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+        Self::parse(&value)
+    }
+}
+
+impl<'de> ::serde_with::DeserializeAs<'de, f32> for Percentage {
+    fn deserialize_as<D>(deserializer: D) -> ::core::result::Result<f32, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let value = <::prost::alloc::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+        Self::parse(&value).map(|ratio| ratio as f32)
+    }
+}
+"#;
+
+/// `double` / `float` ratios (de)serialized as percentage strings
+pub(crate) fn percentage_fields(path: &str, fields: &[&str]) -> Extension {
+    scalar_adapter_fields(path, fields, &["double", "float"], "Percentage", PERCENTAGE)
+}
+
+/// `serde_as` with the `adapter` struct defined in `code` on fields of the scalar `types`,
+/// wrapped in `Option` or `Vec` as the label requires
+fn scalar_adapter_fields(
+    path: &str,
+    fields: &[&str],
+    types: &'static [&'static str],
+    adapter: &'static str,
+    code: &'static str,
+) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    let package_path = path.clone();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        attrs.push(Attribute::Type(path.clone(), serde_as_attr().to_string()));
+        for name in &fields {
+            let f = field(msg, &path, name)?;
+            if !types.contains(&f.ty.as_str()) || f.key.is_some() || f.oneof.is_some() {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a {} field",
+                    name,
+                    path,
+                    types.join(" or ")
+                )));
+            }
+            let adapter = format!("{}{}", root_prefix(protos, &path), adapter);
+            let adapter = match f.label {
+                Label::Repeated => format!("Vec<{}>", adapter),
+                Label::Optional => format!("Option<{}>", adapter),
+                _ => adapter,
+            };
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                format!(r#"#[serde_as(as = "{}")]"#, adapter),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        let package = protos.package_of(&package_path).unwrap_or_default();
+        append_once(files, package, &format!("pub struct {};", adapter), code)
+    })
+}
+
+const SCRUB_BUCKET: &str = r#"
+/// A stable bucket id for a high-cardinality value: the hex FNV-1a hash of the string.
+pub fn scrub_bucket(value: &str) -> ::prost::alloc::string::String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    ::prost::alloc::format!("{:016x}", hash)
+}
+"#;
+
+/// `fn scrub(&self) -> Self` clearing PII fields and bucketing high-cardinality string fields
+pub(crate) fn scrub(path: &str, remove_fields: &[&str], bucket_fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let remove: Vec<String> = remove_fields.iter().map(|f| f.to_string()).collect();
+    let bucket: Vec<String> = bucket_fields.iter().map(|f| f.to_string()).collect();
+    Extension::generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        let mut body = String::new();
+        body.push_str(&clear_fields(msg, &path, "scrubbed", &remove)?);
+        for name in &bucket {
+            let f = field(msg, &path, name)?;
+            if f.ty != "string" || f.key.is_some() || f.oneof.is_some() {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a string field",
+                    name, path
+                )));
+            }
+            let ident = field_ident(name);
+            body.push_str(&match f.label {
+                Label::Repeated => format!(
+                    "scrubbed.{0} = scrubbed.{0}.iter().map(|v| scrub_bucket(v)).collect();\n",
+                    ident
+                ),
+                Label::Optional => format!(
+                    "scrubbed.{0} = scrubbed.{0}.as_deref().map(scrub_bucket);\n",
+                    ident
+                ),
+                _ => format!("scrubbed.{0} = scrub_bucket(&scrubbed.{0});\n", ident),
+            });
+        }
+        let package = protos.package_of(&path).unwrap_or_default();
+        append_once(files, package, "pub fn scrub_bucket(", SCRUB_BUCKET)?;
+        let code = format!(
+            r#"
+            impl {ty} {{
+                /// A copy of the message safe for analytics: PII fields are cleared and
+                /// high-cardinality fields replaced with a bucket id, see [`scrub_bucket`].
+                pub fn scrub(&self) -> Self {{
+                    let mut scrubbed = self.clone();
+                    {body}
+                    scrubbed
+                }}
+            }}
+            "#,
+            ty = type_path(protos, &path),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// fail unless the field type resolves to the well known / message type `expected`
+fn expect_type(
+    protos: &Protos,
+    path: &str,
+    ty: &str,
+    name: &str,
+    expected: &str,
+) -> io::Result<()> {
+    match protos.resolve(path, ty) {
+        Resolved::Message(t) | Resolved::External(t) if t == expected => Ok(()),
+        _ => Err(invalid(format!(
+            "field `{}` of `{}` is not a `{}`",
+            name, path, expected
+        ))),
+    }
+}
+
+const ENCODED_LEN_CACHE: &str = r#"
+/// A message whose `encoded_len` is computed at most once. Mutable access to the message
+/// drops the cached value.
+#[derive(Debug, Clone, Default)]
+pub struct EncodedLenCache<T> {
+    message: T,
+    encoded_len: ::std::sync::OnceLock<usize>,
+}
+
+impl<T: ::prost::Message> EncodedLenCache<T> {
+    pub fn new(message: T) -> Self {
+        Self {
+            message,
+            encoded_len: ::std::sync::OnceLock::new(),
+        }
+    }
+
+    /// The `encoded_len` of the message, computed on first use.
+    pub fn cached_encoded_len(&self) -> usize {
+        *self.encoded_len.get_or_init(|| self.message.encoded_len())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+}
+
+impl<T> ::core::ops::Deref for EncodedLenCache<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.message
+    }
+}
+
+impl<T> ::core::ops::DerefMut for EncodedLenCache<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.encoded_len.take();
+        &mut self.message
+    }
+}
+"#;
+
+/// `fn cache_encoded_len(self) -> EncodedLenCache<Self>`. prost-derive rejects fields it doesn't
+/// know, so the cache lives in a wrapper rather than in the message itself.
+pub(crate) fn encoded_len_cache(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            message(protos, path)?;
+            let package = protos.package_of(path).unwrap_or_default();
+            append_once(
+                files,
+                package,
+                "pub struct EncodedLenCache<T>",
+                ENCODED_LEN_CACHE,
+            )?;
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// Wrap the message to compute its `encoded_len` only once.
+                    pub fn cache_encoded_len(self) -> EncodedLenCache<Self> {{
+                        EncodedLenCache::new(self)
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `fn default_instance() -> &'static Self`, a lazily initialized default message
+pub(crate) fn default_instance(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            message(protos, path)?;
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// The default message, created on first use and shared afterwards.
+                    pub fn default_instance() -> &'static Self {{
+                        static INSTANCE: ::std::sync::OnceLock<{ty}> = ::std::sync::OnceLock::new();
+                        INSTANCE.get_or_init(::core::default::Default::default)
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `pub fn <field>(&self)` getters: strings as `&str`, repeated fields as slices, optional
+/// messages as `Option<&T>`, numbers and bools by value and anything else by reference. Fields
+/// prost already has a getter for (enums and optional scalars) are skipped.
+pub(crate) fn getters(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let msg = message(protos, path)?;
+            let ty = type_path(protos, path);
+            let content = file_of(protos, files, path)?;
+            let fields = parse_struct(content, &ty)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "message `{}` not found in the generated code",
+                        path
+                    ))
+                })?
+                .fields;
+            let mut getters = String::new();
+            for f in &fields {
+                let Some(ident) = &f.ident else { continue };
+                let name = ident.to_string();
+                let proto = msg
+                    .fields
+                    .iter()
+                    .find(|pf| pf.oneof.is_none() && field_ident(&pf.name) == name);
+                if let Some(pf) = proto {
+                    let prost_getter = matches!(protos.resolve(path, &pf.ty), Resolved::Enum(_))
+                        || (pf.label == Label::Optional && is_scalar(&pf.ty));
+                    if prost_getter && pf.key.is_none() {
+                        continue;
+                    }
+                }
+                let bytes = proto.is_some_and(|pf| pf.ty == "bytes" && pf.key.is_none());
+                let (ret, body) = getter(&f.ty, &name, bytes);
+                getters.push_str(&format!(
+                    "/// The `{name}` field.\npub fn {name}(&self) -> {ret} {{ {body} }}\n"
+                ));
+            }
+            let self_ty = ty.rsplit("::").next().unwrap_or_default();
+            let code = format!("impl {self_ty} {{\n{getters}}}\n");
+            insert_after(content, &ty, "struct", &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `fn into_inner(self) -> T` and `From<Wrapper> for T` on messages with a single field
+pub(crate) fn into_inner(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            message(protos, path)?;
+            let ty = type_path(protos, path);
+            let content = file_of(protos, files, path)?;
+            let fields = parse_struct(content, &ty)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "message `{}` not found in the generated code",
+                        path
+                    ))
+                })?
+                .fields;
+            let (ident, inner) = match fields.iter().next() {
+                Some(syn::Field {
+                    ident: Some(ident),
+                    ty,
+                    ..
+                }) if fields.len() == 1 => (ident.to_string(), type_string(ty)),
+                _ => return Err(invalid(format!("message `{}` is not a single field", path))),
+            };
+            let self_ty = ty.rsplit("::").next().unwrap_or_default();
+            let code = format!(
+                r#"
+                impl {self_ty} {{
+                    /// Unwrap the `{ident}` field.
+                    pub fn into_inner(self) -> {inner} {{
+                        self.{ident}
+                    }}
+                }}
+                impl ::core::convert::From<{self_ty}> for {inner} {{
+                    fn from(value: {self_ty}) -> Self {{
+                        value.{ident}
+                    }}
+                }}
+                "#
+            );
+            insert_after(content, &ty, "struct", &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// the return type and body of the getter of a field of type `ty`
+fn getter(ty: &syn::Type, name: &str, bytes: bool) -> (String, String) {
+    const BY_VALUE: &[&str] = &["i32", "i64", "u32", "u64", "f32", "f64", "bool"];
+    let rendered = type_string(ty);
+    if rendered == "::prost::alloc::string::String" {
+        return ("&str".to_string(), format!("&self.{}", name));
+    }
+    if bytes && rendered == "::prost::alloc::vec::Vec<u8>" {
+        return ("&[u8]".to_string(), format!("&self.{}", name));
+    }
+    if BY_VALUE.contains(&rendered.as_str()) {
+        return (rendered, format!("self.{}", name));
+    }
+    if let Some(inner) = type_arg(ty, "Vec") {
+        return (
+            format!("&[{}]", type_string(inner)),
+            format!("&self.{}", name),
+        );
+    }
+    if let Some(inner) = type_arg(ty, "Option") {
+        return match type_arg(inner, "Box") {
+            Some(boxed) => (
+                format!("::core::option::Option<&{}>", type_string(boxed)),
+                format!("self.{}.as_deref()", name),
+            ),
+            None => (
+                format!("::core::option::Option<&{}>", type_string(inner)),
+                format!("self.{}.as_ref()", name),
+            ),
+        };
+    }
+    (format!("&{}", rendered), format!("&self.{}", name))
+}
+
+/// the type argument of `ty` if it's a `name<T>`, e.g. `Option`
+fn type_arg<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == name => {
+            match args.args.first()? {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `ty` as prettyplease prints it
+fn type_string(ty: &syn::Type) -> String {
+    let mut alias: syn::ItemType = syn::parse_str("type T = ();").expect("valid type alias");
+    *alias.ty = ty.clone();
+    let file = syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![syn::Item::Type(alias)],
+    };
+    let code = prettyplease::unparse(&file);
+    code.trim()
+        .trim_start_matches("type T = ")
+        .trim_end_matches(';')
+        .to_string()
+}
+
+const SERIALIZE_REDACTED: &str = r#"
+/// Serialize any value as `"***"`, for fields that must not leak into serialized output.
+pub fn serialize_redacted<T, S>(_: &T, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+where
+    S: ::serde::Serializer,
+{
+    serializer.serialize_str("***")
+}
+"#;
+
+/// serialize the fields as `"***"` while still deserializing the real values
+pub(crate) fn serialize_redacted(path: &str, fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    let package_path = path.clone();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        for name in &fields {
+            field(msg, &path, name)?;
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                format!(
+                    r#"#[serde(serialize_with = "{}serialize_redacted")]"#,
+                    root_prefix(protos, &path)
+                ),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        let package = protos.package_of(&package_path).unwrap_or_default();
+        append_once(
+            files,
+            package,
+            "pub fn serialize_redacted<",
+            SERIALIZE_REDACTED,
+        )
+    })
+}
+
+const LEN_PREFIXED_BYTES: &str = r#"
+/// (De)serialize `bytes` fields with a length prefix: the value is written as a byte array
+/// made of the data length as a 4-byte big-endian `u32`, followed by the data itself, e.g.
+/// `[0, 0, 0, 2, 0xab, 0xcd]` for `[0xab, 0xcd]`. Deserialization fails if the prefix doesn't
+/// match the number of bytes that follow.
+pub mod len_prefixed_bytes {
+    pub fn serialize<T, S>(value: &T, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+    where
+        T: ::core::convert::AsRef<[u8]>,
+        S: ::serde::Serializer,
+    {
+        let data = value.as_ref();
+        let len = u32::try_from(data.len()).map_err(::serde::ser::Error::custom)?;
+        let mut buf = ::prost::alloc::vec::Vec::with_capacity(4 + data.len());
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(data);
+        serializer.serialize_bytes(&buf)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> ::core::result::Result<T, D::Error>
+    where
+        T: ::core::convert::From<::prost::alloc::vec::Vec<u8>>,
+        D: ::serde::Deserializer<'de>,
+    {
+        let buf = <::prost::alloc::vec::Vec<u8> as ::serde::Deserialize>::deserialize(deserializer)?;
+        if buf.len() < 4 {
+            return Err(::serde::de::Error::custom("missing length prefix"));
+        }
+        let (prefix, data) = buf.split_at(4);
+        let len = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if len != data.len() {
+            return Err(::serde::de::Error::custom(::prost::alloc::format!(
+                "length prefix is {} but {} bytes follow",
+                len,
+                data.len()
+            )));
+        }
+        Ok(T::from(data.to_vec()))
+    }
+}
+"#;
+
+/// (de)serialize `bytes` fields through the generated `len_prefixed_bytes` module
+pub(crate) fn len_prefixed_bytes(path: &str, fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+    let package_path = path.clone();
+    Extension::attributes(move |protos, attrs| {
+        let msg = message(protos, &path)?;
+        for name in &fields {
+            let f = field(msg, &path, name)?;
+            if f.ty != "bytes" || f.label != Label::None || f.key.is_some() || f.oneof.is_some() {
+                return Err(invalid(format!(
+                    "field `{}` of `{}` is not a singular bytes field",
+                    name, path
+                )));
+            }
+            attrs.push(Attribute::Field(
+                format!("{}.{}", path, name),
+                format!(
+                    r#"#[serde(with = "{}len_prefixed_bytes")]"#,
+                    root_prefix(protos, &path)
+                ),
+            ));
+        }
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        let package = protos.package_of(&package_path).unwrap_or_default();
+        append_once(
+            files,
+            package,
+            "pub mod len_prefixed_bytes {",
+            LEN_PREFIXED_BYTES,
+        )
+    })
+}
+
+/// `fn parse_ci(s: &str) -> Option<Self>` matching Rust and proto variant names ignoring case
+pub(crate) fn enum_parse_ci(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let e = enumeration(protos, path)?;
+            // prost drops the aliases (`allow_alias`), their names parse to the first value
+            let arms: String = variants(e)
+                .into_iter()
+                .map(|(variant, name)| {
+                    let number = e.values.iter().find(|v| v.name == name).map(|v| v.number);
+                    let names: Vec<&str> = std::iter::once(variant.trim_start_matches("r#"))
+                        .chain(
+                            e.values
+                                .iter()
+                                .filter(|v| Some(v.number) == number)
+                                .map(|v| v.name.as_str()),
+                        )
+                        .collect();
+                    let conditions: Vec<String> = names
+                        .iter()
+                        .map(|name| format!(r#"s.eq_ignore_ascii_case("{}")"#, name))
+                        .collect();
+                    format!(
+                        "s if {} => Some(Self::{}),",
+                        conditions.join(" || "),
+                        variant
+                    )
+                })
+                .collect();
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// Parse a variant from its Rust name (e.g. `Done`) or its proto name
+                    /// (e.g. `TODO_STATUS_DONE`), ignoring ASCII case.
+                    pub fn parse_ci(s: &str) -> ::core::option::Option<Self> {{
+                        match s {{
+                            {arms}
+                            _ => None,
+                        }}
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `fn to_custom_str(&self) -> &'static str` and `fn from_custom_str(s: &str) -> Option<Self>`
+/// on the enum, using the string of each `(variant, string)` mapping and the proto name for the
+/// variants without one
+pub(crate) fn enum_str_map(path: &str, mappings: &[(&str, &str)]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let mappings: Vec<(String, String)> = mappings
+        .iter()
+        .map(|(variant, s)| (variant.to_string(), s.to_string()))
+        .collect();
+    Extension::generate(move |protos, files| {
+        let variants = variants(enumeration(protos, &path)?);
+        for (variant, _) in &mappings {
+            if !variants
+                .iter()
+                .any(|(ident, name)| ident == variant || name == variant)
+            {
+                return Err(invalid(format!(
+                    "`{}` is not a value of `{}`",
+                    variant, path
+                )));
+            }
+        }
+        let strings: Vec<(&str, &str)> = variants
+            .iter()
+            .map(|(ident, name)| {
+                let s = mappings
+                    .iter()
+                    .find(|(variant, _)| variant == ident || variant == name)
+                    .map_or(*name, |(_, s)| s.as_str());
+                (ident.as_str(), s)
+            })
+            .collect();
+        for (i, (_, s)) in strings.iter().enumerate() {
+            if strings[..i].iter().any(|(_, other)| other == s) {
+                return Err(invalid(format!(
+                    "`{}` is used for more than one value of `{}`",
+                    s, path
+                )));
+            }
+        }
+        let to: String = strings
+            .iter()
+            .map(|(ident, s)| format!("Self::{} => {:?},\n", ident, s))
+            .collect();
+        let from: String = strings
+            .iter()
+            .map(|(ident, s)| format!("{:?} => Some(Self::{}),\n", s, ident))
+            .collect();
+        let code = format!(
+            r#"
+            impl {ty} {{
+                /// The custom string of the variant, its proto name if it has none.
+                pub fn to_custom_str(&self) -> &'static str {{
+                    match self {{
+                        {to}
+                    }}
+                }}
+                /// The variant with the given custom string (or proto name if it has none).
+                pub fn from_custom_str(s: &str) -> ::core::option::Option<Self> {{
+                    match s {{
+                        {from}
+                        _ => None,
+                    }}
+                }}
+            }}
+            "#,
+            ty = type_path(protos, &path),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// `fn redacted(&self) -> Self` returning a copy with the sensitive fields reset to their default
+pub(crate) fn redacted_clone(path: &str, redact_fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = redact_fields.iter().map(|f| f.to_string()).collect();
+    Extension::generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        let code = format!(
+            r#"
+            impl {ty} {{
+                /// A copy of the message with the sensitive fields reset to their default.
+                pub fn redacted(&self) -> Self {{
+                    let mut redacted = self.clone();
+                    {body}
+                    redacted
+                }}
+            }}
+            "#,
+            ty = type_path(protos, &path),
+            body = clear_fields(msg, &path, "redacted", &fields)?,
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// `impl Display` writing `template`, with each `{field}` (or `{field:spec}`, e.g. `{field:?}`)
+/// placeholder replaced by that field of the message. `{{` and `}}` are literal braces.
+pub(crate) fn display_template(path: &str, template: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let template = template.to_string();
+    Extension::generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        let bad = |reason: &str| invalid(format!("{} in the template of `{}`", reason, path));
+        let mut format = String::new();
+        let mut args = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    format.extend([c, c]);
+                }
+                '}' => return Err(bad("unmatched `}`")),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(bad("unclosed `{`")),
+                        }
+                    }
+                    let (name, spec) = match placeholder.split_once(':') {
+                        Some((name, spec)) => (name, format!(":{}", spec)),
+                        None => (placeholder.as_str(), String::new()),
+                    };
+                    if !msg.oneofs.iter().any(|o| o == name)
+                        && field(msg, &path, name)?.oneof.is_some()
+                    {
+                        return Err(invalid(format!(
+                            "oneof field `{}` of `{}` is not supported",
+                            name, path
+                        )));
+                    }
+                    format.push_str(&format!("{{{}}}", spec));
+                    args.push_str(&format!(", self.{}", field_ident(name)));
+                }
+                c => format.push(c),
+            }
+        }
+        let code = format!(
+            r#"
+            impl ::core::fmt::Display for {ty} {{
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                    write!(f, {format:?}{args})
+                }}
+            }}
+            "#,
+            ty = type_path(protos, &path),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// `impl Debug` printing the given fields as `"***"`, with prost's own `Debug` skipped. Enum
+/// fields are printed as their `i32`, unlike prost's impl which prints the variant name.
+pub(crate) fn redacted_debug(path: &str, redact_fields: &[&str]) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let fields: Vec<String> = redact_fields.iter().map(|f| f.to_string()).collect();
+    let skip_path = path.clone();
+    Extension::attributes(move |_, attrs| {
+        attrs.push(Attribute::SkipDebug(skip_path.clone()));
+        Ok(())
+    })
+    .and_generate(move |protos, files| {
+        let msg = message(protos, &path)?;
+        for name in &fields {
+            if field(msg, &path, name)?.oneof.is_some() {
+                return Err(invalid(format!(
+                    "oneof field `{}` of `{}` is not supported",
+                    name, path
+                )));
+            }
+        }
+        let ty = type_path(protos, &path);
+        let names = msg
+            .fields
+            .iter()
+            .filter(|f| f.oneof.is_none())
+            .map(|f| &f.name)
+            .chain(msg.oneofs.iter());
+        let mut body = String::new();
+        for name in names {
+            let ident = field_ident(name);
+            let value = if fields.contains(name) {
+                "&\"***\"".to_string()
+            } else {
+                format!("&self.{}", ident)
+            };
+            body.push_str(&format!(
+                ".field({:?}, {})\n",
+                ident.trim_start_matches("r#"),
+                value
+            ));
+        }
+        let code = format!(
+            r#"
+            impl ::core::fmt::Debug for {ty} {{
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                    f.debug_struct({name:?})
+                        {body}
+                        .finish()
+                }}
+            }}
+            "#,
+            name = ty.rsplit("::").next().unwrap_or_default(),
+        );
+        append(protos, files, &path, &code)
+    })
+}
+
+/// statements resetting the fields of the message bound to `var`
+fn clear_fields(msg: &Message, path: &str, var: &str, fields: &[String]) -> io::Result<String> {
+    let mut body = String::new();
+    for name in fields {
+        let f = field(msg, path, name)?;
+        if f.oneof.is_some() {
+            return Err(invalid(format!(
+                "oneof field `{}` of `{}` is not supported",
+                name, path
+            )));
+        }
+        body.push_str(&format!(
+            "{}.{} = Default::default();\n",
+            var,
+            field_ident(name)
+        ));
+    }
+    Ok(body)
+}
+
+/// `struct <Type>Patch` with every field wrapped in an `Option`, and
+/// `fn apply_patch(&mut self, patch: <Type>Patch)` overwriting the fields that are set. The
+/// field types are taken from the generated struct, so they match whatever prost emitted.
+pub(crate) fn partial_type(path: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    Extension::generate(move |protos, files| {
+        message(protos, &path)?;
+        if protos.message(&format!("{}Patch", path)).is_some() {
+            return Err(invalid(format!("`{}Patch` is already defined", path)));
+        }
+        let ty = type_path(protos, &path);
+        let content = file_of(protos, files, &path)?;
+        let mut fields = parse_struct(content, &ty)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "message `{}` not found in the generated code",
+                    path
+                ))
+            })?
+            .fields;
+        let self_ty = ty.rsplit("::").next().unwrap_or_default();
+        let name = format!("{}Patch", self_ty);
+        let mut assignments = String::new();
+        for f in fields.iter_mut() {
+            if let Some(ident) = &f.ident {
+                assignments.push_str(&format!(
+                    "if let Some(value) = patch.{0} {{ self.{0} = value; }}\n",
+                    ident
+                ));
+            }
+            f.attrs.retain(|a| a.path().is_ident("doc"));
+            f.ty = option_of(f.ty.clone());
+        }
+        let mut patch: syn::ItemStruct = syn::parse_str(&format!(
+            r#"
+            /// A partial update of [`{self_ty}`]: `apply_patch` only overwrites the fields
+            /// that are set.
+            #[derive(Clone, PartialEq, Debug, Default)]
+            pub struct {name} {{}}
+            "#
+        ))
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", path, e)))?;
+        patch.fields = fields;
+        let patch = syn::File {
+            shebang: None,
+            attrs: Vec::new(),
+            items: vec![syn::Item::Struct(patch)],
+        };
+        let code = format!(
+            r#"
+            {patch}
+            impl {self_ty} {{
+                /// Overwrite the fields set in `patch`, keeping the others.
+                pub fn apply_patch(&mut self, patch: {name}) {{
+                    {assignments}
+                }}
+            }}
+            "#,
+            patch = prettyplease::unparse(&patch),
+        );
+        insert_after(content, &ty, "struct", &code)
+    })
+}
+
+/// `struct <mirror>`, a copy of the message fields with `#[serde(remote = "<Type>")]`, to
+/// (de)serialize the message through `#[serde(with = "<mirror>")]` without deriving serde on it
+pub(crate) fn serde_remote(path: &str, mirror_name: &str) -> Extension {
+    let path = path.trim_start_matches('.').to_string();
+    let mirror_name = mirror_name.to_string();
+    Extension::generate(move |protos, files| {
+        message(protos, &path)?;
+        let sibling = match path.rsplit_once('.') {
+            Some((parent, _)) => format!("{}.{}", parent, mirror_name),
+            None => mirror_name.clone(),
+        };
+        if protos.message(&sibling).is_some() || protos.enumeration(&sibling).is_some() {
+            return Err(invalid(format!("`{}` is already defined", sibling)));
+        }
+        let ty = type_path(protos, &path);
+        let content = file_of(protos, files, &path)?;
+        let mut fields = parse_struct(content, &ty)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "message `{}` not found in the generated code",
+                    path
+                ))
+            })?
+            .fields;
+        for f in fields.iter_mut() {
+            f.attrs
+                .retain(|a| a.path().is_ident("doc") || a.path().is_ident("serde"));
+        }
+        let self_ty = ty.rsplit("::").next().unwrap_or_default();
+        let mut mirror: syn::ItemStruct = syn::parse_str(&format!(
+            r#"
+            /// Serde mirror of [`{self_ty}`], use it with `#[serde(with = "{mirror_name}")]`.
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(remote = "{self_ty}")]
+            pub struct {mirror_name} {{}}
+            "#
+        ))
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", path, e)))?;
+        mirror.fields = fields;
+        let code = prettyplease::unparse(&syn::File {
+            shebang: None,
+            attrs: Vec::new(),
+            items: vec![syn::Item::Struct(mirror)],
+        });
+        insert_after(content, &ty, "struct", &code)
+    })
+}
+
+/// `struct <Type>Cow<'a>`, a copy of the message with the given string fields (e.g.
+/// `todo.Todo.title`) as `Cow<'a, str>`, and `From<<Type>Cow<'_>> for <Type>`. prost's derive
+/// only encodes `String`s, so the message itself keeps them.
+pub(crate) fn cow_fields(fields: &[&str]) -> Extension {
+    let messages = fields_by_message(fields);
+    Extension::generate(move |protos, files| {
+        for (path, names) in &messages {
+            let (content, ty, mut fields) = companion_fields(protos, files, path, "Cow")?;
+            let idents: Vec<String> = names.iter().map(|n| field_ident(n)).collect();
+            let mut conversions = String::new();
+            for f in fields.iter_mut() {
+                let Some(ident) = f.ident.as_ref().map(|i| i.to_string()) else {
+                    continue;
+                };
+                if idents.contains(&ident) {
+                    f.ty = syn::parse_str("::std::borrow::Cow<'a, str>").expect("valid type");
+                    conversions.push_str(&format!("{0}: value.{0}.into_owned(),\n", ident));
+                } else {
+                    conversions.push_str(&format!("{0}: value.{0},\n", ident));
+                }
+            }
+            let self_ty = ty.rsplit("::").next().unwrap_or_default();
+            let name = format!("{}Cow", self_ty);
+            let cow = companion_struct(
+                path,
+                &format!(
+                    r#"
+                    /// [`{self_ty}`] borrowing its string fields, e.g. while parsing. Convert it
+                    /// into the message to encode it.
+                    #[derive(Clone, PartialEq, Debug, Default)]
+                    pub struct {name}<'a> {{}}
+                    "#
+                ),
+                fields,
+            )?;
+            let code = format!(
+                r#"
+                {cow}
+                impl ::core::convert::From<{name}<'_>> for {self_ty} {{
+                    fn from(value: {name}<'_>) -> Self {{
+                        Self {{
+                            {conversions}
+                        }}
+                    }}
+                }}
+                "#
+            );
+            insert_after(content, &ty, "struct", &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `struct <Type>Secret`, a copy of the message with the given string fields (e.g.
+/// `account.Account.token`) as `secrecy::Secret<String>`, and `From` both ways. prost's derive
+/// only encodes `String`s and forces `PartialEq`, which `Secret` doesn't implement, so the message
+/// itself keeps them and the copy only derives `Debug`.
+pub(crate) fn secret_fields(fields: &[&str]) -> Extension {
+    let messages = fields_by_message(fields);
+    Extension::generate(move |protos, files| {
+        for (path, names) in &messages {
+            let (content, ty, mut fields) = companion_fields(protos, files, path, "Secret")?;
+            let idents: Vec<String> = names.iter().map(|n| field_ident(n)).collect();
+            let mut exposed = String::new();
+            let mut wrapped = String::new();
+            for f in fields.iter_mut() {
+                let Some(ident) = f.ident.as_ref().map(|i| i.to_string()) else {
+                    continue;
+                };
+                if idents.contains(&ident) {
+                    f.ty = syn::parse_str("::secrecy::Secret<::prost::alloc::string::String>")
+                        .expect("valid type");
+                    exposed.push_str(&format!(
+                        "{0}: ::secrecy::ExposeSecret::expose_secret(&value.{0}).clone(),\n",
+                        ident
+                    ));
+                    wrapped.push_str(&format!("{0}: ::secrecy::Secret::new(value.{0}),\n", ident));
+                } else {
+                    exposed.push_str(&format!("{0}: value.{0},\n", ident));
+                    wrapped.push_str(&format!("{0}: value.{0},\n", ident));
+                }
+            }
+            let self_ty = ty.rsplit("::").next().unwrap_or_default();
+            let name = format!("{}Secret", self_ty);
+            let secret = companion_struct(
+                path,
+                &format!(
+                    r#"
+                    /// [`{self_ty}`] with its sensitive fields kept in `secrecy::Secret`. Convert
+                    /// it into the message to encode it.
+                    #[derive(Debug)]
+                    pub struct {name} {{}}
+                    "#
+                ),
+                fields,
+            )?;
+            let code = format!(
+                r#"
+                {secret}
+                impl ::core::convert::From<{name}> for {self_ty} {{
+                    fn from(value: {name}) -> Self {{
+                        Self {{
+                            {exposed}
+                        }}
+                    }}
+                }}
+                impl ::core::convert::From<{self_ty}> for {name} {{
+                    fn from(value: {self_ty}) -> Self {{
+                        Self {{
+                            {wrapped}
+                        }}
+                    }}
+                }}
+                "#
+            );
+            insert_after(content, &ty, "struct", &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// for each `(field, newtype)`, e.g. `("todo.Todo.id", "TodoId")`, `struct TodoId(String)` next
+/// to the message with conversions from and into `String`, and `Todo::typed_id()`. prost's
+/// derive encodes and defaults the field as a `String`, so the field itself keeps the type.
+pub(crate) fn typed_ids(fields: &[(&str, &str)]) -> Extension {
+    let fields: Vec<(String, String)> = fields
+        .iter()
+        .map(|(path, name)| (path.trim_start_matches('.').to_string(), name.to_string()))
+        .collect();
+    Extension::generate(move |protos, files| {
+        for (path, newtype) in &fields {
+            let (msg, name) = path.rsplit_once('.').unwrap_or(("", path));
+            message(protos, msg)?;
+            let ty = type_path(protos, msg);
+            let self_ty = ty.rsplit("::").next().unwrap_or_default();
+            let ident = field_ident(name);
+            let content = file_of(protos, files, msg)?;
+            let declared = content.contains(&format!("pub struct {}(", newtype));
+            let newtype_code = if declared {
+                String::new()
+            } else {
+                format!(
+                    r#"
+                    /// a `string` id, kept apart from the other ids by its type
+                    #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+                    pub struct {newtype}(pub ::prost::alloc::string::String);
+                    impl ::core::convert::From<::prost::alloc::string::String> for {newtype} {{
+                        fn from(value: ::prost::alloc::string::String) -> Self {{
+                            Self(value)
+                        }}
+                    }}
+                    impl ::core::convert::From<{newtype}> for ::prost::alloc::string::String {{
+                        fn from(value: {newtype}) -> Self {{
+                            value.0
+                        }}
+                    }}
+                    impl ::core::fmt::Display for {newtype} {{
+                        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                            f.write_str(&self.0)
+                        }}
+                    }}
+                    "#
+                )
+            };
+            let code = format!(
+                r#"
+                {newtype_code}
+                impl {self_ty} {{
+                    /// `{name}` as a [`{newtype}`]
+                    pub fn typed_{name}(&self) -> {newtype} {{
+                        {newtype}(self.{ident}.clone())
+                    }}
+                }}
+                "#,
+                name = ident.trim_start_matches("r#"),
+            );
+            insert_after(content, &ty, "struct", &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// the field names (e.g. `todo.Todo.title`) grouped by message, in order
+fn fields_by_message(fields: &[&str]) -> Vec<(String, Vec<String>)> {
+    let mut messages: Vec<(String, Vec<String>)> = Vec::new();
+    for path in fields {
+        let (msg, name) = path
+            .trim_start_matches('.')
+            .rsplit_once('.')
+            .unwrap_or(("", path));
+        match messages.iter_mut().find(|(m, _)| m == msg) {
+            Some((_, names)) => names.push(name.to_string()),
+            None => messages.push((msg.to_string(), vec![name.to_string()])),
+        }
+    }
+    messages
+}
+
+/// the generated file, type path and fields (with their doc comments only) of the message, for
+/// a `<Type><suffix>` copy of it
+fn companion_fields<'a>(
+    protos: &Protos,
+    files: &'a mut Files,
+    path: &str,
+    suffix: &str,
+) -> io::Result<(&'a mut String, String, syn::Fields)> {
+    message(protos, path)?;
+    if protos.message(&format!("{}{}", path, suffix)).is_some() {
+        return Err(invalid(format!("`{}{}` is already defined", path, suffix)));
+    }
+    let ty = type_path(protos, path);
+    let content = file_of(protos, files, path)?;
+    let mut fields = parse_struct(content, &ty)
+        .ok_or_else(|| {
+            invalid(format!(
+                "message `{}` not found in the generated code",
+                path
+            ))
+        })?
+        .fields;
+    for f in fields.iter_mut() {
+        f.attrs.retain(|a| a.path().is_ident("doc"));
+    }
+    Ok((content, ty, fields))
+}
+
+/// the struct declared (without fields) by `header`, formatted with `fields`
+fn companion_struct(path: &str, header: &str, fields: syn::Fields) -> io::Result<String> {
+    let mut item: syn::ItemStruct = syn::parse_str(header)
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", path, e)))?;
+    item.fields = fields;
+    Ok(prettyplease::unparse(&syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![syn::Item::Struct(item)],
+    }))
+}
+
+/// `::core::option::Option<ty>`
+fn option_of(ty: syn::Type) -> syn::Type {
+    let mut option: syn::TypePath =
+        syn::parse_str("::core::option::Option<()>").expect("valid type");
+    if let Some(syn::PathArguments::AngleBracketed(args)) = option
+        .path
+        .segments
+        .last_mut()
+        .map(|segment| &mut segment.arguments)
+    {
+        args.args[0] = syn::GenericArgument::Type(ty);
+    }
+    syn::Type::Path(option)
+}
+
+/// `fn checksum(&self) -> u64`, the FNV-1a hash of the encoded message
+pub(crate) fn checksum(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            message(protos, path)?;
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// A stable hash of the message content: the FNV-1a hash of its encoded
+                    /// bytes. Map fields are encoded in iteration order, so messages with
+                    /// `HashMap` fields only hash the same if their maps iterate the same way.
+                    pub fn checksum(&self) -> u64 {{
+                        ::prost::Message::encode_to_vec(self)
+                            .iter()
+                            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {{
+                                (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+                            }})
+                    }}
+                }}
+                "#,
+                ty = type_path(protos, path),
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// `fn mock() -> Self` filling every field with a deterministic placeholder: strings hold the
+/// field name, numbers the field number, enums their last value, nested messages their default
+pub(crate) fn mock(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let msg = message(protos, path)?;
+            let ty = type_path(protos, path);
+            let mut values = String::new();
+            let mut oneofs = Vec::new();
+            for f in &msg.fields {
+                let value = mock_value(protos, path, &f.ty, f)?;
+                let value = match (f.oneof, &f.key, f.label) {
+                    (Some(i), _, _) if oneofs.contains(&i) => continue,
+                    (Some(i), _, _) => {
+                        oneofs.push(i);
+                        let name = &msg.oneofs[i];
+                        values.push_str(&format!(
+                            "{}: Some({}::{}({})),\n",
+                            field_ident(name),
+                            oneof_type(&ty, name),
+                            f.name.to_upper_camel_case(),
+                            value
+                        ));
+                        continue;
+                    }
+                    (None, Some(key), _) => format!(
+                        "[({}, {})].into_iter().collect()",
+                        mock_value(protos, path, key, f)?,
+                        value
+                    ),
+                    (None, None, Label::Repeated) => format!("::prost::alloc::vec![{}]", value),
+                    // prost wraps singular messages in an `Option` whatever their label
+                    (None, None, Label::Optional) => format!("Some({})", value),
+                    (None, None, _) if is_message(protos, path, &f.ty) => {
+                        format!("Some({})", value)
+                    }
+                    _ => value,
+                };
+                values.push_str(&format!("{}: {},\n", field_ident(&f.name), value));
+            }
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    /// A message with deterministic placeholder values, for tests.
+                    pub fn mock() -> Self {{
+                        Self {{
+                            {values}
+                        }}
+                    }}
+                }}
+                "#
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// the Rust path of the enum prost generates for a oneof of the message at `ty`
+fn oneof_type(ty: &str, oneof: &str) -> String {
+    let module = match ty.rsplit_once("::") {
+        Some((parent, name)) => format!("{}::{}", parent, name.to_snake_case()),
+        None => ty.to_snake_case(),
+    };
+    format!("{}::{}", module, oneof.to_upper_camel_case())
+}
+
+fn is_message(protos: &Protos, path: &str, ty: &str) -> bool {
+    matches!(
+        protos.resolve(path, ty),
+        Resolved::Message(_) | Resolved::External(_)
+    )
+}
+
+/// the placeholder of a single value of type `ty` for the field `f`
+fn mock_value(protos: &Protos, path: &str, ty: &str, f: &Field) -> io::Result<String> {
+    Ok(match protos.resolve(path, ty) {
+        Resolved::Scalar(s) => match s.as_str() {
+            "string" => format!("::prost::alloc::string::String::from({:?})", f.name),
+            "bytes" => format!("::core::convert::Into::into(b{:?}.to_vec())", f.name),
+            "bool" => "true".to_string(),
+            "double" | "float" => format!("{}.0", f.number),
+            _ => f.number.to_string(),
+        },
+        Resolved::Enum(e) => enumeration(protos, &e)?
+            .values
+            .last()
+            .map(|v| v.number.to_string())
+            .unwrap_or_else(|| "0".to_string()),
+        Resolved::Message(_) | Resolved::External(_) => {
+            "::core::default::Default::default()".to_string()
+        }
+    })
+}
+
+/// `fn present_fields(&self) -> u64` with a bit set for every field holding a non-default
+/// value, and a `<FIELD>_BIT` constant with the position of each field
+pub(crate) fn presence_bitset(paths: &[&str]) -> Extension {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &paths {
+            let msg = message(protos, path)?;
+            if msg.fields.len() > 64 {
+                return Err(invalid(format!(
+                    "`{}` has {} fields, a presence bitset holds at most 64",
+                    path,
+                    msg.fields.len()
+                )));
+            }
+            let ty = type_path(protos, path);
+            let mut consts = String::new();
+            let mut checks = String::new();
+            for (bit, f) in msg.fields.iter().enumerate() {
+                let ident = field_ident(&f.name);
+                let name = format!("{}_BIT", f.name.to_shouty_snake_case());
+                let present = match (f.oneof, &f.key, f.label) {
+                    (Some(i), _, _) => format!(
+                        "matches!(self.{}, Some({}::{}(_)))",
+                        field_ident(&msg.oneofs[i]),
+                        oneof_type(&ty, &msg.oneofs[i]),
+                        f.name.to_upper_camel_case()
+                    ),
+                    (None, Some(_), _) | (None, None, Label::Repeated) => {
+                        format!("!self.{}.is_empty()", ident)
+                    }
+                    (None, None, Label::Optional) => format!("self.{}.is_some()", ident),
+                    _ => match protos.resolve(path, &f.ty) {
+                        Resolved::Scalar(s) => match s.as_str() {
+                            "string" | "bytes" => format!("!self.{}.is_empty()", ident),
+                            "bool" => format!("self.{}", ident),
+                            "double" | "float" => format!("self.{} != 0.0", ident),
+                            _ => format!("self.{} != 0", ident),
+                        },
+                        Resolved::Enum(_) => format!("self.{} != 0", ident),
+                        Resolved::Message(_) | Resolved::External(_) => {
+                            format!("self.{}.is_some()", ident)
+                        }
+                    },
+                };
+                consts.push_str(&format!(
+                    "/// The bit of `{}` in [`Self::present_fields`].\npub const {}: u32 = {};\n",
+                    f.name, name, bit
+                ));
+                checks.push_str(&format!(
+                    "if {} {{ bits |= 1 << Self::{}; }}\n",
+                    present, name
+                ));
+            }
+            let code = format!(
+                r#"
+                impl {ty} {{
+                    {consts}
+                    /// A bitset of the fields holding a non-default value, see the `*_BIT`
+                    /// constants for their position.
+                    pub fn present_fields(&self) -> u64 {{
+                        let mut bits = 0;
+                        {checks}
+                        bits
+                    }}
+                }}
+                "#
+            );
+            append(protos, files, path, &code)?;
+        }
+        Ok(())
+    })
+}
+
+/// run the calls the generated `*_server` modules make to the service methods inside a
+/// `tracing` span named after the method. The trait methods themselves have no body to put
+/// `#[tracing::instrument]` on, so the span is entered around the call instead.
+#[cfg(feature = "tonic")]
+pub(crate) fn instrument(services: &[&str]) -> Extension {
+    let services: Vec<String> = services
+        .iter()
+        .map(|s| s.trim_start_matches('.').to_string())
+        .collect();
+    Extension::generate(move |protos, files| {
+        for path in &services {
+            let service = protos
+                .service(path)
+                .ok_or_else(|| invalid(format!("service `{}` not found", path)))?;
+            let content = file_of(protos, files, path)?;
+            let name = service.name.to_upper_camel_case();
+            for method in &service.methods {
+                let method = method.name.to_snake_case();
+                let call = format!("<T as {}>::{}(&inner, request)", name, method);
+                let from = format!("{}.await", call);
+                if !content.contains(&from) {
+                    return Err(invalid(format!(
+                        "server of `{}` not found in the generated code, is `build_server` off?",
+                        path
+                    )));
+                }
+                let to = format!(
+                    "tracing::Instrument::instrument({}, tracing::info_span!({:?}, service = {:?})).await",
+                    call, method, path
+                );
+                *content = content.replace(&from, &to);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// a `metrics::counter!("<namespace>_requests_total", ..)` increment, labelled with the service
+/// and method, before every call the generated servers make to the service methods
+#[cfg(feature = "tonic")]
+pub(crate) fn method_counters(namespace: &str) -> Extension {
+    let namespace = namespace.to_string();
+    Extension::generate(move |protos, files| {
+        for (path, service) in protos.services() {
+            let content = file_of(protos, files, &path)?;
+            let name = service.name.to_upper_camel_case();
+            for method in &service.methods {
+                let method = method.name.to_snake_case();
+                let call = format!("<T as {}>::{}(&inner, request)", name, method);
+                let Some(pos) = content.find(&call) else {
+                    return Err(invalid(format!(
+                        "server of `{}` not found in the generated code, is `build_server` off?",
+                        path
+                    )));
+                };
+                let line = content[..pos].rfind('\n').map_or(0, |i| i + 1);
+                let indent: String = content[line..].chars().take_while(|c| *c == ' ').collect();
+                let counter = format!(
+                    "{}metrics::counter!(\"{}_requests_total\", \"service\" => {:?}, \"method\" => {:?}).increment(1);\n",
+                    indent, namespace, path, method
+                );
+                content.insert_str(line, &counter);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// a `<service>_paths` module of `pub const` request paths (e.g.
+/// `"/todo.TodoService/CreateTodo"`) for every method of every service
+#[cfg(feature = "tonic")]
+pub(crate) fn method_path_consts() -> Extension {
+    Extension::generate(|protos, files| {
+        for file in &protos.files {
+            for service in &file.services {
+                let path = match file.package.as_str() {
+                    "" => service.name.clone(),
+                    package => format!("{}.{}", package, service.name),
+                };
+                let module = format!("{}_paths", service.name.to_snake_case());
+                let consts: String = service
+                    .methods
+                    .iter()
+                    .map(|m| {
+                        format!(
+                            "/// request path of `{}`\npub const {}: &str = \"/{}/{}\";\n",
+                            m.name,
+                            m.name.to_shouty_snake_case(),
+                            path,
+                            m.name
+                        )
+                    })
+                    .collect();
+                let code = format!(
+                    "/// request paths of the methods of `{}`\npub mod {} {{\n{}}}\n",
+                    path, module, consts
+                );
+                append_once(
+                    files,
+                    &file.package,
+                    &format!("pub mod {} {{", module),
+                    &code,
+                )?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// `#[allow(missing_docs)]` on every top-level item of the generated files. `include!` rejects
+/// inner attributes, so the files can't start with `#![allow(missing_docs)]`.
+pub(crate) fn allow_missing_docs() -> Extension {
+    Extension::generate(|_, files| {
+        for content in files.values_mut() {
+            *content = allow_top_level(content, "#[allow(missing_docs)]");
+        }
+        Ok(())
+    })
+}
+
+/// put `attr` before the doc comments and attributes of every item that starts at the top level
+/// of the prettyplease formatted `content`
+fn allow_top_level(content: &str, attr: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut item_start = true;
+    for line in content.lines() {
+        let top_level = !line.is_empty() && !line.starts_with(char::is_whitespace);
+        let comment = line.starts_with("//") && !line.starts_with("///");
+        if top_level && item_start && !comment {
+            out.push_str(attr);
+            out.push('\n');
+            item_start = false;
+        }
+        if top_level && (line.ends_with('}') || line.ends_with(';')) {
+            item_start = true;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// print a `cargo:warning` for every trait derived more than once on the same type, which
+/// otherwise fails deep in the generated code with conflicting implementations
+pub(crate) fn warn_duplicate_derives() -> Extension {
+    Extension::generate(|_, files| {
+        for (package, content) in files.iter() {
+            for warning in duplicate_derives(package, content) {
+                println!("cargo:warning={}", warning);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// the traits derived more than once on the structs and enums of a generated file, compared
+/// by their last path segment (e.g. `Clone` and `::core::clone::Clone`)
+pub(crate) fn duplicate_derives(package: &str, content: &str) -> Vec<String> {
+    fn walk(items: &[syn::Item], scope: &str, warnings: &mut Vec<String>) {
+        for item in items {
+            let (ident, attrs) = match item {
+                syn::Item::Struct(s) => (&s.ident, &s.attrs),
+                syn::Item::Enum(e) => (&e.ident, &e.attrs),
+                syn::Item::Mod(m) => {
+                    if let Some((_, items)) = &m.content {
+                        walk(items, &format!("{}::{}", scope, m.ident), warnings);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let mut derives = Vec::new();
+            for attr in attrs.iter().filter(|a| a.path().is_ident("derive")) {
+                let paths = attr.parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+                );
+                let paths: Vec<syn::Path> =
+                    paths.map(|p| p.into_iter().collect()).unwrap_or_default();
+                for path in paths {
+                    let Some(name) = path.segments.last().map(|s| s.ident.to_string()) else {
+                        continue;
+                    };
+                    if derives.contains(&name) {
+                        warnings.push(format!(
+                            "`{}` is derived more than once on `{}::{}`",
+                            name, scope, ident
+                        ));
+                    } else {
+                        derives.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if let Ok(file) = syn::parse_file(content) {
+        walk(&file.items, &package.replace('.', "::"), &mut warnings);
+    }
+    warnings
+}
@@ -0,0 +1,679 @@
+use crate::{
+    parser::{Enum, Field, Message, Protos},
+    utils::{serde_as_attr, serde_deny_unknown_fields_attr, serde_flatten_attr},
+};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// the generated files, keyed by their protobuf package
+pub(crate) type Files = BTreeMap<String, String>;
+
+/// an attribute resolved against the parsed protos, forwarded to the wrapped builder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Attribute {
+    Type(String, String),
+    /// like `Type`, but only applied if the path is a message
+    Message(String, String),
+    /// like `Type`, but only applied if the path is an enum
+    Enum(String, String),
+    Field(String, String),
+    /// don't let prost derive `Debug` for the type, as another attribute provides it
+    SkipDebug(String),
+    /// let prost put the field in a `Box`
+    Boxed(String),
+    /// let prost use `bytes::Bytes` for the `bytes` fields matching the path
+    Bytes(String),
+    /// let prost use `BTreeMap` for the map fields matching the path
+    BTreeMap(String),
+}
+
+type AttributesFn = Box<dyn Fn(&Protos, &mut Vec<Attribute>) -> io::Result<()>>;
+type GenerateFn = Box<dyn Fn(&Protos, &mut Files) -> io::Result<()>>;
+
+/// Work that needs the parsed protos: attributes added right before compiling, and code
+/// generated into the files once prost/tonic wrote them.
+pub(crate) struct Extension {
+    attributes: Option<AttributesFn>,
+    generate: Option<GenerateFn>,
+}
+
+impl Extension {
+    pub(crate) fn attributes(
+        f: impl Fn(&Protos, &mut Vec<Attribute>) -> io::Result<()> + 'static,
+    ) -> Self {
+        Self {
+            attributes: Some(Box::new(f)),
+            generate: None,
+        }
+    }
+
+    pub(crate) fn generate(f: impl Fn(&Protos, &mut Files) -> io::Result<()> + 'static) -> Self {
+        Self {
+            attributes: None,
+            generate: Some(Box::new(f)),
+        }
+    }
+
+    pub(crate) fn and_generate(
+        mut self,
+        f: impl Fn(&Protos, &mut Files) -> io::Result<()> + 'static,
+    ) -> Self {
+        self.generate = Some(Box::new(f));
+        self
+    }
+}
+
+/// Wraps a prost `Config` or a tonic `Builder` to add extra code to the files they generate.
+///
+/// prost/tonic only let us inject attributes, so anything that needs new items (functions,
+/// impl blocks, ...) is recorded here and written after the inner builder compiled the protos.
+/// The output directory must be set through the generator (or default to `OUT_DIR`).
+pub struct Generator<B> {
+    pub(crate) builder: B,
+    pub(crate) out_dir: Option<PathBuf>,
+    /// the name of the file generated for the protos without a package, `_` unless set
+    pub(crate) default_package_filename: Option<String>,
+    /// the attributes added through `BuilderAttributes`, forwarded when compiling so they can
+    /// still be removed
+    pub(crate) recorded: Vec<Attribute>,
+    /// the blocks added through `with_ordered_attributes`, emitted ahead of everything else
+    pub(crate) ordered: Vec<Attribute>,
+    extensions: Vec<Extension>,
+}
+
+impl<B> Generator<B> {
+    pub fn new(builder: B) -> Self {
+        Self {
+            builder,
+            out_dir: None,
+            default_package_filename: None,
+            recorded: Vec::new(),
+            ordered: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+
+    /// take the wrapped builder out, keeping the extensions around to post-process its output
+    #[cfg(feature = "tonic")]
+    pub(crate) fn split(self) -> (B, Generator<()>) {
+        let generator = Generator {
+            builder: (),
+            out_dir: self.out_dir,
+            default_package_filename: self.default_package_filename,
+            recorded: self.recorded,
+            ordered: self.ordered,
+            extensions: self.extensions,
+        };
+        (self.builder, generator)
+    }
+
+    pub(crate) fn extend(&mut self, extension: Extension) {
+        self.extensions.push(extension);
+    }
+
+    pub(crate) fn record(&mut self, attrs: Vec<Attribute>) {
+        self.recorded.extend(attrs);
+    }
+
+    pub(crate) fn record_ordered(&mut self, attrs: Vec<Attribute>) {
+        self.ordered.extend(attrs);
+    }
+
+    /// drop the type attributes recorded so far for the given paths
+    pub(crate) fn remove_type_attributes(&mut self, paths: &[&str]) {
+        let paths: Vec<&str> = paths.iter().map(|p| p.trim_start_matches('.')).collect();
+        let keep = |attr: &Attribute| match attr {
+            Attribute::Type(path, _) | Attribute::Message(path, _) | Attribute::Enum(path, _) => {
+                !paths.contains(&path.trim_start_matches('.'))
+            }
+            _ => true,
+        };
+        self.recorded.retain(keep);
+        self.ordered.retain(keep);
+    }
+
+    /// the protos parsed for the extensions, or none if there are no extensions, so plain
+    /// attribute builds don't depend on what the parser understands
+    pub(crate) fn parse(&self, protos: &[impl AsRef<Path>]) -> io::Result<Protos> {
+        if self.extensions.is_empty() {
+            Ok(Protos::default())
+        } else {
+            Protos::load(protos)
+        }
+    }
+
+    /// the ordered blocks, then the recorded attributes followed by the ones the extensions want
+    /// to add for `protos`. Several helpers put `serde_as` on the type they touch, it's only kept
+    /// once per type and moved ahead of the serde derives.
+    pub(crate) fn attributes(&self, protos: &Protos) -> io::Result<Vec<Attribute>> {
+        let mut attrs = self.recorded.clone();
+        for f in self.extensions.iter().filter_map(|e| e.attributes.as_ref()) {
+            f(protos, &mut attrs)?;
+        }
+        btree_map_containers(&mut attrs);
+        let mut serde_as = Vec::new();
+        attrs.retain(|attr| match attr {
+            Attribute::Type(path, attr) if attr == serde_as_attr() => {
+                let path = path.trim_start_matches('.').to_string();
+                if serde_as.contains(&path) {
+                    false
+                } else {
+                    serde_as.push(path);
+                    true
+                }
+            }
+            _ => true,
+        });
+        // `serde_as` only rewrites the fields for the serde derives expanded after it
+        let (front, rest): (Vec<_>, Vec<_>) = attrs
+            .into_iter()
+            .partition(|attr| matches!(attr, Attribute::Type(_, attr) if attr == serde_as_attr()));
+        let mut attrs = self.ordered.clone();
+        attrs.extend(front);
+        attrs.extend(rest);
+        for warning in dedup_rename_all(&mut attrs)
+            .into_iter()
+            .chain(deny_unknown_with_flatten(&attrs))
+        {
+            println!("cargo:warning={}", warning);
+        }
+        Ok(attrs)
+    }
+
+    /// whether each path of `attrs` matches one of the symbols of `protos`
+    pub(crate) fn report(&self, protos: &Protos, attrs: &[Attribute]) -> BuildReport {
+        let symbols = protos.symbols();
+        let mut paths: Vec<(String, bool)> = Vec::new();
+        for attr in attrs {
+            let path = match attr {
+                Attribute::Type(path, _)
+                | Attribute::Message(path, _)
+                | Attribute::Enum(path, _)
+                | Attribute::Field(path, _)
+                | Attribute::SkipDebug(path)
+                | Attribute::Boxed(path)
+                | Attribute::Bytes(path)
+                | Attribute::BTreeMap(path) => path,
+            };
+            if !paths.iter().any(|(p, _)| p == path) {
+                let matched = symbols.iter().any(|s| path_matches(path, s));
+                paths.push((path.clone(), matched));
+            }
+        }
+        BuildReport { paths }
+    }
+
+    fn file_name(&self, package: &str) -> String {
+        match (package, &self.default_package_filename) {
+            ("", Some(name)) => format!("{}.rs", name),
+            ("", None) => "_.rs".to_string(),
+            _ => format!("{}.rs", package),
+        }
+    }
+
+    /// run the recorded extensions against the files generated for `protos`
+    pub(crate) fn post_process(&self, protos: &Protos) -> io::Result<()> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| invalid("OUT_DIR environment variable is not set"))?,
+        };
+
+        let mut files = Files::new();
+        for file in &protos.files {
+            let path = out_dir.join(self.file_name(&file.package));
+            if !files.contains_key(&file.package) && path.exists() {
+                files.insert(file.package.clone(), fs::read_to_string(path)?);
+            }
+        }
+        let original = files.clone();
+
+        for f in self.extensions.iter().filter_map(|e| e.generate.as_ref()) {
+            f(protos, &mut files)?;
+        }
+
+        for (package, content) in files {
+            if original.get(&package) != Some(&content) {
+                fs::write(out_dir.join(self.file_name(&package)), content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The attribute paths registered on a [`Generator`], each with whether it matched an item of
+/// the compiled protos. prost silently ignores paths that match nothing, e.g. a typo in a
+/// package name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// the paths in registration order, paired with whether they matched
+    pub paths: Vec<(String, bool)>,
+}
+
+impl BuildReport {
+    /// the paths that didn't match any message, enum, field, oneof or enum value
+    pub fn unmatched(&self) -> Vec<&str> {
+        self.paths
+            .iter()
+            .filter(|(_, matched)| !matched)
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+}
+
+/// drop the `#[serde(rename_all = "...")]` lines repeating one already added to the same path,
+/// which serde rejects, returning a warning for each
+fn dedup_rename_all(attrs: &mut Vec<Attribute>) -> Vec<String> {
+    let mut renamed: Vec<String> = Vec::new();
+    let mut warnings = Vec::new();
+    for attr in attrs.iter_mut() {
+        let (Attribute::Type(path, attr)
+        | Attribute::Message(path, attr)
+        | Attribute::Enum(path, attr)) = attr
+        else {
+            continue;
+        };
+        let path = path.trim_start_matches('.');
+        let lines: Vec<&str> = attr
+            .lines()
+            .filter(|line| {
+                if !line.trim_start().starts_with("#[serde(rename_all = ") {
+                    return true;
+                }
+                if renamed.iter().any(|p| p == path) {
+                    warnings.push(format!(
+                        "skipping `{}` on `{}`, which already has a `rename_all`",
+                        line.trim(),
+                        path
+                    ));
+                    return false;
+                }
+                renamed.push(path.to_string());
+                true
+            })
+            .collect();
+        *attr = lines.join("\n");
+    }
+    attrs.retain(|attr| match attr {
+        Attribute::Type(_, attr) | Attribute::Message(_, attr) | Attribute::Enum(_, attr) => {
+            !attr.is_empty()
+        }
+        _ => true,
+    });
+    warnings
+}
+
+/// the types with `#[serde(deny_unknown_fields)]` and a `#[serde(flatten)]` field, which serde
+/// doesn't support together
+fn deny_unknown_with_flatten(attrs: &[Attribute]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for attr in attrs {
+        let Attribute::Type(path, attr) = attr else {
+            continue;
+        };
+        if attr != serde_deny_unknown_fields_attr() {
+            continue;
+        }
+        let path = path.trim_start_matches('.');
+        let flattened = attrs.iter().find_map(|a| match a {
+            Attribute::Field(field, attr) if attr.contains(serde_flatten_attr()) => field
+                .trim_start_matches('.')
+                .rsplit_once('.')
+                .filter(|(ty, _)| *ty == path)
+                .map(|(_, name)| name),
+            _ => None,
+        });
+        if let Some(field) = flattened {
+            warnings.push(format!(
+                "`{}` has `#[serde(deny_unknown_fields)]` and the flattened field `{}`, which serde doesn't support together",
+                path, field
+            ));
+        }
+    }
+    warnings
+}
+
+/// the `serde_as` wrappers are written for a `HashMap`, but serde_with only converts between maps
+/// of the same kind: use a `BTreeMap` on the map fields prost generates as one
+fn btree_map_containers(attrs: &mut [Attribute]) {
+    let btree_maps: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::BTreeMap(path) => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    for attr in attrs.iter_mut() {
+        if let Attribute::Field(path, attr) = attr {
+            let field = path.trim_start_matches('.');
+            if btree_maps.iter().any(|p| path_matches(p, field)) {
+                *attr = attr.replace(
+                    "::std::collections::HashMap<_, ",
+                    "::std::collections::BTreeMap<_, ",
+                );
+            }
+        }
+    }
+}
+
+/// whether an attribute path selects `symbol` (fully qualified, without the leading dot) the way
+/// prost matches paths: `.` matches everything, paths with a leading dot match by prefix and
+/// the others by suffix
+fn path_matches(path: &str, symbol: &str) -> bool {
+    match path.strip_prefix('.') {
+        Some("") => true,
+        Some(prefix) => symbol == prefix || symbol.starts_with(&format!("{}.", prefix)),
+        None => symbol == path || symbol.ends_with(&format!(".{}", path)),
+    }
+}
+
+pub(crate) fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.into())
+}
+
+/// the generated file of the package that defines `path`
+pub(crate) fn file_of<'a>(
+    protos: &Protos,
+    files: &'a mut Files,
+    path: &str,
+) -> io::Result<&'a mut String> {
+    let package = protos
+        .package_of(path)
+        .ok_or_else(|| invalid(format!("no package defines `{}`", path)))?;
+    files
+        .get_mut(package)
+        .ok_or_else(|| invalid(format!("no generated file for package `{}`", package)))
+}
+
+/// the offset and indentation of the line declaring the item (e.g. `pub enum`) at `type_path`
+/// (as returned by [`type_path`]) in a generated file
+pub(crate) fn find_item(content: &str, type_path: &str, keyword: &str) -> Option<(usize, String)> {
+    let mut parts: Vec<&str> = type_path.split("::").collect();
+    let name = parts.pop()?;
+    let mut pos = 0;
+    let mut indent = String::new();
+    for module in parts {
+        let header = format!("\n{}pub mod {} {{", indent, module);
+        pos += content[pos..].find(&header)? + header.len();
+        indent.push_str("    ");
+    }
+    let header = format!("\n{}pub {} {} {{", indent, keyword, name);
+    let offset = pos + content[pos..].find(&header)? + 1;
+    Some((offset, indent))
+}
+
+/// the struct at `type_path` (as returned by [`type_path`]) in a generated file
+pub(crate) fn parse_struct(content: &str, type_path: &str) -> Option<syn::ItemStruct> {
+    let file = syn::parse_file(content).ok()?;
+    let mut items = file.items;
+    let mut parts: Vec<&str> = type_path.split("::").collect();
+    let name = parts.pop()?;
+    for module in parts {
+        items = items.into_iter().find_map(|item| match item {
+            syn::Item::Mod(m) if m.ident == module => m.content.map(|(_, items)| items),
+            _ => None,
+        })?;
+    }
+    items.into_iter().find_map(|item| match item {
+        syn::Item::Struct(s) if s.ident == name => Some(s),
+        _ => None,
+    })
+}
+
+/// insert `code` right after the item at `type_path`, in the same module, so it can refer to
+/// the types the same way the item does
+pub(crate) fn insert_after(
+    content: &mut String,
+    type_path: &str,
+    keyword: &str,
+    code: &str,
+) -> io::Result<()> {
+    let not_found = || invalid(format!("`{}` not found in the generated code", type_path));
+    let (offset, indent) = find_item(content, type_path, keyword).ok_or_else(not_found)?;
+    let end = format!("\n{}}}\n", indent);
+    let end = offset + content[offset..].find(&end).ok_or_else(not_found)? + end.len();
+    let file = syn::parse_file(code)
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", type_path, e)))?;
+    let code: String = prettyplease::unparse(&file)
+        .lines()
+        .map(|line| match line {
+            "" => "\n".to_string(),
+            _ => format!("{}{}\n", indent, line),
+        })
+        .collect();
+    content.insert_str(end, &code);
+    Ok(())
+}
+
+/// append `code` to the file of the package that defines `path`
+pub(crate) fn append(protos: &Protos, files: &mut Files, path: &str, code: &str) -> io::Result<()> {
+    let content = file_of(protos, files, path)?;
+    let file = syn::parse_file(code)
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", path, e)))?;
+    content.push_str(&prettyplease::unparse(&file));
+    Ok(())
+}
+
+/// append `code` to the file of `package`, unless `marker` shows it is already there
+pub(crate) fn append_once(
+    files: &mut Files,
+    package: &str,
+    marker: &str,
+    code: &str,
+) -> io::Result<()> {
+    let content = files
+        .get_mut(package)
+        .ok_or_else(|| invalid(format!("no generated file for package `{}`", package)))?;
+    if content.contains(marker) {
+        return Ok(());
+    }
+    let file = syn::parse_file(code)
+        .map_err(|e| invalid(format!("generated invalid code for `{}`: {}", marker, e)))?;
+    content.push_str(&prettyplease::unparse(&file));
+    Ok(())
+}
+
+/// `super::` repeated to reach the root of the package file from inside the type `path`
+pub(crate) fn root_prefix(protos: &Protos, path: &str) -> String {
+    "super::".repeat(type_path(protos, path).matches("::").count())
+}
+
+/// look up a message, failing the build if it does not exist
+pub(crate) fn message<'a>(protos: &'a Protos, path: &str) -> io::Result<&'a Message> {
+    protos
+        .message(path)
+        .ok_or_else(|| invalid(format!("message `{}` not found", path)))
+}
+
+/// look up an enum, failing the build if it does not exist
+pub(crate) fn enumeration<'a>(protos: &'a Protos, path: &str) -> io::Result<&'a Enum> {
+    protos
+        .enumeration(path)
+        .ok_or_else(|| invalid(format!("enum `{}` not found", path)))
+}
+
+/// the Rust variant prost generates for an enum value, with the enum name prefix stripped
+pub(crate) fn variant_ident(enum_name: &str, value: &str) -> String {
+    let prefix = enum_name.to_upper_camel_case();
+    let name = value.to_upper_camel_case();
+    let stripped = match name.strip_prefix(&prefix) {
+        Some(rest) if rest.starts_with(|c: char| c.is_uppercase()) => rest.to_string(),
+        _ => name,
+    };
+    sanitize(stripped)
+}
+
+/// look up a field of a message, failing the build if it does not exist
+pub(crate) fn field<'a>(msg: &'a Message, path: &str, name: &str) -> io::Result<&'a Field> {
+    msg.fields
+        .iter()
+        .find(|f| f.name == name)
+        .ok_or_else(|| invalid(format!("field `{}` not found in `{}`", name, path)))
+}
+
+/// the Rust identifier prost generates for a proto field
+pub(crate) fn field_ident(name: &str) -> String {
+    sanitize(name.to_snake_case())
+}
+
+/// the Rust path of a type relative to the root of its package file, e.g. `outer::Inner`
+pub(crate) fn type_path(protos: &Protos, path: &str) -> String {
+    let path = path.trim_start_matches('.');
+    let package = protos.package_of(path).unwrap_or("");
+    let name = path
+        .strip_prefix(package)
+        .unwrap_or(path)
+        .trim_start_matches('.');
+    let mut parts: Vec<&str> = name.split('.').collect();
+    let last = parts.pop().unwrap_or_default();
+    parts
+        .into_iter()
+        .map(|p| sanitize(p.to_snake_case()))
+        .chain(std::iter::once(sanitize(last.to_upper_camel_case())))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// the snake case name of a type, usable inside generated function names
+pub(crate) fn type_snake(protos: &Protos, path: &str) -> String {
+    type_path(protos, path).replace("::", "_").to_snake_case()
+}
+
+/// the Rust type of a scalar proto type
+pub(crate) fn scalar_type(ty: &str) -> &'static str {
+    match ty {
+        "double" => "f64",
+        "float" => "f32",
+        "int32" | "sint32" | "sfixed32" => "i32",
+        "int64" | "sint64" | "sfixed64" => "i64",
+        "uint32" | "fixed32" => "u32",
+        "uint64" | "fixed64" => "u64",
+        "bool" => "bool",
+        "string" => "::prost::alloc::string::String",
+        _ => "::prost::alloc::vec::Vec<u8>",
+    }
+}
+
+/// escape identifiers that clash with Rust keywords, the same way prost does
+fn sanitize(ident: String) -> String {
+    match ident.as_str() {
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "false" | "fn" | "for" | "if"
+        | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref"
+        | "return" | "static" | "struct" | "trait" | "true" | "type" | "unsafe" | "use"
+        | "where" | "while" | "dyn" | "abstract" | "become" | "box" | "do" | "final" | "macro"
+        | "override" | "priv" | "typeof" | "unsized" | "virtual" | "yield" | "async" | "await"
+        | "try" => format!("r#{}", ident),
+        "_" | "super" | "self" | "Self" | "extern" | "crate" => format!("{}_", ident),
+        s if s.starts_with(|c: char| c.is_numeric()) => format!("_{}", ident),
+        _ => ident,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_item_should_walk_nested_modules() {
+        let content = "\
+#[repr(i32)]
+pub enum Kind {
+    A = 0,
+}
+pub mod node {
+    #[repr(i32)]
+    pub enum Kind {
+        B = 0,
+    }
+}
+";
+        let (offset, indent) = find_item(content, "node::Kind", "enum").unwrap();
+        assert_eq!(indent, "    ");
+        assert!(content[..offset].ends_with("    #[repr(i32)]\n"));
+        assert!(content[offset..].starts_with("    pub enum Kind {\n        B = 0,"));
+        assert!(find_item(content, "node::Other", "enum").is_none());
+    }
+
+    #[test]
+    fn insert_after_should_keep_the_module_indentation() {
+        let mut content = "\
+// @generated
+pub mod node {
+    pub struct Leaf {
+        pub id: i32,
+    }
+    pub struct Other {}
+}
+"
+        .to_string();
+        let leaf = parse_struct(&content, "node::Leaf").unwrap();
+        assert_eq!(leaf.fields.len(), 1);
+        assert!(parse_struct(&content, "Leaf").is_none());
+
+        insert_after(
+            &mut content,
+            "node::Leaf",
+            "struct",
+            "impl Leaf { fn f() {} }",
+        )
+        .unwrap();
+        assert!(content.contains("        pub id: i32,\n    }\n    impl Leaf {\n        fn f() {}\n    }\n    pub struct Other {}"));
+    }
+
+    #[test]
+    fn dedup_rename_all_should_keep_the_first() {
+        let mut attrs = vec![
+            Attribute::Type(
+                "todo.Todo".to_string(),
+                "#[derive(serde::Serialize)]\n#[serde(rename_all = \"camelCase\")]".to_string(),
+            ),
+            Attribute::Message(
+                ".todo.Todo".to_string(),
+                "#[serde(rename_all = \"snake_case\")]".to_string(),
+            ),
+            Attribute::Type(
+                "todo.TodoStatus".to_string(),
+                "#[serde(rename_all = \"snake_case\")]".to_string(),
+            ),
+        ];
+        assert_eq!(
+            dedup_rename_all(&mut attrs),
+            ["skipping `#[serde(rename_all = \"snake_case\")]` on `todo.Todo`, which already has a `rename_all`"]
+        );
+        assert_eq!(attrs.len(), 2);
+        assert_eq!(
+            attrs[0],
+            Attribute::Type(
+                "todo.Todo".to_string(),
+                "#[derive(serde::Serialize)]\n#[serde(rename_all = \"camelCase\")]".to_string(),
+            )
+        );
+    }
+
+    #[test]
+    fn deny_unknown_with_flatten_should_find_conflicts() {
+        let attrs = vec![
+            Attribute::Type(
+                "event.Event".to_string(),
+                serde_deny_unknown_fields_attr().to_string(),
+            ),
+            Attribute::Type(
+                "event.Created".to_string(),
+                serde_deny_unknown_fields_attr().to_string(),
+            ),
+            Attribute::Field(
+                ".event.Event.payload".to_string(),
+                serde_flatten_attr().to_string(),
+            ),
+        ];
+        assert_eq!(
+            deny_unknown_with_flatten(&attrs),
+            ["`event.Event` has `#[serde(deny_unknown_fields)]` and the flattened field `payload`, which serde doesn't support together"]
+        );
+    }
+}
@@ -3,4 +3,9 @@ pub mod prost;
 #[cfg(feature = "tonic")]
 pub mod tonic;
 
-mod utils;
+mod codegen;
+mod generator;
+mod parser;
+pub mod utils;
+
+pub use generator::{BuildReport, Generator};
@@ -0,0 +1,906 @@
+//! A small `.proto` parser, good enough to enumerate packages, messages, enums, fields and
+//! services without invoking `protoc`.
+
+use std::{fs, io, path::Path};
+
+/// a parsed `.proto` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProtoFile {
+    pub package: String,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub services: Vec<Service>,
+}
+
+/// a message definition, including its nested messages and enums
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Message {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub fields: Vec<Field>,
+    pub oneofs: Vec<String>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+}
+
+/// a message field. For map fields `key` holds the key type and `ty` the value type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub label: Label,
+    pub ty: String,
+    pub key: Option<String>,
+    pub number: u32,
+    pub oneof: Option<usize>,
+    pub options: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Label {
+    #[default]
+    None,
+    Optional,
+    Required,
+    Repeated,
+}
+
+/// an enum definition
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub values: Vec<EnumValue>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnumValue {
+    pub name: String,
+    pub number: i32,
+}
+
+/// a service definition
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Service {
+    pub name: String,
+    pub comments: Vec<String>,
+    pub methods: Vec<Method>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Method {
+    pub name: String,
+    pub input: String,
+    pub output: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+}
+
+/// what a type name used by a field resolves to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    Scalar(String),
+    Message(String),
+    Enum(String),
+    /// a type defined outside of the parsed files, most likely a message from an import
+    External(String),
+}
+
+/// whether a named type is a message or an enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeKind {
+    Message,
+    Enum,
+}
+
+/// a set of parsed `.proto` files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Protos {
+    pub files: Vec<ProtoFile>,
+}
+
+const SCALARS: &[&str] = &[
+    "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+    "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+];
+
+/// whether the proto type name is one of the scalar value types
+pub fn is_scalar(ty: &str) -> bool {
+    SCALARS.contains(&ty)
+}
+
+impl Protos {
+    /// parse the given `.proto` files. Imports are not followed.
+    pub fn load(paths: &[impl AsRef<Path>]) -> io::Result<Self> {
+        let files = paths
+            .iter()
+            .map(|p| parse_file(p.as_ref()))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self { files })
+    }
+
+    /// all messages, keyed by their fully qualified name (without the leading dot)
+    pub fn messages(&self) -> Vec<(String, &Message)> {
+        fn walk<'a>(prefix: &str, msgs: &'a [Message], out: &mut Vec<(String, &'a Message)>) {
+            for m in msgs {
+                let name = join(prefix, &m.name);
+                out.push((name.clone(), m));
+                walk(&name, &m.messages, out);
+            }
+        }
+        let mut out = Vec::new();
+        for file in &self.files {
+            walk(&file.package, &file.messages, &mut out);
+        }
+        out
+    }
+
+    /// all enums, keyed by their fully qualified name (without the leading dot)
+    pub fn enums(&self) -> Vec<(String, &Enum)> {
+        let mut out = Vec::new();
+        for file in &self.files {
+            for e in &file.enums {
+                out.push((join(&file.package, &e.name), e));
+            }
+        }
+        for (name, m) in self.messages() {
+            for e in &m.enums {
+                out.push((join(&name, &e.name), e));
+            }
+        }
+        out
+    }
+
+    /// the fully qualified names of the messages or enums defined in `package`, nested ones
+    /// included
+    pub fn types(&self, package: &str, kind: TypeKind) -> Vec<String> {
+        let names: Vec<String> = match kind {
+            TypeKind::Message => self.messages().into_iter().map(|(name, _)| name).collect(),
+            TypeKind::Enum => self.enums().into_iter().map(|(name, _)| name).collect(),
+        };
+        names
+            .into_iter()
+            .filter(|name| self.package_of(name) == Some(package))
+            .collect()
+    }
+
+    /// find a message by its fully qualified name, e.g. `todo.Todo`
+    pub fn message(&self, path: &str) -> Option<&Message> {
+        let path = path.trim_start_matches('.');
+        self.messages()
+            .into_iter()
+            .find(|(name, _)| name == path)
+            .map(|(_, m)| m)
+    }
+
+    /// find an enum by its fully qualified name, e.g. `todo.TodoStatus`
+    pub fn enumeration(&self, path: &str) -> Option<&Enum> {
+        let path = path.trim_start_matches('.');
+        self.enums()
+            .into_iter()
+            .find(|(name, _)| name == path)
+            .map(|(_, e)| e)
+    }
+
+    /// every service with its fully qualified name, e.g. `todo.TodoService`
+    #[cfg(feature = "tonic")]
+    pub fn services(&self) -> Vec<(String, &Service)> {
+        self.files
+            .iter()
+            .flat_map(|f| {
+                f.services
+                    .iter()
+                    .map(move |s| (join(&f.package, &s.name), s))
+            })
+            .collect()
+    }
+
+    /// find a service by its fully qualified name, e.g. `todo.TodoService`
+    #[cfg(feature = "tonic")]
+    pub fn service(&self, path: &str) -> Option<&Service> {
+        let path = path.trim_start_matches('.');
+        self.services()
+            .into_iter()
+            .find(|(name, _)| name == path)
+            .map(|(_, s)| s)
+    }
+
+    /// the fully qualified names of everything prost can put attributes on: messages, enums,
+    /// fields, oneofs and enum values
+    pub fn symbols(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for (name, m) in self.messages() {
+            out.extend(m.fields.iter().map(|f| join(&name, &f.name)));
+            out.extend(m.oneofs.iter().map(|o| join(&name, o)));
+            out.push(name);
+        }
+        for (name, e) in self.enums() {
+            out.extend(e.values.iter().map(|v| join(&name, &v.name)));
+            out.push(name);
+        }
+        out
+    }
+
+    /// the package that defines the given fully qualified type name
+    pub fn package_of(&self, path: &str) -> Option<&str> {
+        let path = path.trim_start_matches('.');
+        self.files
+            .iter()
+            .filter(|f| f.package.is_empty() || path.starts_with(&format!("{}.", f.package)))
+            .max_by_key(|f| f.package.len())
+            .map(|f| f.package.as_str())
+    }
+
+    /// resolve a type name used inside `scope` (the fully qualified name of the enclosing
+    /// message) following the protobuf scoping rules
+    pub fn resolve(&self, scope: &str, ty: &str) -> Resolved {
+        if is_scalar(ty) {
+            return Resolved::Scalar(ty.to_string());
+        }
+        let lookup = |name: &str| {
+            if self.message(name).is_some() {
+                Some(Resolved::Message(name.to_string()))
+            } else if self.enumeration(name).is_some() {
+                Some(Resolved::Enum(name.to_string()))
+            } else {
+                None
+            }
+        };
+        if let Some(abs) = ty.strip_prefix('.') {
+            return lookup(abs).unwrap_or_else(|| Resolved::External(abs.to_string()));
+        }
+        let mut scope = scope.trim_start_matches('.');
+        loop {
+            if let Some(r) = lookup(&join(scope, ty)) {
+                return r;
+            }
+            if scope.is_empty() {
+                return Resolved::External(ty.to_string());
+            }
+            scope = scope.rsplit_once('.').map(|(s, _)| s).unwrap_or("");
+        }
+    }
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// parse a `.proto` file from disk
+pub fn parse_file(path: impl AsRef<Path>) -> io::Result<ProtoFile> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    parse(&source).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+/// parse the content of a `.proto` file
+pub fn parse(source: &str) -> Result<ProtoFile, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    parser.file()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Word,
+    Str,
+    Symbol,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    comments: Vec<String>,
+}
+
+/// split the source into tokens, each carrying the comments directly above it. Like protoc, a
+/// comment on the same line as the previous token trails it and a blank line detaches the
+/// comments before it, so both are dropped.
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    let mut line = 0;
+    let mut token_line = None;
+    let mut blank = true;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            if blank {
+                comments.clear();
+            }
+            line += 1;
+            blank = true;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            blank = false;
+            let trailing = token_line == Some(line);
+            let start = i + 2;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            if trailing {
+                continue;
+            }
+            let text: String = chars[start..i].iter().collect();
+            comments.push(
+                text.strip_prefix(' ')
+                    .unwrap_or(&text)
+                    .trim_end()
+                    .to_string(),
+            );
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            blank = false;
+            let trailing = token_line == Some(line);
+            let start = i + 2;
+            i = start;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            let block: String = chars[start..i.min(chars.len())].iter().collect();
+            i += 2;
+            line += block.matches('\n').count();
+            if trailing {
+                continue;
+            }
+            comments.extend(
+                block
+                    .lines()
+                    .map(|l| l.trim().trim_start_matches('*').trim().to_string())
+                    .filter(|l| !l.is_empty()),
+            );
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    text.push(chars[i]);
+                    i += 1;
+                }
+                text.push(chars[i]);
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err("unterminated string literal".to_string());
+            }
+            i += 1;
+            tokens.push(Token {
+                kind: Kind::Str,
+                text,
+                comments: std::mem::take(&mut comments),
+            });
+            token_line = Some(line);
+            blank = false;
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_alphanumeric() || "_.-+".contains(chars[i])) {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: Kind::Word,
+                text: chars[start..i].iter().collect(),
+                comments: std::mem::take(&mut comments),
+            });
+            token_line = Some(line);
+            blank = false;
+        } else {
+            i += 1;
+            tokens.push(Token {
+                kind: Kind::Symbol,
+                text: c.to_string(),
+                comments: std::mem::take(&mut comments),
+            });
+            token_line = Some(line);
+            blank = false;
+        }
+    }
+    Ok(tokens)
+}
+
+/// an integer literal: decimal, hex (`0x10`) or octal (`010`), with an optional sign
+fn int(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let n = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+    Some(if negative { -n } else { n })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|t| t.text.as_str())
+    }
+
+    fn comments(&self) -> Vec<String> {
+        self.tokens
+            .get(self.pos)
+            .map(|t| t.comments.clone())
+            .unwrap_or_default()
+    }
+
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| "unexpected end of file".to_string())?;
+        self.pos += 1;
+        Ok(token.text.clone())
+    }
+
+    fn word(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.pos) {
+            Some(t) if t.kind == Kind::Word => self.next(),
+            Some(t) => Err(format!("expected identifier, found `{}`", t.text)),
+            None => Err("unexpected end of file".to_string()),
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), String> {
+        let t = self.next()?;
+        if t == s {
+            Ok(())
+        } else {
+            Err(format!("expected `{s}`, found `{t}`"))
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.peek() == Some(s) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// skip a statement up to and including `;`, or a `{ ... }` block
+    fn skip_statement(&mut self) -> Result<(), String> {
+        loop {
+            match self.next()?.as_str() {
+                ";" => return Ok(()),
+                "{" => return self.skip_block(),
+                _ => {}
+            }
+        }
+    }
+
+    /// skip until the `}` matching an already consumed `{`
+    fn skip_block(&mut self) -> Result<(), String> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()?.as_str() {
+                "{" => depth += 1,
+                "}" => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn file(&mut self) -> Result<ProtoFile, String> {
+        let mut file = ProtoFile::default();
+        while let Some(t) = self.peek() {
+            match t {
+                "package" => {
+                    self.pos += 1;
+                    file.package = self.word()?;
+                    self.expect(";")?;
+                }
+                "message" => file.messages.push(self.message()?),
+                "enum" => file.enums.push(self.enumeration()?),
+                "service" => file.services.push(self.service()?),
+                ";" => self.pos += 1,
+                _ => self.skip_statement()?,
+            }
+        }
+        Ok(file)
+    }
+
+    fn message(&mut self) -> Result<Message, String> {
+        let comments = self.comments();
+        self.expect("message")?;
+        let mut msg = Message {
+            name: self.word()?,
+            comments,
+            ..Default::default()
+        };
+        self.expect("{")?;
+        self.message_body(&mut msg, None)?;
+        Ok(msg)
+    }
+
+    fn message_body(&mut self, msg: &mut Message, oneof: Option<usize>) -> Result<(), String> {
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some("message") if oneof.is_none() => msg.messages.push(self.message()?),
+                Some("enum") if oneof.is_none() => msg.enums.push(self.enumeration()?),
+                Some("oneof") if oneof.is_none() => {
+                    self.pos += 1;
+                    msg.oneofs.push(self.word()?);
+                    self.expect("{")?;
+                    self.message_body(msg, Some(msg.oneofs.len() - 1))?;
+                }
+                // proto2 groups, with or without a label, are skipped with their body
+                Some("option" | "reserved" | "extensions" | "extend" | "group") => {
+                    self.skip_statement()?
+                }
+                Some("optional" | "required" | "repeated")
+                    if self.tokens.get(self.pos + 1).map(|t| t.text.as_str()) == Some("group") =>
+                {
+                    self.skip_statement()?
+                }
+                Some(";") => self.pos += 1,
+                Some(_) => {
+                    let field = self.field(oneof)?;
+                    msg.fields.push(field);
+                }
+                None => return Err(format!("unterminated message `{}`", msg.name)),
+            }
+        }
+    }
+
+    fn field(&mut self, oneof: Option<usize>) -> Result<Field, String> {
+        let mut field = Field {
+            comments: self.comments(),
+            oneof,
+            ..Default::default()
+        };
+        field.label = match self.peek() {
+            Some("optional") => Label::Optional,
+            Some("required") => Label::Required,
+            Some("repeated") => Label::Repeated,
+            _ => Label::None,
+        };
+        if field.label != Label::None {
+            self.pos += 1;
+        }
+        let ty = self.word()?;
+        if ty == "map" && self.eat("<") {
+            field.key = Some(self.word()?);
+            self.expect(",")?;
+            field.ty = self.word()?;
+            self.expect(">")?;
+        } else {
+            field.ty = ty;
+        }
+        field.name = self.word()?;
+        self.expect("=")?;
+        let number = self.word()?;
+        field.number = int(&number)
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| format!("invalid field number `{number}`"))?;
+        if self.eat("[") {
+            loop {
+                let name = self.option_name()?;
+                self.expect("=")?;
+                self.option_value(&name, &mut field.options)?;
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect("]")?;
+        }
+        self.expect(";")?;
+        Ok(field)
+    }
+
+    fn option_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        while let Some(t) = self.peek() {
+            if t == "=" || t == ":" || t == "{" {
+                break;
+            }
+            name.push_str(t);
+            self.pos += 1;
+        }
+        Ok(name)
+    }
+
+    /// read an option value. Aggregate values (`{ a: 1, b: 2 }`) are flattened into dotted names.
+    fn option_value(&mut self, name: &str, out: &mut Vec<(String, String)>) -> Result<(), String> {
+        if self.eat("{") {
+            while !self.eat("}") {
+                let key = self.option_name()?;
+                self.eat(":");
+                self.option_value(&format!("{name}.{key}"), out)?;
+                if !self.eat(",") {
+                    self.eat(";");
+                }
+            }
+        } else if self.eat("[") {
+            while !self.eat("]") {
+                self.option_value(name, out)?;
+                self.eat(",");
+            }
+        } else {
+            let mut value = self.next()?;
+            // adjacent string literals are concatenated, like in C
+            while self.tokens[self.pos - 1].kind == Kind::Str
+                && self
+                    .tokens
+                    .get(self.pos)
+                    .is_some_and(|t| t.kind == Kind::Str)
+            {
+                value.push_str(&self.next()?);
+            }
+            out.push((name.to_string(), value));
+        }
+        Ok(())
+    }
+
+    fn enumeration(&mut self) -> Result<Enum, String> {
+        let comments = self.comments();
+        self.expect("enum")?;
+        let mut e = Enum {
+            name: self.word()?,
+            comments,
+            ..Default::default()
+        };
+        self.expect("{")?;
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    return Ok(e);
+                }
+                Some("option" | "reserved") => self.skip_statement()?,
+                Some(";") => self.pos += 1,
+                Some(_) => {
+                    let name = self.word()?;
+                    self.expect("=")?;
+                    let number = self.word()?;
+                    let number = int(&number)
+                        .and_then(|n| n.try_into().ok())
+                        .ok_or_else(|| format!("invalid enum value `{number}`"))?;
+                    if self.eat("[") {
+                        while !self.eat("]") {
+                            self.next()?;
+                        }
+                    }
+                    self.expect(";")?;
+                    e.values.push(EnumValue { name, number });
+                }
+                None => return Err(format!("unterminated enum `{}`", e.name)),
+            }
+        }
+    }
+
+    fn service(&mut self) -> Result<Service, String> {
+        let comments = self.comments();
+        self.expect("service")?;
+        let mut svc = Service {
+            name: self.word()?,
+            comments,
+            ..Default::default()
+        };
+        self.expect("{")?;
+        loop {
+            match self.peek() {
+                Some("}") => {
+                    self.pos += 1;
+                    return Ok(svc);
+                }
+                Some("rpc") => {
+                    self.pos += 1;
+                    let name = self.word()?;
+                    self.expect("(")?;
+                    let client_streaming = self.eat("stream");
+                    let input = self.word()?;
+                    self.expect(")")?;
+                    self.expect("returns")?;
+                    self.expect("(")?;
+                    let server_streaming = self.eat("stream");
+                    let output = self.word()?;
+                    self.expect(")")?;
+                    if !self.eat(";") {
+                        self.expect("{")?;
+                        self.skip_block()?;
+                    }
+                    svc.methods.push(Method {
+                        name,
+                        input,
+                        output,
+                        client_streaming,
+                        server_streaming,
+                    });
+                }
+                Some(";") => self.pos += 1,
+                Some(_) => self.skip_statement()?,
+                None => return Err(format!("unterminated service `{}`", svc.name)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_todo_proto_should_work() {
+        let protos = Protos::load(&["fixtures/protos/todo.proto"]).unwrap();
+        let file = &protos.files[0];
+        assert_eq!(file.package, "todo");
+        assert_eq!(file.messages.len(), 5);
+        assert_eq!(file.enums[0].values[1].name, "TODO_STATUS_DONE");
+
+        let todo = protos.message("todo.Todo").unwrap();
+        let names: Vec<_> = todo.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "id",
+                "title",
+                "description",
+                "status",
+                "created_at",
+                "updated_at"
+            ]
+        );
+        assert_eq!(
+            protos.resolve("todo.Todo", &todo.fields[3].ty),
+            Resolved::Enum("todo.TodoStatus".to_string())
+        );
+        assert_eq!(
+            protos.resolve("todo.Todo", &todo.fields[4].ty),
+            Resolved::External("google.protobuf.Timestamp".to_string())
+        );
+
+        assert_eq!(protos.types("todo", TypeKind::Enum), ["todo.TodoStatus"]);
+        assert_eq!(protos.types("todo", TypeKind::Message).len(), 5);
+
+        let svc = &file.services[0];
+        assert_eq!(svc.methods.len(), 4);
+        assert!(svc.methods[0].server_streaming);
+        assert_eq!(svc.methods[1].input, "CreateTodoRequest");
+    }
+
+    #[test]
+    fn parse_hex_numbers_groups_and_adjacent_strings_should_work() {
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            package a;
+            enum Z { Z_A = 0; Z_D = 0x10; Z_O = 010; Z_N = -1; }
+            message M {
+                optional string a = 0x1 [(x) = "x" "y", deprecated = true];
+                optional group G = 2 {
+                    optional int32 v = 1;
+                }
+                repeated group H = 3 { required int32 w = 1; }
+                optional int32 b = 4;
+            }
+            "#,
+        )
+        .unwrap();
+        let numbers: Vec<i32> = file.enums[0].values.iter().map(|v| v.number).collect();
+        assert_eq!(numbers, [0, 16, 8, -1]);
+        let m = &file.messages[0];
+        let fields: Vec<(&str, u32)> = m
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.number))
+            .collect();
+        assert_eq!(fields, [("a", 1), ("b", 4)]);
+        assert_eq!(
+            m.fields[0].options[0],
+            ("(x)".to_string(), "xy".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_leading_comments_only_should_work() {
+        let file = parse(
+            r#"
+            syntax = "proto3";
+            package a;
+
+            // license banner
+
+            /* A message
+             * over two lines */
+            message M {
+                string id = 1; // the id
+
+                // detached
+
+                // title doc
+                string title = 2; /* trailing */
+                // kind doc
+                int32 kind = 3;
+            }
+            "#,
+        )
+        .unwrap();
+        let m = &file.messages[0];
+        assert_eq!(m.comments, ["A message", "over two lines"]);
+        let comments: Vec<&[String]> = m.fields.iter().map(|f| f.comments.as_slice()).collect();
+        assert_eq!(
+            comments,
+            [
+                &[][..],
+                &["title doc".to_string()],
+                &["kind doc".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_nested_map_oneof_and_options_should_work() {
+        let file = parse(
+            r#"
+            syntax = "proto3";
+            package a.b;
+            // A node
+            message Node {
+                message Child { int32 v = 1; }
+                enum Kind { KIND_A = 0; KIND_B = 1 [deprecated = true]; }
+                map<string, Child> children = 1;
+                oneof value {
+                    string text = 2;
+                    int64 number = 3;
+                }
+                repeated Kind kinds = 4 [packed = true];
+                string name = 5 [(validate.rules).string = {min_len: 3, pattern: "^[a-z]+$"}];
+                reserved 6, 7;
+            }
+            "#,
+        )
+        .unwrap();
+        let node = &file.messages[0];
+        assert_eq!(node.comments, ["A node"]);
+        assert_eq!(node.messages[0].name, "Child");
+        assert_eq!(node.fields[0].key.as_deref(), Some("string"));
+        assert_eq!(node.fields[0].ty, "Child");
+        assert_eq!(node.fields[1].oneof, Some(0));
+        assert_eq!(node.fields[3].label, Label::Repeated);
+        assert_eq!(
+            node.fields[4].options,
+            [
+                (
+                    "(validate.rules).string.min_len".to_string(),
+                    "3".to_string()
+                ),
+                (
+                    "(validate.rules).string.pattern".to_string(),
+                    "^[a-z]+$".to_string()
+                ),
+            ]
+        );
+    }
+}
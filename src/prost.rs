@@ -1,11 +1,38 @@
-use crate::utils::{
-    derive_builder_attr, serde_as_attr, serde_attr, sqlx_from_row_attr, sqlx_type_attr,
+use crate::{
+    codegen,
+    generator::{Attribute, BuildReport},
+    parser::{Protos, TypeKind},
+    utils::{
+        api_preset_attrs, arbitrary_attr, async_graphql_enum_attr,
+        async_graphql_simple_object_attr, attribute_map_attrs, base64_bytes_attrs, boxed_attrs,
+        btree_map_attrs, builder_defaults_attrs, bytes_attrs, clap_args_attrs, clap_parser_attr,
+        comments_as_schema_desc_attrs, db_preset_attrs, deprecated_attr, derive_builder_attr,
+        derive_more_attr, derive_where_attrs, diesel_attr, enum_original_names_attrs,
+        eq_hash_safe_attrs, eq_where_possible_attrs, fake_attrs, field_attributes_by_tag_attrs,
+        fields_by_type_attrs, fields_matching_attrs, i64_as_string_attrs, joined_enum_attrs,
+        joined_field_attrs, joined_message_attrs, joined_type_attrs, must_use_attr,
+        non_exhaustive_attr, ord_attr, profile_attrs, proptest_attr, rkyv_attr, sea_orm_attrs,
+        serde_as_attrs, serde_as_custom_attrs, serde_as_map_attrs, serde_as_vec_attrs, serde_attr,
+        serde_deny_unknown_fields_attr, serde_descriptor_attrs, serde_excluding_attrs,
+        serde_flatten_attr, serde_package_attrs, serde_rename_all_attr,
+        serde_rename_all_fields_attr, serde_tag_attr, serde_tag_content_attr, serde_untagged_attr,
+        serde_with_attrs, single_field_messages, sqlx_from_row_attr, sqlx_from_row_opts_attr,
+        sqlx_type_attr, string_fields, strum_attr, type_attrs, zeroize_attr, BuilderError, Profile,
+        RenameAll,
+    },
+    Generator,
 };
 use prost_build::Config;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
 /// provide extra attributes to the generated protobuf code easily
 pub trait BuilderAttributes {
-    /// add type attributes with `#[derive(serde::Serialize, serde::Deserialize)]`
+    /// add type attributes with `#[derive(serde::Serialize, serde::Deserialize)]`. Through a
+    /// `Generator`, a `#[serde(rename_all = "...")]` repeating one already added to the same path
+    /// is dropped with a `cargo:warning`.
     fn with_serde(
         &mut self,
         paths: &[&str],
@@ -13,19 +40,310 @@ pub trait BuilderAttributes {
         de: bool,
         extra_attrs: Option<&[&str]>,
     ) -> &mut Self;
+    /// add serde derives to the types of `paths` but the ones in `exclude`, e.g. response types
+    /// that are only streamed
+    fn with_serde_excluding(
+        &mut self,
+        paths: &[&str],
+        exclude: &[&str],
+        ser: bool,
+        de: bool,
+    ) -> &mut Self;
+    /// add serde derives to every message and enum of `package`. Panics if the protos can't be
+    /// parsed.
+    fn with_serde_all(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self;
+    /// add serde derives to every message and enum of `package`, listed from a compiled
+    /// `FileDescriptorSet` (e.g. `protoc --include_imports --descriptor_set_out=...`) instead of
+    /// parsing the protos. Panics if the file can't be read or decoded.
+    fn with_serde_all_from_descriptor(
+        &mut self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self;
+    /// add serde derives and `#[serde(rename_all = "...")]` to the enums of `package` only
+    fn with_serde_enums(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self;
+    /// add `#[serde(rename_all = "...")]` to the enums (and oneofs) matching the paths, renaming
+    /// their variants, e.g. `Done` to `DONE` with `ScreamingSnakeCase`. The same attribute renames the
+    /// fields of a message, so this goes through `enum_attribute` and skips messages matching the paths.
+    fn with_serde_enum_rename(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self;
+    /// add `#[serde(rename_all_fields = "...")]` to the enums (and oneofs) matching the paths,
+    /// renaming the fields of their struct variants. Unlike `rename_all`, serde only accepts it on
+    /// enums, so this goes through `enum_attribute` too. Needs serde 1.0.185 or later. prost's
+    /// oneof variants hold a single value, so it only matters for enums given struct variants
+    /// some other way.
+    fn with_serde_rename_all_fields(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self;
+    /// add serde derives and `#[serde(rename_all = "...")]` to the messages of `package` only
+    fn with_serde_messages(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self;
+    /// serialize the variants of the enums with their proto names (e.g. `TODO_STATUS_DONE`)
+    /// rather than the Rust ones. Panics if the protos can't be parsed.
+    fn with_serde_enum_original_names(&mut self, proto_files: &[&str], paths: &[&str])
+        -> &mut Self;
+    /// add [`serde_as_attr`](crate::utils::serde_as_attr) to the type and each attribute (e.g.
+    /// `#[serde_as(as = "DisplayFromStr")]`) to its fields. Through a `Generator`, the type
+    /// attribute is added once however many times this is called for the type.
     fn with_serde_as(&mut self, paths: &str, fields: &[(&[&str], &str)]) -> &mut Self;
+    /// like `with_serde_as`, with each `as` expression (e.g. `DisplayFromStr`) wrapped in
+    /// `Vec<...>` for repeated fields
+    fn with_serde_as_vec(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self;
+    /// like `with_serde_as`, with each `as` expression used for the values of a
+    /// `HashMap<_, ...>` for map fields
+    fn with_serde_as_map(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self;
+    /// like `with_serde_as`, with a custom converter (e.g. `crate::convert::Hex`) per field,
+    /// wrapped in `container` if given, e.g. `Option` gives `#[serde_as(as = "Option<...>")]`
+    fn with_serde_as_custom(
+        &mut self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> &mut Self;
+    /// serialize the 64-bit integer fields of the messages as strings, which JSON numbers can't
+    /// hold exactly, through `#[serde_as(as = "DisplayFromStr")]` and `#[serde_with::serde_as]` on
+    /// the messages. Panics if the protos can't be parsed.
+    fn with_i64_as_string(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self;
+    /// serialize the `bytes` fields of the messages as base64 strings instead of arrays of numbers,
+    /// through `#[serde_as(as = "serde_with::base64::Base64")]` and `#[serde_with::serde_as]` on the
+    /// messages. Needs serde_with's `base64` feature. Panics if the protos can't be parsed.
+    fn with_base64_bytes(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self;
+    /// add `#[schemars(description = "...")]` with the leading proto comments to the messages and
+    /// their fields that have any, so JSON schemas carry the same docs as rustdoc. Panics if the
+    /// protos can't be parsed.
+    fn with_comments_as_schema_desc(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self;
+    /// add `#[serde(tag = "...")]` to the types, e.g. a oneof (`event.Event.payload`), so the
+    /// variant name is serialized as a field of the content. serde can only do this for variants
+    /// holding a struct or map, so oneof members must be messages.
+    fn with_serde_tag(&mut self, paths: &[&str], tag: &str) -> &mut Self;
+    /// add `#[serde(tag = "...", content = "...")]` to the types, so the variant name and its
+    /// content are serialized as two fields. Unlike `with_serde_tag`, this works for scalar members.
+    fn with_serde_tag_content(&mut self, paths: &[&str], tag: &str, content: &str) -> &mut Self;
+    /// add `#[serde(untagged)]` to the types, e.g. a oneof, so only the variant content is
+    /// serialized. Deserializing tries the variants in order and picks the first that fits, so
+    /// members with the same shape (e.g. `Created` and `Deleted` with a single string) can't be told
+    /// apart.
+    fn with_serde_untagged(&mut self, paths: &[&str]) -> &mut Self;
+    /// add `#[serde(flatten)]` to the message fields, so the fields of the nested message are
+    /// serialized inline in the parent. serde_with's `skip_serializing_none` (added by
+    /// `with_serde_as`) doesn't see through flattened fields, so `None`s inside them are still
+    /// serialized.
+    fn with_serde_flatten(&mut self, fields: &[&str]) -> &mut Self;
+    /// add `#[serde(deny_unknown_fields)]` to the types, so deserializing rejects unknown keys. serde
+    /// doesn't support it together with `#[serde(flatten)]` fields, a `Generator` warns about types
+    /// that have both.
+    fn with_serde_deny_unknown_fields(&mut self, paths: &[&str]) -> &mut Self;
+    /// add `#[serde(with = "...")]` to the fields, e.g. `("todo.Todo.status", "my_mod")`, to
+    /// control how each one is (de)serialized. prost can't change the Rust type of a field, so a
+    /// newtype like `UserId` has to be converted from and to the proto type in that module.
+    fn with_field_serde_with(&mut self, fields: &[(&str, &str)]) -> &mut Self;
     /// add type attributes with `#[derive(sqlx::Type)]`
     fn with_sqlx_type(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
     /// add type attributes with `#[derive(sqlx::FromRow)]`
     fn with_sqlx_from_row(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add type attributes with `#[derive(sqlx::FromRow)]` and an optional
+    /// `#[sqlx(rename_all = "...")]`
+    fn with_sqlx_from_row_opts(
+        &mut self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// the usual attributes for messages exposed in a JSON API:
+    /// `#[derive(serde::Serialize, serde::Deserialize)]`, `#[serde(rename_all = "camelCase")]`,
+    /// `#[serde_with::serde_as]` and `#[serde_with::skip_serializing_none]`
+    fn with_api_preset(&mut self, paths: &[&str]) -> &mut Self;
+    /// the usual attributes for messages read from a database: `#[derive(sqlx::FromRow)]` and
+    /// `#[sqlx(rename_all = "snake_case")]`
+    fn with_db_preset(&mut self, paths: &[&str]) -> &mut Self;
+    /// add the attributes of the [`Profile`] to the types, in the order they were added to it
+    fn with_profile(&mut self, paths: &[&str], profile: &Profile) -> &mut Self;
     /// add type attributes with `#[derive(derive_builder::Builder)]`
     fn with_derive_builder(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add `#[builder(default = "...")]` to the fields, paired with the default expression, e.g.
+    /// `("todo.CreateTodoRequest.description", "\"draft\".into()")`. Use it with
+    /// `with_derive_builder`.
+    fn with_builder_field_default(&mut self, fields: &[(&str, &str)]) -> &mut Self;
+    /// add type attributes with `#[derive(diesel::Queryable, diesel::Insertable)]` (as selected)
+    /// and an optional `#[diesel(table_name = "...")]`. The table comes from a `diesel::table!`
+    /// schema module that this crate can't generate, so it must be in scope of the generated code,
+    /// e.g. by `include!`-ing it into a module that imports the schema.
+    fn with_diesel(
+        &mut self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// add `#[derive(sea_orm::DeriveEntityModel)]` and `#[sea_orm(table_name = "...")]` to the
+    /// message, and `#[sea_orm(primary_key)]` to its key fields (names, e.g. `id`). The derive expects
+    /// `Relation` and `ActiveModelBehavior` in scope, which the generated code doesn't provide.
+    fn with_sea_orm(
+        &mut self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// add type attributes with `#[derive(zeroize::Zeroize)]`, and `zeroize::ZeroizeOnDrop` if
+    /// `on_drop`, for messages holding secrets. Every field must implement `Zeroize`, which enums
+    /// (`i32`) do but nested messages, `Timestamp` included, don't unless they derive it too.
+    fn with_zeroize(
+        &mut self,
+        paths: &[&str],
+        on_drop: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// add type attributes with `#[derive(arbitrary::Arbitrary)]`, e.g. to fuzz decoders. The
+    /// derive needs a lifetime for borrowed types, but prost types own their data so the plain
+    /// derive works.
+    fn with_arbitrary(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add type attributes with `#[derive(proptest_derive::Arbitrary)]`. Use `extra_attrs` for
+    /// `#[proptest(...)]` strategy overrides on the type.
+    fn with_proptest(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add type attributes with `#[derive(async_graphql::SimpleObject)]` to messages
+    fn with_async_graphql_object(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// add type attributes with `#[derive(async_graphql::Enum)]` to enums
+    fn with_async_graphql_enum(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self;
+    /// add type attributes with `#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]`
+    fn with_rkyv(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add type attributes with `#[derive(fake::Dummy)]`, and `#[dummy(faker = "...")]` on the
+    /// fields paired with a faker, e.g. `("todo.Todo.title", "Sentence(3..5)")`
+    fn with_fake(&mut self, paths: &[&str], field_fakers: &[(&str, &str)]) -> &mut Self;
+    /// add type attributes with `#[derive(clap::Parser)]`, e.g. with `#[command(...)]` as extra
+    fn with_clap(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
+    /// add `#[arg(...)]` to the fields, e.g. `("todo.CreateTodoRequest.title", "long")`
+    fn with_clap_args(&mut self, fields: &[(&str, &str)]) -> &mut Self;
+    /// add type attributes with `#[derive(derive_more::...)]` for the given traits, e.g. `From`
+    fn with_derive_more(&mut self, paths: &[&str], traits: &[&str]) -> &mut Self;
+    /// add `#[derive(...)]` with the given traits to every message and enum of the protos whose
+    /// fully qualified name (e.g. `todo.CreateTodoRequest`) matches `predicate`. Panics if the protos
+    /// can't be parsed.
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        &mut self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> &mut Self;
+    /// add `#[derive(Eq, Hash)]` to the messages and, as they need it too, to the messages of
+    /// their fields, after checking that none of them holds a `float`/`double` field. Map fields
+    /// still prevent `Hash`.
+    fn with_eq_hash_safe(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> Result<&mut Self, BuilderError>;
+    /// add `#[derive(Eq, Hash)]` to every message of the protos that can have them: no
+    /// `float`/`double` or map fields and no types from other files but `Timestamp`, directly or
+    /// through the messages of their fields. The skipped messages are listed as `cargo:warning`s.
+    /// Panics if the protos can't be parsed.
+    fn derive_eq_where_possible(&mut self, proto_files: &[&str]) -> &mut Self;
+    /// add `#[non_exhaustive]` to the types, so downstream crates can't match them exhaustively
+    /// or build messages with struct literals
+    fn with_non_exhaustive(&mut self, paths: &[&str]) -> &mut Self;
+    /// add `#[derive(PartialOrd, Ord)]` to the types, to sort them. Every field must be `Ord`: no
+    /// `float`/`double` or map fields, and the messages of the fields and the oneofs need it too. `Ord`
+    /// also needs `Eq`, which `with_eq_hash_safe` adds after checking for floats.
+    fn with_ord(&mut self, paths: &[&str]) -> &mut Self;
+    /// add `#[deprecated]` (with an optional note) to the types. prost already adds a plain
+    /// `#[deprecated]` for the `deprecated = true` option, so don't use both on the same item.
+    fn with_deprecated_types(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self;
+    /// add `#[deprecated]` (with an optional note) to the fields, see
+    /// [`with_deprecated_types`](Self::with_deprecated_types)
+    fn with_deprecated_fields(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self;
+    /// add `#[must_use]` (with an optional note) to the types, e.g. responses callers shouldn't
+    /// drop
+    fn with_must_use(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self;
     /// add type attributes with `#[derive(strum::EnumString)]`
     fn with_strum(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self;
     /// add type attributes
     fn with_type_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self;
+    /// add type attributes to the messages matching the paths only, skipping enums, e.g. with
+    /// `.` or a package path
+    fn with_message_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self;
+    /// add type attributes to the enums and oneofs matching the paths only, skipping messages
+    fn with_enum_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self;
+    /// add the attributes to the types as one block in the given order, for derives that must
+    /// see other attributes first, e.g. `#[serde_with::serde_as]` before
+    /// `#[derive(serde::Serialize)]`. Nothing from other helpers is emitted in between. Through a
+    /// `Generator` the block goes ahead of every other attribute on those paths, whatever the
+    /// call order; on a bare builder it lands where it's called, like `with_type_attributes`.
+    fn with_ordered_attributes(&mut self, paths: &[&str], ordered: &[&str]) -> &mut Self;
+    /// add type attributes from a path -> attributes map, e.g. a `Vec<(String, Vec<String>)>`
+    /// loaded from a config file
+    fn with_attribute_map<I, P, A, S>(&mut self, map: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>;
     /// add field attributes
     fn with_field_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self;
+    /// wrap the message fields (full paths, e.g. `tree.Node.leaf`) in a `Box`, e.g. to keep a
+    /// large message small. prost already boxes the fields that make a message recursive.
+    fn with_boxed_fields(&mut self, fields: &[&str]) -> &mut Self;
+    /// use `::prost::bytes::Bytes` instead of `Vec<u8>` for the `bytes` fields matching the paths:
+    /// a field (`peer.Peer.raw_address`), a fully qualified message or package for all their
+    /// `bytes` fields (`.peer.Peer`, the leading dot makes prost match by prefix), or `.` for all
+    /// of them. prost replaces the paths set before, so pass them all in one call, or call it
+    /// through a `Generator`, which sets them together.
+    fn with_bytes(&mut self, paths: &[&str]) -> &mut Self;
+    /// use `BTreeMap` instead of `HashMap` for the map fields matching the paths, e.g. for a
+    /// deterministic order, matched like [`with_bytes`](Self::with_bytes). prost replaces the paths
+    /// set before, so pass them all in one call, or call it through a `Generator`, which sets them
+    /// together and turns the `HashMap<_, ...>` of the `serde_as` helpers into a `BTreeMap` on
+    /// those fields.
+    fn with_btree_maps(&mut self, paths: &[&str]) -> &mut Self;
+    /// add the field attribute to every field of a scalar type across the protos, e.g. `int64`.
+    /// The accepted names are the proto scalar types: `double`, `float`, `int32`, `int64`,
+    /// `uint32`, `uint64`, `sint32`, `sint64`, `fixed32`, `fixed64`, `sfixed32`, `sfixed64`,
+    /// `bool`, `string` and `bytes`. Panics on other names or if the protos can't be parsed.
+    fn with_field_attributes_by_type(
+        &mut self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> &mut Self;
+    /// add each `(message, tag, attribute)` field attribute to the field of the message with that
+    /// tag number, e.g. `("todo.Todo", 1, ...)` for `id`, so it keeps working when fields are
+    /// renamed. Panics if a message has no field with the tag or if the protos can't be parsed.
+    fn with_field_attributes_by_tag(
+        &mut self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> &mut Self;
+    /// add the field attribute to every field of the protos whose fully qualified path (e.g.
+    /// `todo.Todo.id`) matches the regex `pattern`, e.g. `\.id$` or `_id$`. Fails if the pattern
+    /// isn't a valid regex. Panics if the protos can't be parsed.
+    fn with_field_attributes_matching(
+        &mut self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<&mut Self, BuilderError>;
     /// add optional type attributes
     fn with_optional_type_attributes(
         &mut self,
@@ -38,6 +356,54 @@ pub trait BuilderAttributes {
         paths: &[&str],
         attributes: Option<&[&str]>,
     ) -> &mut Self;
+    /// map proto types to existing Rust types instead of generating them, calling `extern_path`
+    /// for each `(proto_path, rust_path)` pair, e.g.
+    /// `(".google.protobuf.Timestamp", "::pbjson_types::Timestamp")`
+    fn with_extern_paths(&mut self, mappings: &[(&str, &str)]) -> &mut Self;
+    /// generate a file named `name` in the output directory that includes every generated file
+    /// in nested modules, see [`Config::include_file`]
+    ///
+    /// ```
+    /// use proto_builder_trait::prost::BuilderAttributes;
+    ///
+    /// let mut config = prost_build::Config::new();
+    /// config
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .with_include_file("_includes.rs");
+    /// ```
+    fn with_include_file(&mut self, name: &str) -> &mut Self;
+    /// pass each argument to `protoc`, see [`Config::protoc_arg`]
+    fn with_protoc_args(&mut self, args: &[&str]) -> &mut Self;
+    /// name the file generated for the protos without a package `name` instead of `_`, see
+    /// [`Config::default_package_filename`]
+    ///
+    /// ```
+    /// use proto_builder_trait::prost::BuilderAttributes;
+    ///
+    /// let mut config = prost_build::Config::new();
+    /// config
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .with_protoc_args(&["--experimental_allow_proto3_optional"])
+    ///     .with_default_package_filename("protos");
+    /// ```
+    fn with_default_package_filename(&mut self, name: &str) -> &mut Self;
+    /// call `f` on the builder mid-chain, to reach the native methods this trait doesn't wrap
+    ///
+    /// ```
+    /// use proto_builder_trait::prost::BuilderAttributes;
+    ///
+    /// let mut config = prost_build::Config::new();
+    /// config
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .apply(|c| c.btree_map(["."]))
+    ///     .with_strum(&["todo.TodoStatus"], None);
+    /// ```
+    fn apply<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self) -> &mut Self,
+    {
+        f(self)
+    }
 }
 
 impl BuilderAttributes for Config {
@@ -48,76 +414,395 @@ impl BuilderAttributes for Config {
         de: bool,
         extra_attrs: Option<&[&str]>,
     ) -> &mut Self {
-        let attr = serde_attr(ser, de);
+        apply(self, type_attrs(paths, serde_attr(ser, de), extra_attrs))
+    }
 
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, attr)
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+    fn with_serde_excluding(
+        &mut self,
+        paths: &[&str],
+        exclude: &[&str],
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        apply(self, serde_excluding_attrs(paths, exclude, ser, de))
     }
 
     fn with_serde_as(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
-        let serde_attr = serde_as_attr();
-        fields.iter().fold(
-            self.type_attribute(path, serde_attr),
-            |builder, (paths, attr)| {
-                paths.iter().fold(builder, |builder, p| {
-                    let p = format!("{}.{}", path, p);
-                    builder.field_attribute(p, attr)
-                })
-            },
+        apply(self, serde_as_attrs(path, fields))
+    }
+
+    fn with_serde_as_vec(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
+        apply(self, serde_as_vec_attrs(path, fields))
+    }
+
+    fn with_serde_as_map(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
+        apply(self, serde_as_map_attrs(path, fields))
+    }
+
+    fn with_serde_as_custom(
+        &mut self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> &mut Self {
+        apply(self, serde_as_custom_attrs(path, fields, container))
+    }
+
+    fn with_i64_as_string(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        apply(self, i64_as_string_attrs(proto_files, paths))
+    }
+
+    fn with_base64_bytes(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        apply(self, base64_bytes_attrs(proto_files, paths))
+    }
+
+    fn with_comments_as_schema_desc(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        apply(self, comments_as_schema_desc_attrs(proto_files, paths))
+    }
+
+    fn with_serde_tag(&mut self, paths: &[&str], tag: &str) -> &mut Self {
+        apply(self, type_attrs(paths, &serde_tag_attr(tag), None))
+    }
+
+    fn with_serde_tag_content(&mut self, paths: &[&str], tag: &str, content: &str) -> &mut Self {
+        apply(
+            self,
+            type_attrs(paths, &serde_tag_content_attr(tag, content), None),
+        )
+    }
+
+    fn with_serde_untagged(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, type_attrs(paths, serde_untagged_attr(), None))
+    }
+
+    fn with_serde_flatten(&mut self, fields: &[&str]) -> &mut Self {
+        apply(
+            self,
+            joined_field_attrs(fields, Some(&[serde_flatten_attr()])),
+        )
+    }
+
+    fn with_serde_deny_unknown_fields(&mut self, paths: &[&str]) -> &mut Self {
+        apply(
+            self,
+            type_attrs(paths, serde_deny_unknown_fields_attr(), None),
+        )
+    }
+
+    fn with_field_serde_with(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        apply(self, serde_with_attrs(fields))
+    }
+
+    fn with_serde_enum_original_names(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> &mut Self {
+        apply(self, enum_original_names_attrs(proto_files, paths))
+    }
+
+    fn with_serde_all(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Message, TypeKind::Enum];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, ser, de, None),
+        )
+    }
+
+    fn with_serde_all_from_descriptor(
+        &mut self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        apply(
+            self,
+            serde_descriptor_attrs(descriptor_path, package, ser, de),
+        )
+    }
+
+    fn with_serde_enums(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Enum];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_serde_enum_rename(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self {
+        let attr = serde_rename_all_attr(rename);
+        apply(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_rename_all_fields(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self {
+        let attr = serde_rename_all_fields_attr(rename);
+        apply(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_messages(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Message];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
         )
     }
 
     fn with_sqlx_type(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, sqlx_type_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, sqlx_type_attr(), extra_attrs))
     }
 
     fn with_sqlx_from_row(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, sqlx_from_row_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, sqlx_from_row_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row_opts(
+        &mut self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = sqlx_from_row_opts_attr(rename_all);
+        apply(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_api_preset(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, api_preset_attrs(paths))
+    }
+
+    fn with_db_preset(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, db_preset_attrs(paths))
+    }
+
+    fn with_profile(&mut self, paths: &[&str], profile: &Profile) -> &mut Self {
+        apply(self, profile_attrs(paths, profile))
     }
 
     fn with_derive_builder(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, derive_builder_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, derive_builder_attr(), extra_attrs))
+    }
+
+    fn with_builder_field_default(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        apply(self, builder_defaults_attrs(fields))
+    }
+
+    fn with_diesel(
+        &mut self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = diesel_attr(table_name, queryable, insertable);
+        apply(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_sea_orm(
+        &mut self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        apply(
+            self,
+            sea_orm_attrs(path, table_name, primary_keys, extra_attrs),
+        )
+    }
+
+    fn with_zeroize(
+        &mut self,
+        paths: &[&str],
+        on_drop: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        apply(self, type_attrs(paths, zeroize_attr(on_drop), extra_attrs))
+    }
+
+    fn with_arbitrary(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        apply(self, type_attrs(paths, arbitrary_attr(), extra_attrs))
+    }
+
+    fn with_proptest(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        apply(self, type_attrs(paths, proptest_attr(), extra_attrs))
+    }
+
+    fn with_async_graphql_object(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = async_graphql_simple_object_attr();
+        apply(self, type_attrs(paths, attr, extra_attrs))
+    }
+
+    fn with_async_graphql_enum(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        apply(
+            self,
+            type_attrs(paths, async_graphql_enum_attr(), extra_attrs),
+        )
+    }
+
+    fn with_rkyv(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        apply(self, type_attrs(paths, rkyv_attr(), extra_attrs))
+    }
+
+    fn with_fake(&mut self, paths: &[&str], field_fakers: &[(&str, &str)]) -> &mut Self {
+        apply(self, fake_attrs(paths, field_fakers))
+    }
+
+    fn with_clap(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        apply(self, type_attrs(paths, clap_parser_attr(), extra_attrs))
+    }
+
+    fn with_clap_args(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        apply(self, clap_args_attrs(fields))
+    }
+
+    fn with_derive_more(&mut self, paths: &[&str], traits: &[&str]) -> &mut Self {
+        apply(self, type_attrs(paths, &derive_more_attr(traits), None))
+    }
+
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        &mut self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> &mut Self {
+        apply(self, derive_where_attrs(proto_files, predicate, derives))
+    }
+
+    fn with_eq_hash_safe(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> Result<&mut Self, BuilderError> {
+        let attrs = eq_hash_safe_attrs(proto_files, paths)?;
+        Ok(apply(self, attrs))
+    }
+
+    fn derive_eq_where_possible(&mut self, proto_files: &[&str]) -> &mut Self {
+        apply(self, eq_where_possible_attrs(proto_files))
+    }
+
+    fn with_non_exhaustive(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, type_attrs(paths, non_exhaustive_attr(), None))
+    }
+
+    fn with_ord(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, type_attrs(paths, ord_attr(), None))
+    }
+
+    fn with_deprecated_types(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = deprecated_attr(note);
+        apply(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_deprecated_fields(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = deprecated_attr(note);
+        apply(self, joined_field_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_must_use(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = must_use_attr(note);
+        apply(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
     }
 
     fn with_strum(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(
-                    ty,
-                    "#[derive(strum::EnumString, strum::Display,strum::EnumIter)]",
-                )
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, strum_attr(), extra_attrs))
     }
 
     fn with_type_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
-        let attr = attributes.join("\n");
+        apply(self, joined_type_attrs(paths, Some(attributes)))
+    }
 
-        paths.iter().fold(self, |builder, ty| {
-            builder.type_attribute(ty, attr.as_str())
-        })
+    fn with_message_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        apply(self, joined_message_attrs(paths, Some(attributes)))
+    }
+
+    fn with_enum_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        apply(self, joined_enum_attrs(paths, Some(attributes)))
+    }
+
+    fn with_ordered_attributes(&mut self, paths: &[&str], ordered: &[&str]) -> &mut Self {
+        apply(self, joined_type_attrs(paths, Some(ordered)))
+    }
+
+    fn with_attribute_map<I, P, A, S>(&mut self, map: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        apply(self, attribute_map_attrs(map))
     }
 
     fn with_field_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
-        let attr = attributes.join("\n");
-        paths.iter().fold(self, |builder, ty| {
-            builder.field_attribute(ty, attr.as_str())
-        })
+        apply(self, joined_field_attrs(paths, Some(attributes)))
+    }
+
+    fn with_boxed_fields(&mut self, fields: &[&str]) -> &mut Self {
+        apply(self, boxed_attrs(fields))
+    }
+
+    fn with_bytes(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, bytes_attrs(paths))
+    }
+
+    fn with_btree_maps(&mut self, paths: &[&str]) -> &mut Self {
+        apply(self, btree_map_attrs(paths))
+    }
+
+    fn with_field_attributes_by_type(
+        &mut self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> &mut Self {
+        apply(
+            self,
+            fields_by_type_attrs(proto_files, proto_type, attribute),
+        )
+    }
+
+    fn with_field_attributes_by_tag(
+        &mut self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> &mut Self {
+        apply(self, field_attributes_by_tag_attrs(proto_files, targets))
+    }
+
+    fn with_field_attributes_matching(
+        &mut self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<&mut Self, BuilderError> {
+        let attrs = fields_matching_attrs(proto_files, pattern, attribute)?;
+        Ok(apply(self, attrs))
     }
 
     fn with_optional_type_attributes(
@@ -125,11 +810,7 @@ impl BuilderAttributes for Config {
         paths: &[&str],
         attributes: Option<&[&str]>,
     ) -> &mut Self {
-        if let Some(attributes) = attributes {
-            self.with_type_attributes(paths, attributes)
-        } else {
-            self
-        }
+        apply(self, joined_type_attrs(paths, attributes))
     }
 
     fn with_optional_field_attributes(
@@ -137,26 +818,941 @@ impl BuilderAttributes for Config {
         paths: &[&str],
         attributes: Option<&[&str]>,
     ) -> &mut Self {
-        if let Some(attributes) = attributes {
-            self.with_field_attributes(paths, attributes)
-        } else {
-            self
+        apply(self, joined_field_attrs(paths, attributes))
+    }
+
+    fn with_extern_paths(&mut self, mappings: &[(&str, &str)]) -> &mut Self {
+        for (proto_path, rust_path) in mappings {
+            self.extern_path(*proto_path, *rust_path);
+        }
+        self
+    }
+
+    fn with_include_file(&mut self, name: &str) -> &mut Self {
+        self.include_file(name)
+    }
+
+    fn with_protoc_args(&mut self, args: &[&str]) -> &mut Self {
+        for arg in args {
+            self.protoc_arg(arg);
         }
+        self
+    }
+
+    fn with_default_package_filename(&mut self, name: &str) -> &mut Self {
+        self.default_package_filename(name)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::tempdir;
+fn apply(config: &mut Config, attrs: Vec<Attribute>) -> &mut Config {
+    let mut skip_debug = Vec::new();
+    let mut bytes = Vec::new();
+    let mut btree_map = Vec::new();
+    for attr in attrs {
+        match attr {
+            Attribute::Type(path, attr) => {
+                config.type_attribute(path, attr);
+            }
+            Attribute::Field(path, attr) => {
+                config.field_attribute(path, attr);
+            }
+            Attribute::Message(path, attr) => {
+                config.message_attribute(path, attr);
+            }
+            Attribute::Enum(path, attr) => {
+                config.enum_attribute(path, attr);
+            }
+            Attribute::SkipDebug(path) => skip_debug.push(path),
+            Attribute::Boxed(path) => {
+                config.boxed(path);
+            }
+            Attribute::Bytes(path) => bytes.push(path),
+            Attribute::BTreeMap(path) => btree_map.push(path),
+        }
+    }
+    // `Config::skip_debug`, `Config::bytes` and `Config::btree_map` replace the paths set
+    // before, so they're called once with all of them
+    if !skip_debug.is_empty() {
+        config.skip_debug(skip_debug);
+    }
+    if !bytes.is_empty() {
+        config.bytes(bytes);
+    }
+    if !btree_map.is_empty() {
+        config.btree_map(btree_map);
+    }
+    config
+}
 
-    #[test]
-    fn test_prost_build_with_extra_attributes_should_work() {
-        let path = tempdir().unwrap();
-        let filename = path.path().join("todo.rs");
-        Config::default()
-            .out_dir(path.path())
+fn record(generator: &mut Generator<Config>, attrs: Vec<Attribute>) -> &mut Generator<Config> {
+    generator.record(attrs);
+    generator
+}
+
+impl Generator<Config> {
+    /// set the output directory of the generated code, see [`Config::out_dir`]
+    pub fn out_dir(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        let path = path.into();
+        self.builder.out_dir(&path);
+        self.out_dir = Some(path);
+        self
+    }
+
+    /// get back the wrapped config with the recorded attributes applied, dropping the
+    /// extensions
+    pub fn into_inner(mut self) -> Config {
+        let mut attrs = std::mem::take(&mut self.ordered);
+        attrs.append(&mut self.recorded);
+        apply(&mut self.builder, attrs);
+        self.builder
+    }
+
+    /// remove the type attributes added so far through `BuilderAttributes` for the given
+    /// paths, e.g. to override a shared base configuration. Attributes set on the builder
+    /// before it was wrapped are kept, prost can't remove them.
+    pub fn without_type_attributes(&mut self, paths: &[&str]) -> &mut Self {
+        self.remove_type_attributes(paths);
+        self
+    }
+
+    /// compile the protos with the wrapped config, then append the extra code
+    pub fn compile_protos(
+        &mut self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<()> {
+        let parsed = self.parse(protos)?;
+        let attrs = self.attributes(&parsed)?;
+        apply(&mut self.builder, attrs);
+        self.builder.compile_protos(protos, includes)?;
+        self.post_process(&parsed)
+    }
+
+    /// like [`compile_protos`](Self::compile_protos), but also report which of the registered
+    /// attribute paths matched an item of the protos, to catch the ones prost silently ignores
+    pub fn compile_with_report(
+        &mut self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<BuildReport> {
+        let parsed = Protos::load(protos)?;
+        let attrs = self.attributes(&parsed)?;
+        let report = self.report(&parsed, &attrs);
+        apply(&mut self.builder, attrs);
+        self.builder.compile_protos(protos, includes)?;
+        self.post_process(&parsed)?;
+        Ok(report)
+    }
+
+    /// generate `fn index_<type>_by_<key>(items: Vec<Type>) -> HashMap<Key, Type>`
+    pub fn with_index_by(&mut self, path: &str, key_field: &str) -> &mut Self {
+        self.extend(codegen::index_by(path, key_field));
+        self
+    }
+
+    /// serialize the given `google.protobuf.Duration` fields as integer milliseconds. This adds
+    /// `serde_with::serde_as` to the type and generates the `DurationMillis` adapter.
+    pub fn with_duration_millis(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::duration_millis(path, fields));
+        self
+    }
+
+    /// serialize the enum fields of the messages as the proto name of their value (e.g.
+    /// `"TODO_STATUS_DONE"`) instead of the `i32`. This adds `serde_with::serde_as` to the types
+    /// and generates `serde_with` adapter impls on the enums, which must be in the same package.
+    /// Values unknown to the enum are written as numbers, and both forms deserialize.
+    pub fn with_enum_fields_as_name(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::enum_fields_as_name(paths));
+        self
+    }
+
+    /// serialize the given `google.protobuf.Timestamp` fields as integer seconds since the Unix
+    /// epoch, dropping nanoseconds. This adds `serde_with::serde_as` to the type and generates
+    /// the `EpochSeconds` adapter.
+    pub fn with_epoch_seconds_timestamps(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::epoch_seconds(path, fields));
+        self
+    }
+
+    /// serialize the given `repeated google.protobuf.Timestamp` fields as arrays of RFC 3339
+    /// strings. This adds `serde_with::serde_as` to the type.
+    pub fn with_timestamp_array(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::timestamp_array(path, fields));
+        self
+    }
+
+    /// serialize every `google.protobuf.Timestamp` field of the compiled protos (singular,
+    /// repeated and map values, oneof members excluded) as an RFC 3339 string through
+    /// `chrono::DateTime<Utc>`. prost keeps generating `prost_types::Timestamp`, which can't be
+    /// swapped for a chrono type; `Duration` and the other well-known types are left alone. This
+    /// adds `serde_with::serde_as` to the messages and generates the `ChronoTimestamp` adapter,
+    /// which needs chrono's `serde` feature.
+    pub fn with_well_known_chrono(&mut self) -> &mut Self {
+        self.extend(codegen::chrono_timestamps());
+        self
+    }
+
+    /// like [`with_well_known_chrono`](Self::with_well_known_chrono), through
+    /// `time::OffsetDateTime` with the `TimeTimestamp` adapter, which needs time's
+    /// `serde-well-known` feature
+    pub fn with_well_known_time(&mut self) -> &mut Self {
+        self.extend(codegen::time_timestamps());
+        self
+    }
+
+    /// treat the given string fields as `rust_decimal::Decimal` numbers in serde: invalid values
+    /// are rejected and valid ones normalized on deserialization. prost still generates
+    /// `String` fields. This adds `serde_with::serde_as` to the type and generates the
+    /// `DecimalString` adapter.
+    pub fn with_decimal_fields(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::decimal_fields(path, fields));
+        self
+    }
+
+    /// treat the given string fields as `std::net::IpAddr` addresses in serde: invalid values
+    /// are rejected and valid ones normalized on deserialization. prost still generates
+    /// `String` fields. This adds `serde_with::serde_as` to the type and generates the
+    /// `IpAddrString` adapter.
+    pub fn with_ip_fields(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::ip_fields(path, fields));
+        self
+    }
+
+    /// (de)serialize the given `double` / `float` ratios as percentage strings, e.g. `0.42` as
+    /// `"42%"`, rounded to 4 decimal places. Invalid strings are rejected on deserialization.
+    /// This adds `serde_with::serde_as` to the type and generates the `Percentage` adapter.
+    pub fn with_percentage_fields(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::percentage_fields(path, fields));
+        self
+    }
+
+    /// (de)serialize the given `bytes` fields as a byte array prefixed with the data length, a
+    /// 4-byte big-endian `u32`. Deserialization rejects values whose prefix doesn't match. This
+    /// generates the `len_prefixed_bytes` module used through `#[serde(with = "...")]`.
+    pub fn with_len_prefixed_bytes(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::len_prefixed_bytes(path, fields));
+        self
+    }
+
+    /// serialize the given fields as `"***"` instead of skipping them, so the key is still
+    /// present. Deserialization accepts the real values.
+    pub fn with_serialize_redacted(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::serialize_redacted(path, fields));
+        self
+    }
+
+    /// rename every field of the message to `<prefix>_<field>` in serde, e.g. `todo_id`
+    pub fn with_serde_field_prefix(&mut self, path: &str, prefix: &str) -> &mut Self {
+        self.extend(codegen::serde_field_prefix(path, prefix));
+        self
+    }
+
+    /// derive `Debug` with `derivative` instead of prost, leaving out the given fields. prost's
+    /// own `Debug` impl is skipped for the type, see [`Config::skip_debug`].
+    pub fn with_derivative_debug(&mut self, path: &str, ignore_fields: &[&str]) -> &mut Self {
+        self.extend(codegen::derivative_debug(path, ignore_fields));
+        self
+    }
+
+    /// skip the elements of the given repeated fields that fail to deserialize instead of
+    /// rejecting the whole message. This adds `serde_with::serde_as` to the type.
+    pub fn with_vec_skip_errors(&mut self, path: &str, fields: &[&str]) -> &mut Self {
+        self.extend(codegen::vec_skip_errors(path, fields));
+        self
+    }
+
+    /// generate `fn parse_ci(s: &str) -> Option<Self>` on the given enums, matching both the
+    /// Rust variant names and the proto value names while ignoring ASCII case
+    pub fn with_enum_parse_ci(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::enum_parse_ci(paths));
+        self
+    }
+
+    /// generate `fn to_custom_str(&self) -> &'static str` and
+    /// `fn from_custom_str(s: &str) -> Option<Self>` on the enum, using the string of each
+    /// `(variant, string)` mapping, e.g. `("Doing", "in_progress")`, instead of the proto names of
+    /// `as_str_name`. Variants are given by their Rust or proto name; those without a mapping
+    /// keep their proto name. Fails to compile the protos if a variant doesn't exist or a string
+    /// is used twice.
+    pub fn with_enum_str_map(&mut self, path: &str, mappings: &[(&str, &str)]) -> &mut Self {
+        self.extend(codegen::enum_str_map(path, mappings));
+        self
+    }
+
+    /// generate `const PROTO_NAMES: &[(Self, &str)]` on the given enums, pairing each variant
+    /// with its proto name
+    pub fn with_enum_name_map(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::enum_name_map(paths));
+        self
+    }
+
+    /// generate `fn next(&self) -> Option<Self>` and `fn prev(&self) -> Option<Self>` on the
+    /// given enums, moving through the variants in the order they are declared
+    pub fn with_enum_navigation(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::enum_navigation(paths));
+        self
+    }
+
+    /// generate `fn cache_encoded_len(self) -> EncodedLenCache<Self>` on the given messages. The
+    /// wrapper memoizes `encoded_len` in a `OnceLock` and resets it on mutable access.
+    pub fn with_encoded_len_cache(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::encoded_len_cache(paths));
+        self
+    }
+
+    /// generate `fn default_instance() -> &'static Self` on the given messages, a default
+    /// message created on first use in a `OnceLock`, e.g. to compare against or reset from
+    pub fn with_default_instance(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::default_instance(paths));
+        self
+    }
+
+    /// generate read-only getters for the fields of the given messages, e.g.
+    /// `pub fn title(&self) -> &str`. Fields prost already has a getter for (enums and optional
+    /// scalars) are skipped.
+    pub fn with_getters(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::getters(paths));
+        self
+    }
+
+    /// generate `fn into_inner(self) -> T` and `From<Wrapper> for T` on messages wrapping a
+    /// single value. Fails if one of them has more or fewer fields. Panics if the protos can't
+    /// be parsed.
+    pub fn with_into_inner(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> Result<&mut Self, BuilderError> {
+        single_field_messages(proto_files, paths)?;
+        self.extend(codegen::into_inner(paths));
+        Ok(self)
+    }
+
+    /// make `variant` (its Rust or proto name, e.g. `Done` or `TODO_STATUS_DONE`) the default
+    /// of the enum. prost's derive picks the first variant as `Default`, so the variant is moved
+    /// first in the generated enum; values and ordering are unchanged. prost also uses it as the
+    /// default of message fields of the enum type, so an omitted field decodes as this variant,
+    /// while other proto3 implementations omit (and read back) `0`. Don't use it for enums in
+    /// messages exchanged with them.
+    pub fn with_enum_default(&mut self, path: &str, variant: &str) -> &mut Self {
+        self.extend(codegen::enum_default(path, variant));
+        self
+    }
+
+    /// replace the `#[repr(i32)]` prost puts on the given enums with `#[repr(<repr>)]`, e.g.
+    /// `u8` or `C`. Fails if a value doesn't fit in the integer type.
+    pub fn with_enum_repr(&mut self, paths: &[&str], repr: &str) -> &mut Self {
+        self.extend(codegen::enum_repr(paths, repr));
+        self
+    }
+
+    /// generate `fn to_metadata(&self) -> tonic::metadata::MetadataMap` on the message, copying
+    /// each `(proto_field, metadata_key)` string field into the map
+    pub fn with_metadata_fields(&mut self, path: &str, fields: &[(&str, &str)]) -> &mut Self {
+        self.extend(codegen::metadata_fields(path, fields));
+        self
+    }
+
+    /// generate `fn scrub(&self) -> Self` on the message, returning a copy with `remove_fields`
+    /// reset to their default and the string `bucket_fields` replaced by a stable hash
+    pub fn with_scrub(
+        &mut self,
+        path: &str,
+        remove_fields: &[&str],
+        bucket_fields: &[&str],
+    ) -> &mut Self {
+        self.extend(codegen::scrub(path, remove_fields, bucket_fields));
+        self
+    }
+
+    /// generate `fn redacted(&self) -> Self` on the message, returning a copy with the given
+    /// fields reset to their default, e.g. before handing it to a less trusted system
+    pub fn with_redacted_clone(&mut self, path: &str, redact_fields: &[&str]) -> &mut Self {
+        self.extend(codegen::redacted_clone(path, redact_fields));
+        self
+    }
+
+    /// generate `impl Display` for the message writing `template`, with each `{field}`
+    /// placeholder replaced by the field, e.g. `"{title} ({status})"`. A format spec can follow
+    /// the name, e.g. `{created_at:?}` for the fields that aren't `Display`. Enum fields are
+    /// written as their `i32`. Fails to compile the protos if a field doesn't exist.
+    pub fn with_display_template(&mut self, path: &str, template: &str) -> &mut Self {
+        self.extend(codegen::display_template(path, template));
+        self
+    }
+
+    /// replace prost's `Debug` impl of the message with one printing the given fields as
+    /// `"***"`, so secrets don't end up in logs. Unlike prost's impl, enum fields are printed as
+    /// their `i32`. prost's own `Debug` is skipped for the type, see [`Config::skip_debug`].
+    pub fn with_redacted_debug(&mut self, path: &str, redact_fields: &[&str]) -> &mut Self {
+        self.extend(codegen::redacted_debug(path, redact_fields));
+        self
+    }
+
+    /// generate `struct <Type>Patch`, a copy of the message with every field wrapped in an
+    /// `Option`, and `fn apply_patch(&mut self, patch: <Type>Patch)` overwriting the fields
+    /// that are set, e.g. for PATCH-style APIs.
+    pub fn with_partial_type(&mut self, path: &str) -> &mut Self {
+        self.extend(codegen::partial_type(path));
+        self
+    }
+
+    /// generate `struct <Type>Cow<'a>`, a copy of the message with the given string fields
+    /// (e.g. `todo.Todo.title`) as `Cow<'a, str>` for zero-copy parsing, and
+    /// `From<<Type>Cow<'_>> for <Type>`. prost's derive only encodes `String`s, so the message
+    /// itself is unchanged. Fails if a field isn't a singular string. Panics if the protos
+    /// can't be parsed.
+    pub fn with_cow_fields(
+        &mut self,
+        proto_files: &[&str],
+        fields: &[&str],
+    ) -> Result<&mut Self, BuilderError> {
+        string_fields(proto_files, fields)?;
+        self.extend(codegen::cow_fields(fields));
+        Ok(self)
+    }
+
+    /// generate `struct <Type>Secret`, a copy of the message with the given string fields (e.g.
+    /// `account.Account.token`) as `secrecy::Secret<String>`, and `From` both ways. prost's
+    /// derive only encodes `String`s and forces `PartialEq`, which `Secret` doesn't implement, so
+    /// the message itself is unchanged and the copy only derives `Debug`: convert into it right
+    /// after decoding and back right before encoding. Needs `secrecy` 0.8 as a dependency. Fails
+    /// if a field isn't a singular string. Panics if the protos can't be parsed.
+    pub fn with_secret_fields(
+        &mut self,
+        proto_files: &[&str],
+        fields: &[&str],
+    ) -> Result<&mut Self, BuilderError> {
+        string_fields(proto_files, fields)?;
+        self.extend(codegen::secret_fields(fields));
+        Ok(self)
+    }
+
+    /// generate a newtype for each `(field, newtype)` string field, e.g.
+    /// `("todo.Todo.id", "TodoId")` for `struct TodoId(String)`, with `From` conversions both ways,
+    /// `Display` and a `typed_id()` getter on the message. prost's derive encodes and defaults
+    /// the field as a `String`, so the field itself keeps its type. Fields sharing a newtype in
+    /// the same module get it once. Fails if a field isn't a singular string. Panics if the
+    /// protos can't be parsed.
+    pub fn with_typed_ids(
+        &mut self,
+        proto_files: &[&str],
+        fields: &[(&str, &str)],
+    ) -> Result<&mut Self, BuilderError> {
+        let paths: Vec<&str> = fields.iter().map(|(path, _)| *path).collect();
+        string_fields(proto_files, &paths)?;
+        self.extend(codegen::typed_ids(fields));
+        Ok(self)
+    }
+
+    /// generate `struct <mirror_name>`, a copy of the message fields with
+    /// `#[serde(remote = "<Type>")]`, so the message can be (de)serialized through
+    /// `#[serde(with = "<mirror_name>")]` without deriving serde on it. The field types must
+    /// implement serde themselves (or carry a `#[serde(with = ...)]`), which the well-known
+    /// types like `Timestamp` don't.
+    pub fn with_serde_remote(&mut self, path: &str, mirror_name: &str) -> &mut Self {
+        self.extend(codegen::serde_remote(path, mirror_name));
+        self
+    }
+
+    /// generate `fn checksum(&self) -> u64` on the given messages, a stable hash of their
+    /// encoded bytes for change detection
+    pub fn with_checksum(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::checksum(paths));
+        self
+    }
+
+    /// generate `fn mock() -> Self` on the given messages, filling every field with a
+    /// deterministic placeholder for tests: strings hold the field name, numbers the field
+    /// number, enums their last value and nested messages their default
+    pub fn with_mock(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::mock(paths));
+        self
+    }
+
+    /// generate `fn present_fields(&self) -> u64` on the given messages, a bitset of the fields
+    /// holding a non-default value, with a `<FIELD>_BIT` constant for the position of each
+    /// field. Fails for messages with more than 64 fields.
+    pub fn with_presence_bitset(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::presence_bitset(paths));
+        self
+    }
+
+    /// add `#[allow(missing_docs)]` to every top-level item of the generated files, for crates
+    /// that deny `missing_docs`. It only applies to the generated code, not to the module it is
+    /// included in.
+    pub fn allow_missing_docs(&mut self) -> &mut Self {
+        self.extend(codegen::allow_missing_docs());
+        self
+    }
+
+    /// once the code is generated, print a `cargo:warning` for every trait derived more than
+    /// once on a type, e.g. `Clone` added through `with_type_attributes` on top of prost's own
+    /// derive, which otherwise only shows up as a confusing compile error
+    pub fn warn_on_duplicate_derives(&mut self) -> &mut Self {
+        self.extend(codegen::warn_duplicate_derives());
+        self
+    }
+
+    /// generate `fn validate(&self) -> Result<(), String>` on the given messages, enforcing the
+    /// `protoc-gen-validate` rules (`(validate.rules)` field options) of their fields. The
+    /// `pattern` rule needs the `regex` crate.
+    pub fn with_pgv_validation(&mut self, paths: &[&str]) -> &mut Self {
+        self.extend(codegen::pgv_validation(paths));
+        self
+    }
+}
+
+impl BuilderAttributes for Generator<Config> {
+    fn with_serde(
+        &mut self,
+        paths: &[&str],
+        ser: bool,
+        de: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        record(self, type_attrs(paths, serde_attr(ser, de), extra_attrs))
+    }
+
+    fn with_serde_excluding(
+        &mut self,
+        paths: &[&str],
+        exclude: &[&str],
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        record(self, serde_excluding_attrs(paths, exclude, ser, de))
+    }
+
+    fn with_serde_as(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
+        record(self, serde_as_attrs(path, fields))
+    }
+
+    fn with_serde_as_vec(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
+        record(self, serde_as_vec_attrs(path, fields))
+    }
+
+    fn with_serde_as_map(&mut self, path: &str, fields: &[(&[&str], &str)]) -> &mut Self {
+        record(self, serde_as_map_attrs(path, fields))
+    }
+
+    fn with_serde_as_custom(
+        &mut self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> &mut Self {
+        record(self, serde_as_custom_attrs(path, fields, container))
+    }
+
+    fn with_i64_as_string(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        record(self, i64_as_string_attrs(proto_files, paths))
+    }
+
+    fn with_base64_bytes(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        record(self, base64_bytes_attrs(proto_files, paths))
+    }
+
+    fn with_comments_as_schema_desc(&mut self, proto_files: &[&str], paths: &[&str]) -> &mut Self {
+        record(self, comments_as_schema_desc_attrs(proto_files, paths))
+    }
+
+    fn with_serde_tag(&mut self, paths: &[&str], tag: &str) -> &mut Self {
+        record(self, type_attrs(paths, &serde_tag_attr(tag), None))
+    }
+
+    fn with_serde_tag_content(&mut self, paths: &[&str], tag: &str, content: &str) -> &mut Self {
+        record(
+            self,
+            type_attrs(paths, &serde_tag_content_attr(tag, content), None),
+        )
+    }
+
+    fn with_serde_untagged(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, type_attrs(paths, serde_untagged_attr(), None))
+    }
+
+    fn with_serde_flatten(&mut self, fields: &[&str]) -> &mut Self {
+        record(
+            self,
+            joined_field_attrs(fields, Some(&[serde_flatten_attr()])),
+        )
+    }
+
+    fn with_serde_deny_unknown_fields(&mut self, paths: &[&str]) -> &mut Self {
+        record(
+            self,
+            type_attrs(paths, serde_deny_unknown_fields_attr(), None),
+        )
+    }
+
+    fn with_field_serde_with(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        record(self, serde_with_attrs(fields))
+    }
+
+    fn with_serde_enum_original_names(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> &mut Self {
+        record(self, enum_original_names_attrs(proto_files, paths))
+    }
+
+    fn with_serde_all(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Message, TypeKind::Enum];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, ser, de, None),
+        )
+    }
+
+    fn with_serde_all_from_descriptor(
+        &mut self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> &mut Self {
+        record(
+            self,
+            serde_descriptor_attrs(descriptor_path, package, ser, de),
+        )
+    }
+
+    fn with_serde_enums(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Enum];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_serde_enum_rename(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self {
+        let attr = serde_rename_all_attr(rename);
+        record(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_rename_all_fields(&mut self, paths: &[&str], rename: RenameAll) -> &mut Self {
+        let attr = serde_rename_all_fields_attr(rename);
+        record(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_messages(
+        &mut self,
+        proto_files: &[&str],
+        package: &str,
+        rename: RenameAll,
+    ) -> &mut Self {
+        let kinds = [TypeKind::Message];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_sqlx_type(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, sqlx_type_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, sqlx_from_row_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row_opts(
+        &mut self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = sqlx_from_row_opts_attr(rename_all);
+        record(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_api_preset(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, api_preset_attrs(paths))
+    }
+
+    fn with_db_preset(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, db_preset_attrs(paths))
+    }
+
+    fn with_profile(&mut self, paths: &[&str], profile: &Profile) -> &mut Self {
+        record(self, profile_attrs(paths, profile))
+    }
+
+    fn with_derive_builder(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, derive_builder_attr(), extra_attrs))
+    }
+
+    fn with_builder_field_default(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        record(self, builder_defaults_attrs(fields))
+    }
+
+    fn with_diesel(
+        &mut self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = diesel_attr(table_name, queryable, insertable);
+        record(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_sea_orm(
+        &mut self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        record(
+            self,
+            sea_orm_attrs(path, table_name, primary_keys, extra_attrs),
+        )
+    }
+
+    fn with_zeroize(
+        &mut self,
+        paths: &[&str],
+        on_drop: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        record(self, type_attrs(paths, zeroize_attr(on_drop), extra_attrs))
+    }
+
+    fn with_arbitrary(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, arbitrary_attr(), extra_attrs))
+    }
+
+    fn with_proptest(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, proptest_attr(), extra_attrs))
+    }
+
+    fn with_async_graphql_object(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        let attr = async_graphql_simple_object_attr();
+        record(self, type_attrs(paths, attr, extra_attrs))
+    }
+
+    fn with_async_graphql_enum(
+        &mut self,
+        paths: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> &mut Self {
+        record(
+            self,
+            type_attrs(paths, async_graphql_enum_attr(), extra_attrs),
+        )
+    }
+
+    fn with_rkyv(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, rkyv_attr(), extra_attrs))
+    }
+
+    fn with_fake(&mut self, paths: &[&str], field_fakers: &[(&str, &str)]) -> &mut Self {
+        record(self, fake_attrs(paths, field_fakers))
+    }
+
+    fn with_clap(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, clap_parser_attr(), extra_attrs))
+    }
+
+    fn with_clap_args(&mut self, fields: &[(&str, &str)]) -> &mut Self {
+        record(self, clap_args_attrs(fields))
+    }
+
+    fn with_derive_more(&mut self, paths: &[&str], traits: &[&str]) -> &mut Self {
+        record(self, type_attrs(paths, &derive_more_attr(traits), None))
+    }
+
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        &mut self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> &mut Self {
+        record(self, derive_where_attrs(proto_files, predicate, derives))
+    }
+
+    fn with_eq_hash_safe(
+        &mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> Result<&mut Self, BuilderError> {
+        let attrs = eq_hash_safe_attrs(proto_files, paths)?;
+        Ok(record(self, attrs))
+    }
+
+    fn derive_eq_where_possible(&mut self, proto_files: &[&str]) -> &mut Self {
+        record(self, eq_where_possible_attrs(proto_files))
+    }
+
+    fn with_non_exhaustive(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, type_attrs(paths, non_exhaustive_attr(), None))
+    }
+
+    fn with_ord(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, type_attrs(paths, ord_attr(), None))
+    }
+
+    fn with_deprecated_types(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = deprecated_attr(note);
+        record(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_deprecated_fields(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = deprecated_attr(note);
+        record(self, joined_field_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_must_use(&mut self, paths: &[&str], note: Option<&str>) -> &mut Self {
+        let attr = must_use_attr(note);
+        record(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_strum(&mut self, paths: &[&str], extra_attrs: Option<&[&str]>) -> &mut Self {
+        record(self, type_attrs(paths, strum_attr(), extra_attrs))
+    }
+
+    fn with_type_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        record(self, joined_type_attrs(paths, Some(attributes)))
+    }
+
+    fn with_message_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        record(self, joined_message_attrs(paths, Some(attributes)))
+    }
+
+    fn with_enum_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        record(self, joined_enum_attrs(paths, Some(attributes)))
+    }
+
+    fn with_ordered_attributes(&mut self, paths: &[&str], ordered: &[&str]) -> &mut Self {
+        self.record_ordered(joined_type_attrs(paths, Some(ordered)));
+        self
+    }
+
+    fn with_attribute_map<I, P, A, S>(&mut self, map: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        record(self, attribute_map_attrs(map))
+    }
+
+    fn with_field_attributes(&mut self, paths: &[&str], attributes: &[&str]) -> &mut Self {
+        record(self, joined_field_attrs(paths, Some(attributes)))
+    }
+
+    fn with_boxed_fields(&mut self, fields: &[&str]) -> &mut Self {
+        record(self, boxed_attrs(fields))
+    }
+
+    fn with_bytes(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, bytes_attrs(paths))
+    }
+
+    fn with_btree_maps(&mut self, paths: &[&str]) -> &mut Self {
+        record(self, btree_map_attrs(paths))
+    }
+
+    fn with_field_attributes_by_type(
+        &mut self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> &mut Self {
+        record(
+            self,
+            fields_by_type_attrs(proto_files, proto_type, attribute),
+        )
+    }
+
+    fn with_field_attributes_by_tag(
+        &mut self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> &mut Self {
+        record(self, field_attributes_by_tag_attrs(proto_files, targets))
+    }
+
+    fn with_field_attributes_matching(
+        &mut self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<&mut Self, BuilderError> {
+        let attrs = fields_matching_attrs(proto_files, pattern, attribute)?;
+        Ok(record(self, attrs))
+    }
+
+    fn with_optional_type_attributes(
+        &mut self,
+        paths: &[&str],
+        attributes: Option<&[&str]>,
+    ) -> &mut Self {
+        record(self, joined_type_attrs(paths, attributes))
+    }
+
+    fn with_optional_field_attributes(
+        &mut self,
+        paths: &[&str],
+        attributes: Option<&[&str]>,
+    ) -> &mut Self {
+        record(self, joined_field_attrs(paths, attributes))
+    }
+
+    fn with_extern_paths(&mut self, mappings: &[(&str, &str)]) -> &mut Self {
+        self.builder.with_extern_paths(mappings);
+        self
+    }
+
+    fn with_include_file(&mut self, name: &str) -> &mut Self {
+        self.builder.include_file(name);
+        self
+    }
+
+    fn with_protoc_args(&mut self, args: &[&str]) -> &mut Self {
+        self.builder.with_protoc_args(args);
+        self
+    }
+
+    fn with_default_package_filename(&mut self, name: &str) -> &mut Self {
+        self.builder.default_package_filename(name);
+        self.default_package_filename = Some(name.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_prost_build_with_extra_attributes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Config::default()
+            .out_dir(path.path())
             .with_serde(
                 &["todo.Todo", "todo.TodoStatus"],
                 true,
@@ -213,56 +1809,1179 @@ mod tests {
             #[derive(Copy)]
             pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
         }
-        #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct GetTodosRequest {
-            #[prost(string, repeated, tag = "1")]
-            pub id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct GetTodosRequest {
+            #[prost(string, repeated, tag = "1")]
+            pub id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct CreateTodoRequest {
+            #[prost(string, tag = "1")]
+            pub title: ::prost::alloc::string::String,
+            #[prost(string, tag = "2")]
+            pub description: ::prost::alloc::string::String,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct DeleteTodoRequest {
+            #[prost(string, tag = "1")]
+            pub id: ::prost::alloc::string::String,
+        }
+        #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+        pub struct DeleteTodoResponse {}
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #[derive(sqlx::Type)]
+        #[derive(strum::EnumString, strum::Display, strum::EnumIter)]
+        #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+        #[repr(i32)]
+        pub enum TodoStatus {
+            Doing = 0,
+            Done = 1,
+        }
+        impl TodoStatus {
+            /// String value of the enum field names used in the ProtoBuf definition.
+            ///
+            /// The values are not transformed in any way and thus are considered stable
+            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+            pub fn as_str_name(&self) -> &'static str {
+                match self {
+                    Self::Doing => "TODO_STATUS_DOING",
+                    Self::Done => "TODO_STATUS_DONE",
+                }
+            }
+            /// Creates an enum from field names used in the ProtoBuf definition.
+            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+                match value {
+                    "TODO_STATUS_DOING" => Some(Self::Doing),
+                    "TODO_STATUS_DONE" => Some(Self::Done),
+                    _ => None,
+                }
+            }
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_prost_build_with_index_by_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_index_by("todo.Todo", "id")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[derive(serde::Serialize, serde::Deserialize)]"));
+        assert!(content.ends_with(
+            r#"/// Index a list of [`Todo`] by its `id` field.
+pub fn index_todo_by_id(
+    items: ::prost::alloc::vec::Vec<Todo>,
+) -> ::std::collections::HashMap<::prost::alloc::string::String, Todo> {
+    items.into_iter().map(|item| (item.id.clone(), item)).collect()
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_duration_millis_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("job.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["job.Job"], true, true, None)
+            .with_duration_millis("job.Job", &["timeout", "retry_delays", "step_timeouts"])
+            .compile_protos(&["fixtures/protos/job.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(message, optional, tag = "2")]
+    #[serde_as(as = "Option<DurationMillis>")]
+    pub timeout: ::core::option::Option<::prost_types::Duration>,"#
+        ));
+        assert!(content.contains(
+            r#"    #[prost(message, repeated, tag = "3")]
+    #[serde_as(as = "Vec<DurationMillis>")]
+    pub retry_delays: ::prost::alloc::vec::Vec<::prost_types::Duration>,"#
+        ));
+        assert!(content.contains(
+            r#"    #[serde_as(as = "::std::collections::HashMap<_, DurationMillis>")]
+    pub step_timeouts: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost_types::Duration,
+    >,"#
+        ));
+        assert!(content.contains("#[serde_with::serde_as]\n#[serde_with::skip_serializing_none]"));
+        assert!(content.contains("seconds: millis / 1000,"));
+        assert!(content.contains("nanos: (millis % 1000) as i32 * 1_000_000,"));
+        assert!(content.contains(".saturating_add(i64::from(source.nanos / 1_000_000));"));
+        assert_eq!(content.matches("pub struct DurationMillis;").count(), 1);
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_fields_as_name_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_enum_fields_as_name(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Todo {"#
+        ));
+        assert!(content.contains(
+            r#"    #[prost(enumeration = "TodoStatus", tag = "4")]
+    #[serde_as(as = "TodoStatus")]
+    pub status: i32,"#
+        ));
+        assert!(content.contains("impl ::serde_with::SerializeAs<i32> for TodoStatus {"));
+        assert!(content.contains("Ok(value) => serializer.serialize_str(value.as_str_name()),"));
+        assert!(
+            content.contains("impl<'de> ::serde_with::DeserializeAs<'de, i32> for TodoStatus {")
+        );
+        assert!(content.contains("fn visit_u64<E>(self, value: u64)"));
+        assert!(content.contains("deserializer.deserialize_any(Visitor)"));
+    }
+
+    #[test]
+    fn test_prost_build_with_epoch_seconds_timestamps_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_epoch_seconds_timestamps("todo.Todo", &["created_at"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(message, optional, tag = "5")]
+    #[serde_as(as = "Option<EpochSeconds>")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,"#
+        ));
+        assert!(content.contains("serializer.serialize_i64(source.seconds)"));
+        assert!(content.contains("Ok(::prost_types::Timestamp {\n            seconds,\n            nanos: 0,\n        })"));
+        assert_eq!(content.matches("pub struct EpochSeconds;").count(), 1);
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_epoch_seconds_timestamps("todo.Todo", &["title"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `title` of `todo.Todo` is not a `google.protobuf.Timestamp`"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_well_known_chrono_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_well_known_chrono()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(message, optional, tag = "5")]
+    #[serde_as(as = "Option<ChronoTimestamp>")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "6")]
+    #[serde_as(as = "Option<ChronoTimestamp>")]
+    pub updated_at: ::core::option::Option<::prost_types::Timestamp>,"#
+        ));
+        assert_eq!(content.matches("#[serde_with::serde_as]").count(), 1);
+        assert_eq!(content.matches("pub struct ChronoTimestamp;").count(), 1);
+
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_well_known_time()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(path.path().join("todo.rs")).unwrap();
+        assert!(content.contains(r#"#[serde_as(as = "Option<TimeTimestamp>")]"#));
+        assert!(content.contains("::time::serde::rfc3339::serialize(&time, serializer)"));
+    }
+
+    #[test]
+    fn test_prost_build_with_vec_skip_errors_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.GetTodosRequest"], true, true, None)
+            .with_vec_skip_errors("todo.GetTodosRequest", &["id"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[serde_with::serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTodosRequest {
+    #[prost(string, repeated, tag = "1")]
+    #[serde_as(as = "serde_with::VecSkipError<_>")]
+    pub id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,"#
+        ));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_vec_skip_errors("todo.Todo", &["title"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `title` of `todo.Todo` is not a repeated field"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_timestamp_array_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("job.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["job.Job"], true, true, None)
+            .with_timestamp_array("job.Job", &["runs"])
+            .compile_protos(&["fixtures/protos/job.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(message, repeated, tag = "4")]
+    #[serde_as(as = "Vec<serde_with::DisplayFromStr>")]
+    pub runs: ::prost::alloc::vec::Vec<::prost_types::Timestamp>,"#
+        ));
+        assert!(content.contains("#[serde_with::serde_as]\n#[serde_with::skip_serializing_none]"));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_timestamp_array("job.Job", &["retry_delays"])
+            .compile_protos(&["fixtures/protos/job.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `retry_delays` of `job.Job` is not a `google.protobuf.Timestamp`"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_serde_field_prefix_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_serde_field_prefix("todo.Todo", "todo_")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for field in [
+            "id",
+            "title",
+            "description",
+            "status",
+            "created_at",
+            "updated_at",
+        ] {
+            assert!(content.contains(&format!(
+                "    #[serde(rename = \"todo_{field}\")]\n    pub {field}: "
+            )));
+        }
+    }
+
+    #[test]
+    fn test_prost_build_with_decimal_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("order.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["order.Order"], true, true, None)
+            .with_decimal_fields("order.Order", &["amount", "discount", "line_amounts"])
+            .compile_protos(&["fixtures/protos/order.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "2")]
+    #[serde_as(as = "DecimalString")]
+    pub amount: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    #[serde_as(as = "Option<DecimalString>")]
+    pub discount: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "4")]
+    #[serde_as(as = "Vec<DecimalString>")]
+    pub line_amounts: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,"#
+        ));
+        assert!(content.contains("let decimal: ::rust_decimal::Decimal = value"));
+        assert!(content.contains("serializer.serialize_str(source)"));
+        assert_eq!(content.matches("pub struct DecimalString;").count(), 1);
+    }
+
+    #[test]
+    fn test_prost_build_with_ip_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("peer.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["peer.Peer"], true, true, None)
+            .with_ip_fields("peer.Peer", &["address", "gateway", "allowed"])
+            .compile_protos(&["fixtures/protos/peer.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "2")]
+    #[serde_as(as = "IpAddrString")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    #[serde_as(as = "Option<IpAddrString>")]
+    pub gateway: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "4")]
+    #[serde_as(as = "Vec<IpAddrString>")]
+    pub allowed: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,"#
+        ));
+        assert!(content.contains("let ip: ::std::net::IpAddr = value"));
+        assert_eq!(content.matches("pub struct IpAddrString;").count(), 1);
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_ip_fields("peer.Peer", &["raw_address"])
+            .compile_protos(&["fixtures/protos/peer.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `raw_address` of `peer.Peer` is not a string field"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_percentage_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("order.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["order.Order"], true, true, None)
+            .with_percentage_fields("order.Order", &["discount_rate", "tax_rate"])
+            .compile_protos(&["fixtures/protos/order.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(double, tag = "5")]
+    #[serde_as(as = "Percentage")]
+    pub discount_rate: f64,
+    #[prost(float, optional, tag = "6")]
+    #[serde_as(as = "Option<Percentage>")]
+    pub tax_rate: ::core::option::Option<f32>,"#
+        ));
+        assert!(
+            content.contains("let percent = ::prost::alloc::format!(\"{:.4}\", ratio * 100.0);")
+        );
+        assert!(content.contains(".strip_suffix('%')"));
+        assert!(
+            content.contains("impl<'de> ::serde_with::DeserializeAs<'de, f32> for Percentage {")
+        );
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_percentage_fields("order.Order", &["amount"])
+            .compile_protos(&["fixtures/protos/order.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `amount` of `order.Order` is not a double or float field"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_len_prefixed_bytes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("peer.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["peer.Peer"], true, true, None)
+            .with_len_prefixed_bytes("peer.Peer", &["raw_address"])
+            .compile_protos(&["fixtures/protos/peer.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[serde(with = "len_prefixed_bytes")]
+    pub raw_address: ::prost::alloc::vec::Vec<u8>,"#
+        ));
+        assert!(content.contains("buf.extend_from_slice(&len.to_be_bytes());"));
+        assert!(content.contains("if len != data.len() {"));
+        assert_eq!(content.matches("pub mod len_prefixed_bytes {").count(), 1);
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_len_prefixed_bytes("peer.Peer", &["address"])
+            .compile_protos(&["fixtures/protos/peer.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `address` of `peer.Peer` is not a singular bytes field"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_serialize_redacted_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_serialize_redacted("todo.Todo", &["title", "description"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "2")]
+    #[serde(serialize_with = "serialize_redacted")]
+    pub title: ::prost::alloc::string::String,"#
+        ));
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "3")]
+    #[serde(serialize_with = "serialize_redacted")]
+    pub description: ::prost::alloc::string::String,"#
+        ));
+        assert!(content.ends_with(
+            r#"/// Serialize any value as `"***"`, for fields that must not leak into serialized output.
+pub fn serialize_redacted<T, S>(
+    _: &T,
+    serializer: S,
+) -> ::core::result::Result<S::Ok, S::Error>
+where
+    S: ::serde::Serializer,
+{
+    serializer.serialize_str("***")
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_str_map_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_str_map("todo.TodoStatus", &[("Doing", "in_progress")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.ends_with(
+            r#"impl TodoStatus {
+    /// The custom string of the variant, its proto name if it has none.
+    pub fn to_custom_str(&self) -> &'static str {
+        match self {
+            Self::Doing => "in_progress",
+            Self::Done => "TODO_STATUS_DONE",
+        }
+    }
+    /// The variant with the given custom string (or proto name if it has none).
+    pub fn from_custom_str(s: &str) -> ::core::option::Option<Self> {
+        match s {
+            "in_progress" => Some(Self::Doing),
+            "TODO_STATUS_DONE" => Some(Self::Done),
+            _ => None,
+        }
+    }
+}
+"#
+        ));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_str_map("todo.TodoStatus", &[("Todo", "todo")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`Todo` is not a value of `todo.TodoStatus`"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_parse_ci_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_parse_ci(&["todo.TodoStatus"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // "done", "DONE" and "todo_status_done" all parse into `TodoStatus::Done`
+        assert!(content.ends_with(
+            r#"impl TodoStatus {
+    /// Parse a variant from its Rust name (e.g. `Done`) or its proto name
+    /// (e.g. `TODO_STATUS_DONE`), ignoring ASCII case.
+    pub fn parse_ci(s: &str) -> ::core::option::Option<Self> {
+        match s {
+            s if s.eq_ignore_ascii_case("Doing")
+                || s.eq_ignore_ascii_case("TODO_STATUS_DOING") => Some(Self::Doing),
+            s if s.eq_ignore_ascii_case("Done")
+                || s.eq_ignore_ascii_case("TODO_STATUS_DONE") => Some(Self::Done),
+            _ => None,
+        }
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_parse_ci_on_aliases_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("alias.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_parse_ci(&["alias.Level"])
+            .compile_protos(&["fixtures/protos/alias.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(!content.contains("Self::Warn)"));
+        assert!(content.contains(
+            r#"            s if s.eq_ignore_ascii_case("Warning")
+                || s.eq_ignore_ascii_case("LEVEL_WARNING")
+                || s.eq_ignore_ascii_case("LEVEL_WARN") => Some(Self::Warning),
+            s if s.eq_ignore_ascii_case("Error")
+                || s.eq_ignore_ascii_case("LEVEL_ERROR") => Some(Self::Error),"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_name_map_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_name_map(&["todo.TodoStatus"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.ends_with(
+            r#"impl TodoStatus {
+    /// Every variant with its name in the ProtoBuf definition, see `as_str_name`.
+    pub const PROTO_NAMES: &'static [(Self, &'static str)] = &[
+        (Self::Doing, "TODO_STATUS_DOING"),
+        (Self::Done, "TODO_STATUS_DONE"),
+    ];
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_enum_navigation_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_enum_navigation(&["todo.TodoStatus"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // `Doing.next() == Some(Done)`, `Done.next() == None`
+        assert!(content.ends_with(
+            r#"impl TodoStatus {
+    /// The variant declared after this one, if any.
+    pub fn next(&self) -> ::core::option::Option<Self> {
+        match self {
+            Self::Doing => Some(Self::Done),
+            Self::Done => None,
+        }
+    }
+    /// The variant declared before this one, if any.
+    pub fn prev(&self) -> ::core::option::Option<Self> {
+        match self {
+            Self::Doing => None,
+            Self::Done => Some(Self::Doing),
         }
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_pgv_validation_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("task.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_pgv_validation(&["task.CreateTaskRequest"])
+            .compile_protos(&["fixtures/protos/task.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // a too short title fails validation
+        assert!(content.contains(
+            r#"        {
+            let value = &self.title;
+            if value.chars().count() < 3 {
+                return Err("title: must be at least 3 characters long".into());
+            }
+            if value.chars().count() > 64 {
+                return Err("title: must be at most 64 characters long".into());
+            }
+        }"#
+        ));
+        assert!(content.contains(r#"::regex::Regex::new("^[a-z0-9-]+$")"#));
+        assert!(content.contains("if *value >= 5i32 {"));
+        assert!(content.contains(r#"return Err("tags: must have at most 10 items".into());"#));
+        assert!(content.contains(r#"return Err("due_at: is required".into());"#));
+        // optional fields are only checked when set
+        assert!(content.contains(
+            r#"        if let Some(value) = &self.note {
+            if value.chars().count() > 280 {"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_derivative_debug_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_derivative_debug("todo.CreateTodoRequest", &["description"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        let start = content.find("#[derive(derivative::Derivative)]").unwrap();
+        let end = content.find("pub struct DeleteTodoRequest").unwrap();
+        insta::assert_snapshot!(&content[start..end], @r###"
+        #[derive(derivative::Derivative)]
+        #[derivative(Debug)]
         #[derive(Clone, PartialEq, ::prost::Message)]
+        #[prost(skip_debug)]
         pub struct CreateTodoRequest {
             #[prost(string, tag = "1")]
             pub title: ::prost::alloc::string::String,
             #[prost(string, tag = "2")]
+            #[derivative(Debug = "ignore")]
             pub description: ::prost::alloc::string::String,
         }
         #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct DeleteTodoRequest {
-            #[prost(string, tag = "1")]
-            pub id: ::prost::alloc::string::String,
-        }
-        #[derive(Clone, Copy, PartialEq, ::prost::Message)]
-        pub struct DeleteTodoResponse {}
-        #[derive(serde::Serialize, serde::Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        #[derive(sqlx::Type)]
-        #[derive(strum::EnumString, strum::Display, strum::EnumIter)]
-        #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
-        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-        #[repr(i32)]
-        pub enum TodoStatus {
-            Doing = 0,
-            Done = 1,
+        "###);
+    }
+
+    #[test]
+    fn test_prost_build_with_metadata_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_metadata_fields(
+                "todo.Todo",
+                &[("id", "x-todo-id"), ("title", "x-todo-title")],
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.ends_with(
+            r#"impl Todo {
+    /// Copy fields into gRPC metadata, e.g. to propagate them on outgoing calls.
+    /// Values that aren't valid ASCII metadata are left out.
+    pub fn to_metadata(&self) -> ::tonic::metadata::MetadataMap {
+        let mut map = ::tonic::metadata::MetadataMap::new();
+        {
+            let value = &self.id;
+            if let Ok(value) = value.parse() {
+                map.insert("x-todo-id", value);
+            }
         }
-        impl TodoStatus {
-            /// String value of the enum field names used in the ProtoBuf definition.
-            ///
-            /// The values are not transformed in any way and thus are considered stable
-            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-            pub fn as_str_name(&self) -> &'static str {
-                match self {
-                    Self::Doing => "TODO_STATUS_DOING",
-                    Self::Done => "TODO_STATUS_DONE",
-                }
+        {
+            let value = &self.title;
+            if let Ok(value) = value.parse() {
+                map.insert("x-todo-title", value);
             }
-            /// Creates an enum from field names used in the ProtoBuf definition.
-            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-                match value {
-                    "TODO_STATUS_DOING" => Some(Self::Doing),
-                    "TODO_STATUS_DONE" => Some(Self::Done),
-                    _ => None,
-                }
+        }
+        map
+    }
+}
+"#
+        ));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_metadata_fields("todo.Todo", &[("id", "X-Todo-Id")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`X-Todo-Id` is not a valid ASCII metadata key"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_encoded_len_cache_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_encoded_len_cache(&["todo.Todo", "todo.CreateTodoRequest"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // the cached value is whatever `encoded_len` returned, until the message is mutated
+        assert!(content.contains("*self.encoded_len.get_or_init(|| self.message.encoded_len())"));
+        assert!(content.contains(
+            r#"    fn deref_mut(&mut self) -> &mut T {
+        self.encoded_len.take();
+        &mut self.message
+    }"#
+        ));
+        assert_eq!(content.matches("pub struct EncodedLenCache<T>").count(), 1);
+        assert!(content.ends_with(
+            r#"impl CreateTodoRequest {
+    /// Wrap the message to compute its `encoded_len` only once.
+    pub fn cache_encoded_len(self) -> EncodedLenCache<Self> {
+        EncodedLenCache::new(self)
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_scrub_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_scrub("todo.Todo", &["description", "created_at"], &["title"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.ends_with(
+            r#"impl Todo {
+    /// A copy of the message safe for analytics: PII fields are cleared and
+    /// high-cardinality fields replaced with a bucket id, see [`scrub_bucket`].
+    pub fn scrub(&self) -> Self {
+        let mut scrubbed = self.clone();
+        scrubbed.description = Default::default();
+        scrubbed.created_at = Default::default();
+        scrubbed.title = scrub_bucket(&scrubbed.title);
+        scrubbed
+    }
+}
+"#
+        ));
+        assert!(content
+            .contains("pub fn scrub_bucket(value: &str) -> ::prost::alloc::string::String {"));
+    }
+
+    #[test]
+    fn test_prost_build_with_redacted_clone_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_redacted_clone("todo.Todo", &["description", "created_at"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // only the listed fields are reset, the clone keeps the others
+        assert!(content.ends_with(
+            r#"impl Todo {
+    /// A copy of the message with the sensitive fields reset to their default.
+    pub fn redacted(&self) -> Self {
+        let mut redacted = self.clone();
+        redacted.description = Default::default();
+        redacted.created_at = Default::default();
+        redacted
+    }
+}
+"#
+        ));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_redacted_clone("todo.Todo", &["secret"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "field `secret` not found in `todo.Todo`");
+    }
+
+    #[test]
+    fn test_prost_build_with_display_template_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_display_template("todo.Todo", "{title} ({status})")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.ends_with(
+            r#"impl ::core::fmt::Display for Todo {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        write!(f, "{} ({})", self.title, self.status)
+    }
+}
+"#
+        ));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_display_template("todo.Todo", "{name}")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "field `name` not found in `todo.Todo`");
+    }
+
+    #[test]
+    fn test_prost_build_with_redacted_debug_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("account.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_redacted_debug("account.Account", &["password"])
+            .compile_protos(&["fixtures/protos/account.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[prost(skip_debug)]"));
+        assert!(content.contains("impl ::core::fmt::Debug for Account {"));
+        assert!(content.contains(r#".field("version", &self.version)"#));
+        assert!(content.contains(r#".field("password", &"***")"#));
+
+        let err = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_redacted_debug("account.Account", &["secret"])
+            .compile_protos(&["fixtures/protos/account.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field `secret` not found in `account.Account`"
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_partial_type_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_partial_type("todo.Todo")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TodoPatch {
+    pub id: ::core::option::Option<::prost::alloc::string::String>,
+    pub title: ::core::option::Option<::prost::alloc::string::String>,"#
+        ));
+        // patching only `title` leaves the other fields alone
+        assert!(content.contains(
+            r#"    pub fn apply_patch(&mut self, patch: TodoPatch) {
+        if let Some(value) = patch.id {
+            self.id = value;
+        }
+        if let Some(value) = patch.title {
+            self.title = value;
+        }"#
+        ));
+        assert!(content.contains(
+            r#"    pub created_at: ::core::option::Option<
+        ::core::option::Option<::prost_types::Timestamp>,
+    >,"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_serde_remote_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde_remote("todo.Todo", "TodoDef")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Todo")]
+pub struct TodoDef {
+    pub id: ::prost::alloc::string::String,"#
+        ));
+        assert!(content.contains(
+            "    pub created_at: ::core::option::Option<::prost_types::Timestamp>,\n    pub updated_at: ::core::option::Option<::prost_types::Timestamp>,\n}"
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_cow_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_cow_fields(&["fixtures/protos/todo.proto"], &["todo.Todo.title"])
+            .unwrap()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"pub struct TodoCow<'a> {
+    pub id: ::prost::alloc::string::String,
+    pub title: ::std::borrow::Cow<'a, str>,"#
+        ));
+        assert!(content.contains("            title: value.title.into_owned(),\n"));
+
+        let err = Generator::new(Config::default())
+            .with_cow_fields(
+                &["fixtures/protos/todo.proto"],
+                &["todo.GetTodosRequest.id"],
+            )
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            BuilderError::NotStringField {
+                type_name: "todo.GetTodosRequest".to_string(),
+                field: "id".to_string(),
             }
+        );
+    }
+
+    #[test]
+    fn test_prost_build_with_typed_ids_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_typed_ids(
+                &["fixtures/protos/todo.proto"],
+                &[
+                    ("todo.Todo.id", "TodoId"),
+                    ("todo.DeleteTodoRequest.id", "TodoId"),
+                ],
+            )
+            .unwrap()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("pub struct TodoId(pub ::prost::alloc::string::String);"));
+        assert_eq!(content.matches("pub struct TodoId(").count(), 1);
+        assert!(content.contains(
+            r#"impl Todo {
+    /// `id` as a [`TodoId`]
+    pub fn typed_id(&self) -> TodoId {
+        TodoId(self.id.clone())
+    }
+}"#
+        ));
+        assert!(content.contains("impl DeleteTodoRequest {\n    /// `id` as a [`TodoId`]"));
+    }
+
+    #[test]
+    fn test_prost_build_with_rkyv_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Config::default()
+            .out_dir(path.path())
+            .with_rkyv(&["todo.Todo"], Some(&["#[archive(check_bytes)]"]))
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[archive(check_bytes)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Todo {"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_default_instance_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_default_instance(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // `Todo::default_instance() == &Todo::default()`, and the same reference every call
+        assert!(content.ends_with(
+            r#"impl Todo {
+    /// The default message, created on first use and shared afterwards.
+    pub fn default_instance() -> &'static Self {
+        static INSTANCE: ::std::sync::OnceLock<Todo> = ::std::sync::OnceLock::new();
+        INSTANCE.get_or_init(::core::default::Default::default)
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_checksum_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_checksum(&["todo.Todo", "todo.GetTodosRequest"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // equal todos hash the same, a different title changes the checksum
+        assert!(content.contains(
+            r#"    pub fn checksum(&self) -> u64 {
+        ::prost::Message::encode_to_vec(self)
+            .iter()
+            .fold(
+                0xcbf2_9ce4_8422_2325_u64,
+                |hash, b| { (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3) },
+            )
+    }"#
+        ));
+        assert!(content.contains("impl Todo {"));
+        assert!(content.contains("impl GetTodosRequest {"));
+    }
+
+    #[test]
+    fn test_prost_build_with_mock_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_mock(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    pub fn mock() -> Self {
+        Self {
+            id: ::prost::alloc::string::String::from("id"),
+            title: ::prost::alloc::string::String::from("title"),
+            description: ::prost::alloc::string::String::from("description"),
+            status: 1,
+            created_at: Some(::core::default::Default::default()),
+            updated_at: Some(::core::default::Default::default()),
         }
-        "###);
+    }"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_presence_bitset_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_presence_bitset(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    /// The bit of `title` in [`Self::present_fields`].
+    pub const TITLE_BIT: u32 = 1;"#
+        ));
+        // a `Todo` with only `title` set gives `0b10`
+        assert!(content.contains(
+            r#"        if !self.title.is_empty() {
+            bits |= 1 << Self::TITLE_BIT;
+        }"#
+        ));
+        assert!(content.contains(
+            r#"        if self.status != 0 {
+            bits |= 1 << Self::STATUS_BIT;
+        }
+        if self.created_at.is_some() {
+            bits |= 1 << Self::CREATED_AT_BIT;
+        }"#
+        ));
+    }
+
+    #[test]
+    fn test_prost_build_with_default_package_filename_should_work() {
+        let path = tempdir().unwrap();
+        let proto = path.path().join("ping.proto");
+        fs::write(
+            &proto,
+            "syntax = \"proto3\";\nmessage Ping { string id = 1; }\n",
+        )
+        .unwrap();
+        let proto = proto.to_str().unwrap();
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_default_package_filename("protos")
+            .with_getters(&["Ping"])
+            .compile_protos(&[proto], &[path.path().to_str().unwrap()])
+            .unwrap();
+        let content = fs::read_to_string(path.path().join("protos.rs")).unwrap();
+        assert!(content.contains("pub fn id(&self) -> &str {"));
+        assert!(!path.path().join("_.rs").exists());
+    }
+
+    #[test]
+    fn test_prost_build_with_repeated_rename_all_should_keep_one() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let camel = serde_rename_all_attr(RenameAll::CamelCase);
+        let snake = serde_rename_all_attr(RenameAll::SnakeCase);
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, Some(&[camel.as_str()]))
+            .with_type_attributes(&["todo.Todo", "todo.TodoStatus"], &[snake.as_str()])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(serde::Serialize, serde::Deserialize)]\n#[serde(rename_all = \"camelCase\")]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        // the other types keep theirs
+        assert_eq!(content.matches("rename_all").count(), 2);
+    }
+
+    #[test]
+    fn test_prost_build_with_duplicate_derives_should_warn() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_type_attributes(&["todo.Todo"], &["#[derive(Clone)]"])
+            .warn_on_duplicate_derives()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert_eq!(
+            codegen::duplicate_derives("todo", &content),
+            vec!["`Clone` is derived more than once on `todo::Todo`"]
+        );
+    }
+
+    #[test]
+    fn test_prost_build_compile_with_report_should_flag_unmatched_paths() {
+        let path = tempdir().unwrap();
+        let report = Generator::new(Config::default())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo", "todo.Todoo"], true, true, None)
+            .with_field_attributes(&["todo.Todo.title"], &["#[serde(default)]"])
+            .with_sqlx_type(&[".todo"], None)
+            .compile_with_report(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        assert_eq!(
+            report.paths,
+            [
+                ("todo.Todo".to_string(), true),
+                ("todo.Todoo".to_string(), false),
+                ("todo.Todo.title".to_string(), true),
+                (".todo".to_string(), true),
+            ]
+        );
+        assert_eq!(report.unmatched(), ["todo.Todoo"]);
+        assert!(path.path().join("todo.rs").exists());
     }
 }
@@ -1,252 +1,1712 @@
-use crate::utils::{
-    derive_builder_attr, serde_as_attr, serde_attr, sqlx_from_row_attr, sqlx_type_attr,
+use crate::{
+    codegen,
+    generator::{Attribute, BuildReport},
+    parser::{Protos, TypeKind},
+    utils::{
+        api_preset_attrs, arbitrary_attr, async_graphql_enum_attr,
+        async_graphql_simple_object_attr, attribute_map_attrs, base64_bytes_attrs, boxed_attrs,
+        btree_map_attrs, builder_defaults_attrs, bytes_attrs, clap_args_attrs, clap_parser_attr,
+        comments_as_schema_desc_attrs, db_preset_attrs, deprecated_attr, derive_builder_attr,
+        derive_more_attr, derive_where_attrs, diesel_attr, enum_original_names_attrs,
+        eq_hash_safe_attrs, eq_where_possible_attrs, fake_attrs, field_attributes_by_tag_attrs,
+        fields_by_type_attrs, fields_matching_attrs, i64_as_string_attrs, joined_enum_attrs,
+        joined_field_attrs, joined_message_attrs, joined_type_attrs, must_use_attr,
+        non_exhaustive_attr, ord_attr, profile_attrs, proptest_attr, rkyv_attr, sea_orm_attrs,
+        serde_as_attrs, serde_as_custom_attrs, serde_as_map_attrs, serde_as_vec_attrs, serde_attr,
+        serde_deny_unknown_fields_attr, serde_descriptor_attrs, serde_excluding_attrs,
+        serde_flatten_attr, serde_package_attrs, serde_rename_all_attr,
+        serde_rename_all_fields_attr, serde_tag_attr, serde_tag_content_attr, serde_untagged_attr,
+        serde_with_attrs, single_field_messages, sqlx_from_row_attr, sqlx_from_row_opts_attr,
+        sqlx_type_attr, string_fields, strum_attr, type_attrs, zeroize_attr, BuilderError, Profile,
+        RenameAll,
+    },
+    Generator,
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
 };
 use tonic_build::Builder;
 
 /// provide extra attributes to the generated protobuf code easily
 pub trait BuilderAttributes {
-    /// add type attributes with `#[derive(serde::Serialize, serde::Deserialize)]`
+    /// add type attributes with `#[derive(serde::Serialize, serde::Deserialize)]`. Through a
+    /// `Generator`, a `#[serde(rename_all = "...")]` repeating one already added to the same path
+    /// is dropped with a `cargo:warning`.
     fn with_serde(self, paths: &[&str], ser: bool, de: bool, extra_attrs: Option<&[&str]>) -> Self;
+    /// add serde derives to the types of `paths` but the ones in `exclude`, e.g. response types
+    /// that are only streamed
+    fn with_serde_excluding(self, paths: &[&str], exclude: &[&str], ser: bool, de: bool) -> Self;
+    /// add serde derives to every message and enum of `package`. Panics if the protos can't be
+    /// parsed.
+    fn with_serde_all(self, proto_files: &[&str], package: &str, ser: bool, de: bool) -> Self;
+    /// add serde derives to every message and enum of `package`, listed from a compiled
+    /// `FileDescriptorSet` (e.g. `protoc --include_imports --descriptor_set_out=...`) instead of
+    /// parsing the protos. Panics if the file can't be read or decoded.
+    fn with_serde_all_from_descriptor(
+        self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> Self;
+    /// add serde derives and `#[serde(rename_all = "...")]` to the enums of `package` only
+    fn with_serde_enums(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self;
+    /// add `#[serde(rename_all = "...")]` to the enums (and oneofs) matching the paths, renaming
+    /// their variants, e.g. `Done` to `DONE` with `ScreamingSnakeCase`. The same attribute renames the
+    /// fields of a message, so this goes through `enum_attribute` and skips messages matching the paths.
+    fn with_serde_enum_rename(self, paths: &[&str], rename: RenameAll) -> Self;
+    /// add `#[serde(rename_all_fields = "...")]` to the enums (and oneofs) matching the paths,
+    /// renaming the fields of their struct variants. Unlike `rename_all`, serde only accepts it on
+    /// enums, so this goes through `enum_attribute` too. Needs serde 1.0.185 or later. prost's
+    /// oneof variants hold a single value, so it only matters for enums given struct variants
+    /// some other way.
+    fn with_serde_rename_all_fields(self, paths: &[&str], rename: RenameAll) -> Self;
+    /// add serde derives and `#[serde(rename_all = "...")]` to the messages of `package` only
+    fn with_serde_messages(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self;
+    /// serialize the variants of the enums with their proto names (e.g. `TODO_STATUS_DONE`)
+    /// rather than the Rust ones. Panics if the protos can't be parsed.
+    fn with_serde_enum_original_names(self, proto_files: &[&str], paths: &[&str]) -> Self;
+    /// add [`serde_as_attr`](crate::utils::serde_as_attr) to the type and each attribute (e.g.
+    /// `#[serde_as(as = "DisplayFromStr")]`) to its fields. Through a `Generator`, the type
+    /// attribute is added once however many times this is called for the type.
     fn with_serde_as(self, path: &str, fields: &[(&[&str], &str)]) -> Self;
+    /// like `with_serde_as`, with each `as` expression (e.g. `DisplayFromStr`) wrapped in
+    /// `Vec<...>` for repeated fields
+    fn with_serde_as_vec(self, path: &str, fields: &[(&[&str], &str)]) -> Self;
+    /// like `with_serde_as`, with each `as` expression used for the values of a
+    /// `HashMap<_, ...>` for map fields
+    fn with_serde_as_map(self, path: &str, fields: &[(&[&str], &str)]) -> Self;
+    /// like `with_serde_as`, with a custom converter (e.g. `crate::convert::Hex`) per field,
+    /// wrapped in `container` if given, e.g. `Option` gives `#[serde_as(as = "Option<...>")]`
+    fn with_serde_as_custom(
+        self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> Self;
+    /// serialize the 64-bit integer fields of the messages as strings, which JSON numbers can't
+    /// hold exactly, through `#[serde_as(as = "DisplayFromStr")]` and `#[serde_with::serde_as]` on
+    /// the messages. Panics if the protos can't be parsed.
+    fn with_i64_as_string(self, proto_files: &[&str], paths: &[&str]) -> Self;
+    /// serialize the `bytes` fields of the messages as base64 strings instead of arrays of numbers,
+    /// through `#[serde_as(as = "serde_with::base64::Base64")]` and `#[serde_with::serde_as]` on the
+    /// messages. Needs serde_with's `base64` feature. Panics if the protos can't be parsed.
+    fn with_base64_bytes(self, proto_files: &[&str], paths: &[&str]) -> Self;
+    /// add `#[schemars(description = "...")]` with the leading proto comments to the messages and
+    /// their fields that have any, so JSON schemas carry the same docs as rustdoc. Panics if the
+    /// protos can't be parsed.
+    fn with_comments_as_schema_desc(self, proto_files: &[&str], paths: &[&str]) -> Self;
+    /// add `#[serde(tag = "...")]` to the types, e.g. a oneof (`event.Event.payload`), so the
+    /// variant name is serialized as a field of the content. serde can only do this for variants
+    /// holding a struct or map, so oneof members must be messages.
+    fn with_serde_tag(self, paths: &[&str], tag: &str) -> Self;
+    /// add `#[serde(tag = "...", content = "...")]` to the types, so the variant name and its
+    /// content are serialized as two fields. Unlike `with_serde_tag`, this works for scalar members.
+    fn with_serde_tag_content(self, paths: &[&str], tag: &str, content: &str) -> Self;
+    /// add `#[serde(untagged)]` to the types, e.g. a oneof, so only the variant content is
+    /// serialized. Deserializing tries the variants in order and picks the first that fits, so
+    /// members with the same shape (e.g. `Created` and `Deleted` with a single string) can't be told
+    /// apart.
+    fn with_serde_untagged(self, paths: &[&str]) -> Self;
+    /// add `#[serde(flatten)]` to the message fields, so the fields of the nested message are
+    /// serialized inline in the parent. serde_with's `skip_serializing_none` (added by
+    /// `with_serde_as`) doesn't see through flattened fields, so `None`s inside them are still
+    /// serialized.
+    fn with_serde_flatten(self, fields: &[&str]) -> Self;
+    /// add `#[serde(deny_unknown_fields)]` to the types, so deserializing rejects unknown keys. serde
+    /// doesn't support it together with `#[serde(flatten)]` fields, a `Generator` warns about types
+    /// that have both.
+    fn with_serde_deny_unknown_fields(self, paths: &[&str]) -> Self;
+    /// add `#[serde(with = "...")]` to the fields, e.g. `("todo.Todo.status", "my_mod")`, to
+    /// control how each one is (de)serialized. prost can't change the Rust type of a field, so a
+    /// newtype like `UserId` has to be converted from and to the proto type in that module.
+    fn with_field_serde_with(self, fields: &[(&str, &str)]) -> Self;
     /// add type attributes with `#[derive(sqlx::Type)]`
     fn with_sqlx_type(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
     /// add type attributes with `#[derive(sqlx::FromRow)]`
     fn with_sqlx_from_row(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(sqlx::FromRow)]` and an optional
+    /// `#[sqlx(rename_all = "...")]`
+    fn with_sqlx_from_row_opts(
+        self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self;
+    /// the usual attributes for messages exposed in a JSON API:
+    /// `#[derive(serde::Serialize, serde::Deserialize)]`, `#[serde(rename_all = "camelCase")]`,
+    /// `#[serde_with::serde_as]` and `#[serde_with::skip_serializing_none]`
+    fn with_api_preset(self, paths: &[&str]) -> Self;
+    /// the usual attributes for messages read from a database: `#[derive(sqlx::FromRow)]` and
+    /// `#[sqlx(rename_all = "snake_case")]`
+    fn with_db_preset(self, paths: &[&str]) -> Self;
+    /// add the attributes of the [`Profile`] to the types, in the order they were added to it
+    fn with_profile(self, paths: &[&str], profile: &Profile) -> Self;
     /// add type attributes with `#[derive(derive_builder::Builder)]`
     fn with_derive_builder(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add `#[builder(default = "...")]` to the fields, paired with the default expression, e.g.
+    /// `("todo.CreateTodoRequest.description", "\"draft\".into()")`. Use it with
+    /// `with_derive_builder`.
+    fn with_builder_field_default(self, fields: &[(&str, &str)]) -> Self;
+    /// add type attributes with `#[derive(diesel::Queryable, diesel::Insertable)]` (as selected)
+    /// and an optional `#[diesel(table_name = "...")]`. The table comes from a `diesel::table!`
+    /// schema module that this crate can't generate, so it must be in scope of the generated code,
+    /// e.g. by `include!`-ing it into a module that imports the schema.
+    fn with_diesel(
+        self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self;
+    /// add `#[derive(sea_orm::DeriveEntityModel)]` and `#[sea_orm(table_name = "...")]` to the
+    /// message, and `#[sea_orm(primary_key)]` to its key fields (names, e.g. `id`). The derive expects
+    /// `Relation` and `ActiveModelBehavior` in scope, which the generated code doesn't provide.
+    fn with_sea_orm(
+        self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> Self;
+    /// add type attributes with `#[derive(zeroize::Zeroize)]`, and `zeroize::ZeroizeOnDrop` if
+    /// `on_drop`, for messages holding secrets. Every field must implement `Zeroize`, which enums
+    /// (`i32`) do but nested messages, `Timestamp` included, don't unless they derive it too.
+    fn with_zeroize(self, paths: &[&str], on_drop: bool, extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(arbitrary::Arbitrary)]`, e.g. to fuzz decoders. The
+    /// derive needs a lifetime for borrowed types, but prost types own their data so the plain
+    /// derive works.
+    fn with_arbitrary(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(proptest_derive::Arbitrary)]`. Use `extra_attrs` for
+    /// `#[proptest(...)]` strategy overrides on the type.
+    fn with_proptest(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(async_graphql::SimpleObject)]` to messages
+    fn with_async_graphql_object(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(async_graphql::Enum)]` to enums
+    fn with_async_graphql_enum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]`
+    fn with_rkyv(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add type attributes with `#[derive(fake::Dummy)]`, and `#[dummy(faker = "...")]` on the
+    /// fields paired with a faker, e.g. `("todo.Todo.title", "Sentence(3..5)")`
+    fn with_fake(self, paths: &[&str], field_fakers: &[(&str, &str)]) -> Self;
+    /// add type attributes with `#[derive(clap::Parser)]`, e.g. with `#[command(...)]` as extra
+    fn with_clap(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
+    /// add `#[arg(...)]` to the fields, e.g. `("todo.CreateTodoRequest.title", "long")`
+    fn with_clap_args(self, fields: &[(&str, &str)]) -> Self;
+    /// add type attributes with `#[derive(derive_more::...)]` for the given traits, e.g. `From`
+    fn with_derive_more(self, paths: &[&str], traits: &[&str]) -> Self;
+    /// add `#[derive(...)]` with the given traits to every message and enum of the protos whose
+    /// fully qualified name (e.g. `todo.CreateTodoRequest`) matches `predicate`. Panics if the protos
+    /// can't be parsed.
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> Self;
+    /// add `#[derive(Eq, Hash)]` to the messages and, as they need it too, to the messages of
+    /// their fields, after checking that none of them holds a `float`/`double` field. Map fields
+    /// still prevent `Hash`.
+    fn with_eq_hash_safe(self, proto_files: &[&str], paths: &[&str]) -> Result<Self, BuilderError>
+    where
+        Self: Sized;
+    /// add `#[derive(Eq, Hash)]` to every message of the protos that can have them: no
+    /// `float`/`double` or map fields and no types from other files but `Timestamp`, directly or
+    /// through the messages of their fields. The skipped messages are listed as `cargo:warning`s.
+    /// Panics if the protos can't be parsed.
+    fn derive_eq_where_possible(self, proto_files: &[&str]) -> Self;
+    /// add `#[non_exhaustive]` to the types, so downstream crates can't match them exhaustively
+    /// or build messages with struct literals
+    fn with_non_exhaustive(self, paths: &[&str]) -> Self;
+    /// add `#[derive(PartialOrd, Ord)]` to the types, to sort them. Every field must be `Ord`: no
+    /// `float`/`double` or map fields, and the messages of the fields and the oneofs need it too. `Ord`
+    /// also needs `Eq`, which `with_eq_hash_safe` adds after checking for floats.
+    fn with_ord(self, paths: &[&str]) -> Self;
+    /// add `#[deprecated]` (with an optional note) to the types. prost already adds a plain
+    /// `#[deprecated]` for the `deprecated = true` option, so don't use both on the same item.
+    fn with_deprecated_types(self, paths: &[&str], note: Option<&str>) -> Self;
+    /// add `#[deprecated]` (with an optional note) to the fields, see
+    /// [`with_deprecated_types`](Self::with_deprecated_types)
+    fn with_deprecated_fields(self, paths: &[&str], note: Option<&str>) -> Self;
+    /// add `#[must_use]` (with an optional note) to the types, e.g. responses callers shouldn't
+    /// drop
+    fn with_must_use(self, paths: &[&str], note: Option<&str>) -> Self;
     /// add type attributes with `#[derive(strum::EnumString)]`
     fn with_strum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self;
     /// add type attributes
     fn with_type_attributes(self, paths: &[&str], attributes: &[&str]) -> Self;
+    /// add type attributes to the messages matching the paths only, skipping enums, e.g. with
+    /// `.` or a package path
+    fn with_message_attributes(self, paths: &[&str], attributes: &[&str]) -> Self;
+    /// add type attributes to the enums and oneofs matching the paths only, skipping messages
+    fn with_enum_attributes(self, paths: &[&str], attributes: &[&str]) -> Self;
+    /// add the attributes to the types as one block in the given order, for derives that must
+    /// see other attributes first, e.g. `#[serde_with::serde_as]` before
+    /// `#[derive(serde::Serialize)]`. Nothing from other helpers is emitted in between. Through a
+    /// `Generator` the block goes ahead of every other attribute on those paths, whatever the
+    /// call order; on a bare builder it lands where it's called, like `with_type_attributes`.
+    fn with_ordered_attributes(self, paths: &[&str], ordered: &[&str]) -> Self;
+    /// add type attributes from a path -> attributes map, e.g. a `Vec<(String, Vec<String>)>`
+    /// loaded from a config file
+    fn with_attribute_map<I, P, A, S>(self, map: I) -> Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>;
     /// add field attributes
     fn with_field_attributes(self, paths: &[&str], attributes: &[&str]) -> Self;
+    /// wrap the message fields (full paths, e.g. `tree.Node.leaf`) in a `Box`, e.g. to keep a
+    /// large message small. prost already boxes the fields that make a message recursive.
+    fn with_boxed_fields(self, fields: &[&str]) -> Self;
+    /// use `::prost::bytes::Bytes` instead of `Vec<u8>` for the `bytes` fields matching the paths:
+    /// a field (`peer.Peer.raw_address`), a fully qualified message or package for all their
+    /// `bytes` fields (`.peer.Peer`, the leading dot makes prost match by prefix), or `.` for all
+    /// of them. prost replaces the paths set before, so pass them all in one call, or call it
+    /// through a `Generator`, which sets them together.
+    fn with_bytes(self, paths: &[&str]) -> Self;
+    /// use `BTreeMap` instead of `HashMap` for the map fields matching the paths, e.g. for a
+    /// deterministic order, matched like [`with_bytes`](Self::with_bytes). prost replaces the paths
+    /// set before, so pass them all in one call, or call it through a `Generator`, which sets them
+    /// together and turns the `HashMap<_, ...>` of the `serde_as` helpers into a `BTreeMap` on
+    /// those fields.
+    fn with_btree_maps(self, paths: &[&str]) -> Self;
+    /// add the field attribute to every field of a scalar type across the protos, e.g. `int64`.
+    /// The accepted names are the proto scalar types: `double`, `float`, `int32`, `int64`,
+    /// `uint32`, `uint64`, `sint32`, `sint64`, `fixed32`, `fixed64`, `sfixed32`, `sfixed64`,
+    /// `bool`, `string` and `bytes`. Panics on other names or if the protos can't be parsed.
+    fn with_field_attributes_by_type(
+        self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> Self;
+    /// add each `(message, tag, attribute)` field attribute to the field of the message with that
+    /// tag number, e.g. `("todo.Todo", 1, ...)` for `id`, so it keeps working when fields are
+    /// renamed. Panics if a message has no field with the tag or if the protos can't be parsed.
+    fn with_field_attributes_by_tag(
+        self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> Self;
+    /// add the field attribute to every field of the protos whose fully qualified path (e.g.
+    /// `todo.Todo.id`) matches the regex `pattern`, e.g. `\.id$` or `_id$`. Fails if the pattern
+    /// isn't a valid regex. Panics if the protos can't be parsed.
+    fn with_field_attributes_matching(
+        self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<Self, BuilderError>
+    where
+        Self: Sized;
     /// add optional type attributes
     fn with_optional_type_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self;
     /// add optional field attributes
     fn with_optional_field_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self;
+    /// map proto types to existing Rust types instead of generating them, calling `extern_path`
+    /// for each `(proto_path, rust_path)` pair, e.g.
+    /// `(".google.protobuf.Timestamp", "::pbjson_types::Timestamp")`
+    fn with_extern_paths(self, mappings: &[(&str, &str)]) -> Self;
+    /// generate a file named `name` in the output directory that includes every generated file
+    /// in nested modules, see [`Builder::include_file`]
+    ///
+    /// ```
+    /// use proto_builder_trait::tonic::BuilderAttributes;
+    ///
+    /// let _builder = tonic_build::configure()
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .with_include_file("_includes.rs");
+    /// ```
+    fn with_include_file(self, name: &str) -> Self;
+    /// pass each argument to `protoc`, see [`Builder::protoc_arg`]. tonic has no
+    /// `default_package_filename`, use `compile_with_config` with a prost `Config` for it.
+    ///
+    /// ```
+    /// use proto_builder_trait::tonic::BuilderAttributes;
+    ///
+    /// let _builder = tonic_build::configure()
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .with_protoc_args(&["--experimental_allow_proto3_optional"]);
+    /// ```
+    fn with_protoc_args(self, args: &[&str]) -> Self;
+    /// call `f` on the builder mid-chain, to reach the native methods this trait doesn't wrap
+    ///
+    /// ```
+    /// use proto_builder_trait::tonic::BuilderAttributes;
+    ///
+    /// let _builder = tonic_build::configure()
+    ///     .with_serde(&["todo.Todo"], true, true, None)
+    ///     .apply(|b| b.btree_map(["."]))
+    ///     .with_strum(&["todo.TodoStatus"], None);
+    /// ```
+    fn apply<F>(self, f: F) -> Self
+    where
+        Self: Sized,
+        F: FnOnce(Self) -> Self,
+    {
+        f(self)
+    }
 }
 
 /// provide extra attributes to the generated protobuf code easily
 impl BuilderAttributes for Builder {
     fn with_serde(self, paths: &[&str], ser: bool, de: bool, extra_attrs: Option<&[&str]>) -> Self {
-        let attr = serde_attr(ser, de);
+        apply(self, type_attrs(paths, serde_attr(ser, de), extra_attrs))
+    }
 
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, attr)
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+    fn with_serde_excluding(self, paths: &[&str], exclude: &[&str], ser: bool, de: bool) -> Self {
+        apply(self, serde_excluding_attrs(paths, exclude, ser, de))
     }
 
     fn with_serde_as(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
-        let serde_attr = serde_as_attr();
-        let builder = self.type_attribute(path, serde_attr);
-        fields.iter().fold(builder, |builder, (paths, attr)| {
-            paths.iter().fold(builder, |builder, p| {
-                let p = format!("{}.{}", path, p);
-                builder.field_attribute(p, attr)
-            })
-        })
+        apply(self, serde_as_attrs(path, fields))
+    }
+
+    fn with_serde_as_vec(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
+        apply(self, serde_as_vec_attrs(path, fields))
+    }
+
+    fn with_serde_as_map(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
+        apply(self, serde_as_map_attrs(path, fields))
+    }
+
+    fn with_serde_as_custom(
+        self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> Self {
+        apply(self, serde_as_custom_attrs(path, fields, container))
+    }
+
+    fn with_i64_as_string(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        apply(self, i64_as_string_attrs(proto_files, paths))
+    }
+
+    fn with_base64_bytes(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        apply(self, base64_bytes_attrs(proto_files, paths))
+    }
+
+    fn with_comments_as_schema_desc(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        apply(self, comments_as_schema_desc_attrs(proto_files, paths))
+    }
+
+    fn with_serde_tag(self, paths: &[&str], tag: &str) -> Self {
+        apply(self, type_attrs(paths, &serde_tag_attr(tag), None))
+    }
+
+    fn with_serde_tag_content(self, paths: &[&str], tag: &str, content: &str) -> Self {
+        apply(
+            self,
+            type_attrs(paths, &serde_tag_content_attr(tag, content), None),
+        )
+    }
+
+    fn with_serde_untagged(self, paths: &[&str]) -> Self {
+        apply(self, type_attrs(paths, serde_untagged_attr(), None))
+    }
+
+    fn with_serde_flatten(self, fields: &[&str]) -> Self {
+        apply(
+            self,
+            joined_field_attrs(fields, Some(&[serde_flatten_attr()])),
+        )
+    }
+
+    fn with_serde_deny_unknown_fields(self, paths: &[&str]) -> Self {
+        apply(
+            self,
+            type_attrs(paths, serde_deny_unknown_fields_attr(), None),
+        )
+    }
+
+    fn with_field_serde_with(self, fields: &[(&str, &str)]) -> Self {
+        apply(self, serde_with_attrs(fields))
+    }
+
+    fn with_serde_enum_original_names(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        apply(self, enum_original_names_attrs(proto_files, paths))
+    }
+
+    fn with_serde_all(self, proto_files: &[&str], package: &str, ser: bool, de: bool) -> Self {
+        let kinds = [TypeKind::Message, TypeKind::Enum];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, ser, de, None),
+        )
+    }
+
+    fn with_serde_all_from_descriptor(
+        self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> Self {
+        apply(
+            self,
+            serde_descriptor_attrs(descriptor_path, package, ser, de),
+        )
+    }
+
+    fn with_serde_enums(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self {
+        let kinds = [TypeKind::Enum];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_serde_enum_rename(self, paths: &[&str], rename: RenameAll) -> Self {
+        let attr = serde_rename_all_attr(rename);
+        apply(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_rename_all_fields(self, paths: &[&str], rename: RenameAll) -> Self {
+        let attr = serde_rename_all_fields_attr(rename);
+        apply(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_messages(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self {
+        let kinds = [TypeKind::Message];
+        apply(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
     }
 
     fn with_sqlx_type(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, sqlx_type_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, sqlx_type_attr(), extra_attrs))
     }
 
     fn with_sqlx_from_row(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, sqlx_from_row_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, sqlx_from_row_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row_opts(
+        self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        let attr = sqlx_from_row_opts_attr(rename_all);
+        apply(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_api_preset(self, paths: &[&str]) -> Self {
+        apply(self, api_preset_attrs(paths))
+    }
+
+    fn with_db_preset(self, paths: &[&str]) -> Self {
+        apply(self, db_preset_attrs(paths))
+    }
+
+    fn with_profile(self, paths: &[&str], profile: &Profile) -> Self {
+        apply(self, profile_attrs(paths, profile))
     }
 
     fn with_derive_builder(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(ty, derive_builder_attr())
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, derive_builder_attr(), extra_attrs))
+    }
+
+    fn with_builder_field_default(self, fields: &[(&str, &str)]) -> Self {
+        apply(self, builder_defaults_attrs(fields))
+    }
+
+    fn with_diesel(
+        self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        let attr = diesel_attr(table_name, queryable, insertable);
+        apply(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_sea_orm(
+        self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        apply(
+            self,
+            sea_orm_attrs(path, table_name, primary_keys, extra_attrs),
+        )
+    }
+
+    fn with_zeroize(self, paths: &[&str], on_drop: bool, extra_attrs: Option<&[&str]>) -> Self {
+        apply(self, type_attrs(paths, zeroize_attr(on_drop), extra_attrs))
+    }
+
+    fn with_arbitrary(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        apply(self, type_attrs(paths, arbitrary_attr(), extra_attrs))
+    }
+
+    fn with_proptest(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        apply(self, type_attrs(paths, proptest_attr(), extra_attrs))
+    }
+
+    fn with_async_graphql_object(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        let attr = async_graphql_simple_object_attr();
+        apply(self, type_attrs(paths, attr, extra_attrs))
+    }
+
+    fn with_async_graphql_enum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        apply(
+            self,
+            type_attrs(paths, async_graphql_enum_attr(), extra_attrs),
+        )
+    }
+
+    fn with_rkyv(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        apply(self, type_attrs(paths, rkyv_attr(), extra_attrs))
+    }
+
+    fn with_fake(self, paths: &[&str], field_fakers: &[(&str, &str)]) -> Self {
+        apply(self, fake_attrs(paths, field_fakers))
+    }
+
+    fn with_clap(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        apply(self, type_attrs(paths, clap_parser_attr(), extra_attrs))
+    }
+
+    fn with_clap_args(self, fields: &[(&str, &str)]) -> Self {
+        apply(self, clap_args_attrs(fields))
+    }
+
+    fn with_derive_more(self, paths: &[&str], traits: &[&str]) -> Self {
+        apply(self, type_attrs(paths, &derive_more_attr(traits), None))
+    }
+
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> Self {
+        apply(self, derive_where_attrs(proto_files, predicate, derives))
+    }
+
+    fn with_eq_hash_safe(self, proto_files: &[&str], paths: &[&str]) -> Result<Self, BuilderError> {
+        let attrs = eq_hash_safe_attrs(proto_files, paths)?;
+        Ok(apply(self, attrs))
+    }
+
+    fn derive_eq_where_possible(self, proto_files: &[&str]) -> Self {
+        apply(self, eq_where_possible_attrs(proto_files))
+    }
+
+    fn with_non_exhaustive(self, paths: &[&str]) -> Self {
+        apply(self, type_attrs(paths, non_exhaustive_attr(), None))
+    }
+
+    fn with_ord(self, paths: &[&str]) -> Self {
+        apply(self, type_attrs(paths, ord_attr(), None))
+    }
+
+    fn with_deprecated_types(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = deprecated_attr(note);
+        apply(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_deprecated_fields(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = deprecated_attr(note);
+        apply(self, joined_field_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_must_use(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = must_use_attr(note);
+        apply(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
     }
 
     fn with_strum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
-        paths.iter().fold(self, |builder, ty| {
-            builder
-                .type_attribute(
-                    ty,
-                    "#[derive(strum::EnumString, strum::Display, strum::EnumIter)]",
-                )
-                .with_optional_type_attributes(&[ty], extra_attrs)
-        })
+        apply(self, type_attrs(paths, strum_attr(), extra_attrs))
     }
 
     fn with_type_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
-        let attr = attributes.join("\n");
+        apply(self, joined_type_attrs(paths, Some(attributes)))
+    }
+
+    fn with_message_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        apply(self, joined_message_attrs(paths, Some(attributes)))
+    }
+
+    fn with_enum_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        apply(self, joined_enum_attrs(paths, Some(attributes)))
+    }
+
+    fn with_ordered_attributes(self, paths: &[&str], ordered: &[&str]) -> Self {
+        apply(self, joined_type_attrs(paths, Some(ordered)))
+    }
 
-        paths.iter().fold(self, |builder, ty| {
-            builder.type_attribute(ty, attr.as_str())
-        })
+    fn with_attribute_map<I, P, A, S>(self, map: I) -> Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        apply(self, attribute_map_attrs(map))
     }
 
     fn with_field_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
-        let attr = attributes.join("\n");
-        paths.iter().fold(self, |builder, ty| {
-            builder.field_attribute(ty, attr.as_str())
-        })
+        apply(self, joined_field_attrs(paths, Some(attributes)))
+    }
+
+    fn with_boxed_fields(self, fields: &[&str]) -> Self {
+        apply(self, boxed_attrs(fields))
+    }
+
+    fn with_bytes(self, paths: &[&str]) -> Self {
+        apply(self, bytes_attrs(paths))
+    }
+
+    fn with_btree_maps(self, paths: &[&str]) -> Self {
+        apply(self, btree_map_attrs(paths))
+    }
+
+    fn with_field_attributes_by_type(
+        self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> Self {
+        apply(
+            self,
+            fields_by_type_attrs(proto_files, proto_type, attribute),
+        )
+    }
+
+    fn with_field_attributes_by_tag(
+        self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> Self {
+        apply(self, field_attributes_by_tag_attrs(proto_files, targets))
+    }
+
+    fn with_field_attributes_matching(
+        self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<Self, BuilderError> {
+        let attrs = fields_matching_attrs(proto_files, pattern, attribute)?;
+        Ok(apply(self, attrs))
     }
 
     fn with_optional_type_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self {
-        if let Some(attributes) = attributes {
-            self.with_type_attributes(paths, attributes)
-        } else {
-            self
-        }
+        apply(self, joined_type_attrs(paths, attributes))
     }
 
     fn with_optional_field_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self {
-        if let Some(attributes) = attributes {
-            self.with_field_attributes(paths, attributes)
-        } else {
-            self
-        }
+        apply(self, joined_field_attrs(paths, attributes))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::tempdir;
+    fn with_extern_paths(self, mappings: &[(&str, &str)]) -> Self {
+        mappings
+            .iter()
+            .fold(self, |builder, (proto_path, rust_path)| {
+                builder.extern_path(proto_path, rust_path)
+            })
+    }
 
-    #[test]
-    fn test_tonic_build_with_extra_attributes_should_work() {
-        let path = tempdir().unwrap();
-        let filename = path.path().join("todo.rs");
-        tonic_build::configure()
-            .out_dir(path.path())
-            .with_serde(
-                &["todo.Todo", "todo.TodoStatus"],
-                true,
-                true,
-                Some(&[r#"#[serde(rename_all = "camelCase")]"#]),
-            )
-            .with_serde_as(
-                "todo.Todo",
-                &[(
-                    &["status", "created_at"],
-                    r#"#[serde_as(as = "DisplayFromStr")]"#,
-                )],
-            )
-            .with_derive_builder(
-                &["todo.Todo"],
-                Some(&[r#"#[builder(build_fn(name = "private_build"))]"#]),
-            )
-            .with_sqlx_type(&["todo.TodoStatus"], None)
-            .with_strum(
-                &["todo.TodoStatus"],
-                Some(&[r#"#[strum(ascii_case_insensitive, serialize_all = "snake_case")]"#]),
-            )
-            .with_field_attributes(
-                &["todo.Todo.created_at", "todo.Todo.updated_at"],
-                &["#[derive(Copy)]"],
-            )
-            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
-            .unwrap();
-        insta::assert_snapshot!(fs::read_to_string(filename).unwrap(), @r###"
-        // This file is @generated by prost-build.
-        #[derive(serde::Serialize, serde::Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        #[serde_with::serde_as]
-        #[serde_with::skip_serializing_none]
-        #[derive(derive_builder::Builder)]
-        #[builder(setter(into, strip_option), default)]
-        #[builder(build_fn(name = "private_build"))]
-        #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct Todo {
-            #[prost(string, tag = "1")]
-            pub id: ::prost::alloc::string::String,
-            #[prost(string, tag = "2")]
-            pub title: ::prost::alloc::string::String,
-            #[prost(string, tag = "3")]
-            pub description: ::prost::alloc::string::String,
-            #[prost(enumeration = "TodoStatus", tag = "4")]
-            #[serde_as(as = "DisplayFromStr")]
-            pub status: i32,
-            #[prost(message, optional, tag = "5")]
-            #[serde_as(as = "DisplayFromStr")]
-            #[derive(Copy)]
-            pub created_at: ::core::option::Option<::prost_types::Timestamp>,
-            #[prost(message, optional, tag = "6")]
-            #[derive(Copy)]
-            pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
-        }
-        #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct GetTodosRequest {
-            #[prost(string, repeated, tag = "1")]
-            pub id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-        }
-        #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct CreateTodoRequest {
-            #[prost(string, tag = "1")]
-            pub title: ::prost::alloc::string::String,
-            #[prost(string, tag = "2")]
-            pub description: ::prost::alloc::string::String,
-        }
-        #[derive(Clone, PartialEq, ::prost::Message)]
-        pub struct DeleteTodoRequest {
-            #[prost(string, tag = "1")]
-            pub id: ::prost::alloc::string::String,
-        }
-        #[derive(Clone, Copy, PartialEq, ::prost::Message)]
-        pub struct DeleteTodoResponse {}
-        #[derive(serde::Serialize, serde::Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        #[derive(sqlx::Type)]
-        #[derive(strum::EnumString, strum::Display, strum::EnumIter)]
-        #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
-        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-        #[repr(i32)]
-        pub enum TodoStatus {
-            Doing = 0,
-            Done = 1,
+    fn with_include_file(self, name: &str) -> Self {
+        self.include_file(name)
+    }
+
+    fn with_protoc_args(self, args: &[&str]) -> Self {
+        args.iter()
+            .fold(self, |builder, arg| builder.protoc_arg(arg))
+    }
+}
+
+fn apply(builder: Builder, attrs: Vec<Attribute>) -> Builder {
+    let mut bytes = Vec::new();
+    let mut btree_map = Vec::new();
+    let mut builder = attrs.into_iter().fold(builder, |builder, attr| match attr {
+        Attribute::Type(path, attr) => builder.type_attribute(path, attr),
+        Attribute::Field(path, attr) => builder.field_attribute(path, attr),
+        Attribute::Message(path, attr) => builder.message_attribute(path, attr),
+        Attribute::Enum(path, attr) => builder.enum_attribute(path, attr),
+        Attribute::SkipDebug(path) => builder.skip_debug(path),
+        Attribute::Boxed(path) => builder.boxed(path),
+        Attribute::Bytes(path) => {
+            bytes.push(path);
+            builder
         }
-        impl TodoStatus {
-            /// String value of the enum field names used in the ProtoBuf definition.
-            ///
-            /// The values are not transformed in any way and thus are considered stable
-            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-            pub fn as_str_name(&self) -> &'static str {
-                match self {
-                    Self::Doing => "TODO_STATUS_DOING",
-                    Self::Done => "TODO_STATUS_DONE",
-                }
-            }
-            /// Creates an enum from field names used in the ProtoBuf definition.
-            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-                match value {
-                    "TODO_STATUS_DOING" => Some(Self::Doing),
-                    "TODO_STATUS_DONE" => Some(Self::Done),
-                    _ => None,
-                }
-            }
+        Attribute::BTreeMap(path) => {
+            btree_map.push(path);
+            builder
         }
-        /// Generated client implementations.
-        pub mod todo_service_client {
-            #![allow(
-                unused_variables,
-                dead_code,
-                missing_docs,
-                clippy::wildcard_imports,
-                clippy::let_unit_value,
-            )]
-            use tonic::codegen::*;
-            use tonic::codegen::http::Uri;
-            #[derive(Debug, Clone)]
-            pub struct TodoServiceClient<T> {
+    });
+    // `Builder::bytes` and `Builder::btree_map` replace the paths set before, so they're called
+    // once with all of them
+    if !bytes.is_empty() {
+        builder = builder.bytes(bytes);
+    }
+    if !btree_map.is_empty() {
+        builder = builder.btree_map(btree_map);
+    }
+    builder
+}
+
+fn record(mut generator: Generator<Builder>, attrs: Vec<Attribute>) -> Generator<Builder> {
+    generator.record(attrs);
+    generator
+}
+
+impl Generator<Builder> {
+    /// set the output directory of the generated code, see [`Builder::out_dir`]
+    pub fn out_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.builder = self.builder.out_dir(&path);
+        self.out_dir = Some(path);
+        self
+    }
+
+    /// get back the wrapped builder with the recorded attributes applied, dropping the
+    /// extensions
+    pub fn into_inner(mut self) -> Builder {
+        self.ordered.append(&mut self.recorded);
+        apply(self.builder, self.ordered)
+    }
+
+    /// remove the type attributes added so far through `BuilderAttributes` for the given
+    /// paths, e.g. to override a shared base configuration. Attributes set on the builder
+    /// before it was wrapped are kept, prost can't remove them.
+    pub fn without_type_attributes(mut self, paths: &[&str]) -> Self {
+        self.remove_type_attributes(paths);
+        self
+    }
+
+    /// compile the protos with the wrapped builder, then append the extra code
+    pub fn compile_protos(
+        self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<()> {
+        let parsed = self.parse(protos)?;
+        let attrs = self.attributes(&parsed)?;
+        let (builder, generator) = self.split();
+        apply(builder, attrs).compile_protos(protos, includes)?;
+        generator.post_process(&parsed)
+    }
+
+    /// like [`compile_protos`](Self::compile_protos), but also report which of the registered
+    /// attribute paths matched an item of the protos, to catch the ones prost silently ignores
+    pub fn compile_with_report(
+        self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<BuildReport> {
+        let parsed = Protos::load(protos)?;
+        let attrs = self.attributes(&parsed)?;
+        let report = self.report(&parsed, &attrs);
+        let (builder, generator) = self.split();
+        apply(builder, attrs).compile_protos(protos, includes)?;
+        generator.post_process(&parsed)?;
+        Ok(report)
+    }
+
+    /// generate `fn index_<type>_by_<key>(items: Vec<Type>) -> HashMap<Key, Type>`
+    pub fn with_index_by(mut self, path: &str, key_field: &str) -> Self {
+        self.extend(codegen::index_by(path, key_field));
+        self
+    }
+
+    /// serialize the given `google.protobuf.Duration` fields as integer milliseconds. This adds
+    /// `serde_with::serde_as` to the type and generates the `DurationMillis` adapter.
+    pub fn with_duration_millis(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::duration_millis(path, fields));
+        self
+    }
+
+    /// serialize the enum fields of the messages as the proto name of their value (e.g.
+    /// `"TODO_STATUS_DONE"`) instead of the `i32`. This adds `serde_with::serde_as` to the types
+    /// and generates `serde_with` adapter impls on the enums, which must be in the same package.
+    /// Values unknown to the enum are written as numbers, and both forms deserialize.
+    pub fn with_enum_fields_as_name(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::enum_fields_as_name(paths));
+        self
+    }
+
+    /// serialize the given `google.protobuf.Timestamp` fields as integer seconds since the Unix
+    /// epoch, dropping nanoseconds. This adds `serde_with::serde_as` to the type and generates
+    /// the `EpochSeconds` adapter.
+    pub fn with_epoch_seconds_timestamps(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::epoch_seconds(path, fields));
+        self
+    }
+
+    /// serialize the given `repeated google.protobuf.Timestamp` fields as arrays of RFC 3339
+    /// strings. This adds `serde_with::serde_as` to the type.
+    pub fn with_timestamp_array(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::timestamp_array(path, fields));
+        self
+    }
+
+    /// serialize every `google.protobuf.Timestamp` field of the compiled protos (singular,
+    /// repeated and map values, oneof members excluded) as an RFC 3339 string through
+    /// `chrono::DateTime<Utc>`. prost keeps generating `prost_types::Timestamp`, which can't be
+    /// swapped for a chrono type; `Duration` and the other well-known types are left alone. This
+    /// adds `serde_with::serde_as` to the messages and generates the `ChronoTimestamp` adapter,
+    /// which needs chrono's `serde` feature.
+    pub fn with_well_known_chrono(mut self) -> Self {
+        self.extend(codegen::chrono_timestamps());
+        self
+    }
+
+    /// like [`with_well_known_chrono`](Self::with_well_known_chrono), through
+    /// `time::OffsetDateTime` with the `TimeTimestamp` adapter, which needs time's
+    /// `serde-well-known` feature
+    pub fn with_well_known_time(mut self) -> Self {
+        self.extend(codegen::time_timestamps());
+        self
+    }
+
+    /// treat the given string fields as `rust_decimal::Decimal` numbers in serde: invalid values
+    /// are rejected and valid ones normalized on deserialization. prost still generates
+    /// `String` fields. This adds `serde_with::serde_as` to the type and generates the
+    /// `DecimalString` adapter.
+    pub fn with_decimal_fields(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::decimal_fields(path, fields));
+        self
+    }
+
+    /// treat the given string fields as `std::net::IpAddr` addresses in serde: invalid values
+    /// are rejected and valid ones normalized on deserialization. prost still generates
+    /// `String` fields. This adds `serde_with::serde_as` to the type and generates the
+    /// `IpAddrString` adapter.
+    pub fn with_ip_fields(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::ip_fields(path, fields));
+        self
+    }
+
+    /// (de)serialize the given `double` / `float` ratios as percentage strings, e.g. `0.42` as
+    /// `"42%"`, rounded to 4 decimal places. Invalid strings are rejected on deserialization.
+    /// This adds `serde_with::serde_as` to the type and generates the `Percentage` adapter.
+    pub fn with_percentage_fields(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::percentage_fields(path, fields));
+        self
+    }
+
+    /// (de)serialize the given `bytes` fields as a byte array prefixed with the data length, a
+    /// 4-byte big-endian `u32`. Deserialization rejects values whose prefix doesn't match. This
+    /// generates the `len_prefixed_bytes` module used through `#[serde(with = "...")]`.
+    pub fn with_len_prefixed_bytes(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::len_prefixed_bytes(path, fields));
+        self
+    }
+
+    /// serialize the given fields as `"***"` instead of skipping them, so the key is still
+    /// present. Deserialization accepts the real values.
+    pub fn with_serialize_redacted(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::serialize_redacted(path, fields));
+        self
+    }
+
+    /// rename every field of the message to `<prefix>_<field>` in serde, e.g. `todo_id`
+    pub fn with_serde_field_prefix(mut self, path: &str, prefix: &str) -> Self {
+        self.extend(codegen::serde_field_prefix(path, prefix));
+        self
+    }
+
+    /// derive `Debug` with `derivative` instead of prost, leaving out the given fields. prost's
+    /// own `Debug` impl is skipped for the type, see [`Config::skip_debug`].
+    pub fn with_derivative_debug(mut self, path: &str, ignore_fields: &[&str]) -> Self {
+        self.extend(codegen::derivative_debug(path, ignore_fields));
+        self
+    }
+
+    /// skip the elements of the given repeated fields that fail to deserialize instead of
+    /// rejecting the whole message. This adds `serde_with::serde_as` to the type.
+    pub fn with_vec_skip_errors(mut self, path: &str, fields: &[&str]) -> Self {
+        self.extend(codegen::vec_skip_errors(path, fields));
+        self
+    }
+
+    /// generate `fn parse_ci(s: &str) -> Option<Self>` on the given enums, matching both the
+    /// Rust variant names and the proto value names while ignoring ASCII case
+    pub fn with_enum_parse_ci(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::enum_parse_ci(paths));
+        self
+    }
+
+    /// generate `fn to_custom_str(&self) -> &'static str` and
+    /// `fn from_custom_str(s: &str) -> Option<Self>` on the enum, using the string of each
+    /// `(variant, string)` mapping, e.g. `("Doing", "in_progress")`, instead of the proto names of
+    /// `as_str_name`. Variants are given by their Rust or proto name; those without a mapping
+    /// keep their proto name. Fails to compile the protos if a variant doesn't exist or a string
+    /// is used twice.
+    pub fn with_enum_str_map(mut self, path: &str, mappings: &[(&str, &str)]) -> Self {
+        self.extend(codegen::enum_str_map(path, mappings));
+        self
+    }
+
+    /// generate `const PROTO_NAMES: &[(Self, &str)]` on the given enums, pairing each variant
+    /// with its proto name
+    pub fn with_enum_name_map(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::enum_name_map(paths));
+        self
+    }
+
+    /// generate `fn next(&self) -> Option<Self>` and `fn prev(&self) -> Option<Self>` on the
+    /// given enums, moving through the variants in the order they are declared
+    pub fn with_enum_navigation(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::enum_navigation(paths));
+        self
+    }
+
+    /// generate `fn cache_encoded_len(self) -> EncodedLenCache<Self>` on the given messages. The
+    /// wrapper memoizes `encoded_len` in a `OnceLock` and resets it on mutable access.
+    pub fn with_encoded_len_cache(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::encoded_len_cache(paths));
+        self
+    }
+
+    /// generate `fn default_instance() -> &'static Self` on the given messages, a default
+    /// message created on first use in a `OnceLock`, e.g. to compare against or reset from
+    pub fn with_default_instance(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::default_instance(paths));
+        self
+    }
+
+    /// generate read-only getters for the fields of the given messages, e.g.
+    /// `pub fn title(&self) -> &str`. Fields prost already has a getter for (enums and optional
+    /// scalars) are skipped.
+    pub fn with_getters(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::getters(paths));
+        self
+    }
+
+    /// generate `fn into_inner(self) -> T` and `From<Wrapper> for T` on messages wrapping a
+    /// single value. Fails if one of them has more or fewer fields. Panics if the protos can't
+    /// be parsed.
+    pub fn with_into_inner(
+        mut self,
+        proto_files: &[&str],
+        paths: &[&str],
+    ) -> Result<Self, BuilderError> {
+        single_field_messages(proto_files, paths)?;
+        self.extend(codegen::into_inner(paths));
+        Ok(self)
+    }
+
+    /// make `variant` (its Rust or proto name, e.g. `Done` or `TODO_STATUS_DONE`) the default
+    /// of the enum. prost's derive picks the first variant as `Default`, so the variant is moved
+    /// first in the generated enum; values and ordering are unchanged. prost also uses it as the
+    /// default of message fields of the enum type, so an omitted field decodes as this variant,
+    /// while other proto3 implementations omit (and read back) `0`. Don't use it for enums in
+    /// messages exchanged with them.
+    pub fn with_enum_default(mut self, path: &str, variant: &str) -> Self {
+        self.extend(codegen::enum_default(path, variant));
+        self
+    }
+
+    /// replace the `#[repr(i32)]` prost puts on the given enums with `#[repr(<repr>)]`, e.g.
+    /// `u8` or `C`. Fails if a value doesn't fit in the integer type.
+    pub fn with_enum_repr(mut self, paths: &[&str], repr: &str) -> Self {
+        self.extend(codegen::enum_repr(paths, repr));
+        self
+    }
+
+    /// generate `fn to_metadata(&self) -> tonic::metadata::MetadataMap` on the message, copying
+    /// each `(proto_field, metadata_key)` string field into the map
+    pub fn with_metadata_fields(mut self, path: &str, fields: &[(&str, &str)]) -> Self {
+        self.extend(codegen::metadata_fields(path, fields));
+        self
+    }
+
+    /// generate `fn scrub(&self) -> Self` on the message, returning a copy with `remove_fields`
+    /// reset to their default and the string `bucket_fields` replaced by a stable hash
+    pub fn with_scrub(
+        mut self,
+        path: &str,
+        remove_fields: &[&str],
+        bucket_fields: &[&str],
+    ) -> Self {
+        self.extend(codegen::scrub(path, remove_fields, bucket_fields));
+        self
+    }
+
+    /// generate `fn redacted(&self) -> Self` on the message, returning a copy with the given
+    /// fields reset to their default, e.g. before handing it to a less trusted system
+    pub fn with_redacted_clone(mut self, path: &str, redact_fields: &[&str]) -> Self {
+        self.extend(codegen::redacted_clone(path, redact_fields));
+        self
+    }
+
+    /// generate `impl Display` for the message writing `template`, with each `{field}`
+    /// placeholder replaced by the field, e.g. `"{title} ({status})"`. A format spec can follow
+    /// the name, e.g. `{created_at:?}` for the fields that aren't `Display`. Enum fields are
+    /// written as their `i32`. Fails to compile the protos if a field doesn't exist.
+    pub fn with_display_template(mut self, path: &str, template: &str) -> Self {
+        self.extend(codegen::display_template(path, template));
+        self
+    }
+
+    /// replace prost's `Debug` impl of the message with one printing the given fields as
+    /// `"***"`, so secrets don't end up in logs. Unlike prost's impl, enum fields are printed as
+    /// their `i32`. prost's own `Debug` is skipped for the type, see [`Config::skip_debug`].
+    pub fn with_redacted_debug(mut self, path: &str, redact_fields: &[&str]) -> Self {
+        self.extend(codegen::redacted_debug(path, redact_fields));
+        self
+    }
+
+    /// generate `struct <Type>Patch`, a copy of the message with every field wrapped in an
+    /// `Option`, and `fn apply_patch(&mut self, patch: <Type>Patch)` overwriting the fields
+    /// that are set, e.g. for PATCH-style APIs.
+    pub fn with_partial_type(mut self, path: &str) -> Self {
+        self.extend(codegen::partial_type(path));
+        self
+    }
+
+    /// generate `struct <Type>Cow<'a>`, a copy of the message with the given string fields
+    /// (e.g. `todo.Todo.title`) as `Cow<'a, str>` for zero-copy parsing, and
+    /// `From<<Type>Cow<'_>> for <Type>`. prost's derive only encodes `String`s, so the message
+    /// itself is unchanged. Fails if a field isn't a singular string. Panics if the protos
+    /// can't be parsed.
+    pub fn with_cow_fields(
+        mut self,
+        proto_files: &[&str],
+        fields: &[&str],
+    ) -> Result<Self, BuilderError> {
+        string_fields(proto_files, fields)?;
+        self.extend(codegen::cow_fields(fields));
+        Ok(self)
+    }
+
+    /// generate `struct <Type>Secret`, a copy of the message with the given string fields (e.g.
+    /// `account.Account.token`) as `secrecy::Secret<String>`, and `From` both ways. prost's
+    /// derive only encodes `String`s and forces `PartialEq`, which `Secret` doesn't implement, so
+    /// the message itself is unchanged and the copy only derives `Debug`: convert into it right
+    /// after decoding and back right before encoding. Needs `secrecy` 0.8 as a dependency. Fails
+    /// if a field isn't a singular string. Panics if the protos can't be parsed.
+    pub fn with_secret_fields(
+        mut self,
+        proto_files: &[&str],
+        fields: &[&str],
+    ) -> Result<Self, BuilderError> {
+        string_fields(proto_files, fields)?;
+        self.extend(codegen::secret_fields(fields));
+        Ok(self)
+    }
+
+    /// generate a newtype for each `(field, newtype)` string field, e.g.
+    /// `("todo.Todo.id", "TodoId")` for `struct TodoId(String)`, with `From` conversions both ways,
+    /// `Display` and a `typed_id()` getter on the message. prost's derive encodes and defaults
+    /// the field as a `String`, so the field itself keeps its type. Fields sharing a newtype in
+    /// the same module get it once. Fails if a field isn't a singular string. Panics if the
+    /// protos can't be parsed.
+    pub fn with_typed_ids(
+        mut self,
+        proto_files: &[&str],
+        fields: &[(&str, &str)],
+    ) -> Result<Self, BuilderError> {
+        let paths: Vec<&str> = fields.iter().map(|(path, _)| *path).collect();
+        string_fields(proto_files, &paths)?;
+        self.extend(codegen::typed_ids(fields));
+        Ok(self)
+    }
+
+    /// generate `struct <mirror_name>`, a copy of the message fields with
+    /// `#[serde(remote = "<Type>")]`, so the message can be (de)serialized through
+    /// `#[serde(with = "<mirror_name>")]` without deriving serde on it. The field types must
+    /// implement serde themselves (or carry a `#[serde(with = ...)]`), which the well-known
+    /// types like `Timestamp` don't.
+    pub fn with_serde_remote(mut self, path: &str, mirror_name: &str) -> Self {
+        self.extend(codegen::serde_remote(path, mirror_name));
+        self
+    }
+
+    /// generate `fn checksum(&self) -> u64` on the given messages, a stable hash of their
+    /// encoded bytes for change detection
+    pub fn with_checksum(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::checksum(paths));
+        self
+    }
+
+    /// generate `fn mock() -> Self` on the given messages, filling every field with a
+    /// deterministic placeholder for tests: strings hold the field name, numbers the field
+    /// number, enums their last value and nested messages their default
+    pub fn with_mock(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::mock(paths));
+        self
+    }
+
+    /// generate `fn present_fields(&self) -> u64` on the given messages, a bitset of the fields
+    /// holding a non-default value, with a `<FIELD>_BIT` constant for the position of each
+    /// field. Fails for messages with more than 64 fields.
+    pub fn with_presence_bitset(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::presence_bitset(paths));
+        self
+    }
+
+    /// run every call the generated server makes to the methods of the given services (e.g.
+    /// `todo.TodoService`) inside a `tracing::info_span!` named after the method, like
+    /// `#[tracing::instrument(skip(self))]` would. Needs `build_server(true)` and `tracing` as a
+    /// dependency.
+    pub fn with_instrument(mut self, services: &[&str]) -> Self {
+        self.extend(codegen::instrument(services));
+        self
+    }
+
+    /// increment `metrics::counter!("<namespace>_requests_total")`, labelled with the `service`
+    /// (e.g. `todo.TodoService`) and `method` (e.g. `create_todo`), on every call the generated
+    /// servers make to the service methods. Needs `build_server(true)` and `metrics` 0.22 or
+    /// later as a dependency.
+    pub fn with_method_counters(mut self, namespace: &str) -> Self {
+        self.extend(codegen::method_counters(namespace));
+        self
+    }
+
+    /// add a `<service>_paths` module (e.g. `todo_service_paths`) with a `pub const` request path
+    /// per method, e.g. `CREATE_TODO: &str = "/todo.TodoService/CreateTodo"`, for routing and
+    /// metrics. tonic itself only has `SERVICE_NAME`.
+    pub fn with_method_path_consts(mut self) -> Self {
+        self.extend(codegen::method_path_consts());
+        self
+    }
+
+    /// add `#[allow(missing_docs)]` to every top-level item of the generated files, for crates
+    /// that deny `missing_docs`. It only applies to the generated code, not to the module it is
+    /// included in.
+    pub fn allow_missing_docs(mut self) -> Self {
+        self.extend(codegen::allow_missing_docs());
+        self
+    }
+
+    /// once the code is generated, print a `cargo:warning` for every trait derived more than
+    /// once on a type, e.g. `Clone` added through `with_type_attributes` on top of prost's own
+    /// derive, which otherwise only shows up as a confusing compile error
+    pub fn warn_on_duplicate_derives(mut self) -> Self {
+        self.extend(codegen::warn_duplicate_derives());
+        self
+    }
+
+    /// generate `fn validate(&self) -> Result<(), String>` on the given messages, enforcing the
+    /// `protoc-gen-validate` rules (`(validate.rules)` field options) of their fields. The
+    /// `pattern` rule needs the `regex` crate.
+    pub fn with_pgv_validation(mut self, paths: &[&str]) -> Self {
+        self.extend(codegen::pgv_validation(paths));
+        self
+    }
+}
+
+impl BuilderAttributes for Generator<Builder> {
+    fn with_serde(self, paths: &[&str], ser: bool, de: bool, extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, serde_attr(ser, de), extra_attrs))
+    }
+
+    fn with_serde_excluding(self, paths: &[&str], exclude: &[&str], ser: bool, de: bool) -> Self {
+        record(self, serde_excluding_attrs(paths, exclude, ser, de))
+    }
+
+    fn with_serde_as(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
+        record(self, serde_as_attrs(path, fields))
+    }
+
+    fn with_serde_as_vec(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
+        record(self, serde_as_vec_attrs(path, fields))
+    }
+
+    fn with_serde_as_map(self, path: &str, fields: &[(&[&str], &str)]) -> Self {
+        record(self, serde_as_map_attrs(path, fields))
+    }
+
+    fn with_serde_as_custom(
+        self,
+        path: &str,
+        fields: &[(&str, &str)],
+        container: Option<&str>,
+    ) -> Self {
+        record(self, serde_as_custom_attrs(path, fields, container))
+    }
+
+    fn with_i64_as_string(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        record(self, i64_as_string_attrs(proto_files, paths))
+    }
+
+    fn with_base64_bytes(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        record(self, base64_bytes_attrs(proto_files, paths))
+    }
+
+    fn with_comments_as_schema_desc(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        record(self, comments_as_schema_desc_attrs(proto_files, paths))
+    }
+
+    fn with_serde_tag(self, paths: &[&str], tag: &str) -> Self {
+        record(self, type_attrs(paths, &serde_tag_attr(tag), None))
+    }
+
+    fn with_serde_tag_content(self, paths: &[&str], tag: &str, content: &str) -> Self {
+        record(
+            self,
+            type_attrs(paths, &serde_tag_content_attr(tag, content), None),
+        )
+    }
+
+    fn with_serde_untagged(self, paths: &[&str]) -> Self {
+        record(self, type_attrs(paths, serde_untagged_attr(), None))
+    }
+
+    fn with_serde_flatten(self, fields: &[&str]) -> Self {
+        record(
+            self,
+            joined_field_attrs(fields, Some(&[serde_flatten_attr()])),
+        )
+    }
+
+    fn with_serde_deny_unknown_fields(self, paths: &[&str]) -> Self {
+        record(
+            self,
+            type_attrs(paths, serde_deny_unknown_fields_attr(), None),
+        )
+    }
+
+    fn with_field_serde_with(self, fields: &[(&str, &str)]) -> Self {
+        record(self, serde_with_attrs(fields))
+    }
+
+    fn with_serde_enum_original_names(self, proto_files: &[&str], paths: &[&str]) -> Self {
+        record(self, enum_original_names_attrs(proto_files, paths))
+    }
+
+    fn with_serde_all(self, proto_files: &[&str], package: &str, ser: bool, de: bool) -> Self {
+        let kinds = [TypeKind::Message, TypeKind::Enum];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, ser, de, None),
+        )
+    }
+
+    fn with_serde_all_from_descriptor(
+        self,
+        descriptor_path: &str,
+        package: &str,
+        ser: bool,
+        de: bool,
+    ) -> Self {
+        record(
+            self,
+            serde_descriptor_attrs(descriptor_path, package, ser, de),
+        )
+    }
+
+    fn with_serde_enums(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self {
+        let kinds = [TypeKind::Enum];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_serde_enum_rename(self, paths: &[&str], rename: RenameAll) -> Self {
+        let attr = serde_rename_all_attr(rename);
+        record(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_rename_all_fields(self, paths: &[&str], rename: RenameAll) -> Self {
+        let attr = serde_rename_all_fields_attr(rename);
+        record(self, joined_enum_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_serde_messages(self, proto_files: &[&str], package: &str, rename: RenameAll) -> Self {
+        let kinds = [TypeKind::Message];
+        record(
+            self,
+            serde_package_attrs(proto_files, package, &kinds, true, true, Some(rename)),
+        )
+    }
+
+    fn with_sqlx_type(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, sqlx_type_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, sqlx_from_row_attr(), extra_attrs))
+    }
+
+    fn with_sqlx_from_row_opts(
+        self,
+        paths: &[&str],
+        rename_all: Option<&str>,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        let attr = sqlx_from_row_opts_attr(rename_all);
+        record(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_api_preset(self, paths: &[&str]) -> Self {
+        record(self, api_preset_attrs(paths))
+    }
+
+    fn with_db_preset(self, paths: &[&str]) -> Self {
+        record(self, db_preset_attrs(paths))
+    }
+
+    fn with_profile(self, paths: &[&str], profile: &Profile) -> Self {
+        record(self, profile_attrs(paths, profile))
+    }
+
+    fn with_derive_builder(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, derive_builder_attr(), extra_attrs))
+    }
+
+    fn with_builder_field_default(self, fields: &[(&str, &str)]) -> Self {
+        record(self, builder_defaults_attrs(fields))
+    }
+
+    fn with_diesel(
+        self,
+        paths: &[&str],
+        table_name: Option<&str>,
+        queryable: bool,
+        insertable: bool,
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        let attr = diesel_attr(table_name, queryable, insertable);
+        record(self, type_attrs(paths, &attr, extra_attrs))
+    }
+
+    fn with_sea_orm(
+        self,
+        path: &str,
+        table_name: &str,
+        primary_keys: &[&str],
+        extra_attrs: Option<&[&str]>,
+    ) -> Self {
+        record(
+            self,
+            sea_orm_attrs(path, table_name, primary_keys, extra_attrs),
+        )
+    }
+
+    fn with_zeroize(self, paths: &[&str], on_drop: bool, extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, zeroize_attr(on_drop), extra_attrs))
+    }
+
+    fn with_arbitrary(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, arbitrary_attr(), extra_attrs))
+    }
+
+    fn with_proptest(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, proptest_attr(), extra_attrs))
+    }
+
+    fn with_async_graphql_object(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        let attr = async_graphql_simple_object_attr();
+        record(self, type_attrs(paths, attr, extra_attrs))
+    }
+
+    fn with_async_graphql_enum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(
+            self,
+            type_attrs(paths, async_graphql_enum_attr(), extra_attrs),
+        )
+    }
+
+    fn with_rkyv(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, rkyv_attr(), extra_attrs))
+    }
+
+    fn with_fake(self, paths: &[&str], field_fakers: &[(&str, &str)]) -> Self {
+        record(self, fake_attrs(paths, field_fakers))
+    }
+
+    fn with_clap(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, clap_parser_attr(), extra_attrs))
+    }
+
+    fn with_clap_args(self, fields: &[(&str, &str)]) -> Self {
+        record(self, clap_args_attrs(fields))
+    }
+
+    fn with_derive_more(self, paths: &[&str], traits: &[&str]) -> Self {
+        record(self, type_attrs(paths, &derive_more_attr(traits), None))
+    }
+
+    fn with_derive_where<F: Fn(&str) -> bool>(
+        self,
+        proto_files: &[&str],
+        predicate: F,
+        derives: &[&str],
+    ) -> Self {
+        record(self, derive_where_attrs(proto_files, predicate, derives))
+    }
+
+    fn with_eq_hash_safe(self, proto_files: &[&str], paths: &[&str]) -> Result<Self, BuilderError> {
+        let attrs = eq_hash_safe_attrs(proto_files, paths)?;
+        Ok(record(self, attrs))
+    }
+
+    fn derive_eq_where_possible(self, proto_files: &[&str]) -> Self {
+        record(self, eq_where_possible_attrs(proto_files))
+    }
+
+    fn with_non_exhaustive(self, paths: &[&str]) -> Self {
+        record(self, type_attrs(paths, non_exhaustive_attr(), None))
+    }
+
+    fn with_ord(self, paths: &[&str]) -> Self {
+        record(self, type_attrs(paths, ord_attr(), None))
+    }
+
+    fn with_deprecated_types(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = deprecated_attr(note);
+        record(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_deprecated_fields(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = deprecated_attr(note);
+        record(self, joined_field_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_must_use(self, paths: &[&str], note: Option<&str>) -> Self {
+        let attr = must_use_attr(note);
+        record(self, joined_type_attrs(paths, Some(&[attr.as_str()])))
+    }
+
+    fn with_strum(self, paths: &[&str], extra_attrs: Option<&[&str]>) -> Self {
+        record(self, type_attrs(paths, strum_attr(), extra_attrs))
+    }
+
+    fn with_type_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        record(self, joined_type_attrs(paths, Some(attributes)))
+    }
+
+    fn with_message_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        record(self, joined_message_attrs(paths, Some(attributes)))
+    }
+
+    fn with_enum_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        record(self, joined_enum_attrs(paths, Some(attributes)))
+    }
+
+    fn with_ordered_attributes(mut self, paths: &[&str], ordered: &[&str]) -> Self {
+        self.record_ordered(joined_type_attrs(paths, Some(ordered)));
+        self
+    }
+
+    fn with_attribute_map<I, P, A, S>(self, map: I) -> Self
+    where
+        I: IntoIterator<Item = (P, A)>,
+        P: AsRef<str>,
+        A: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        record(self, attribute_map_attrs(map))
+    }
+
+    fn with_field_attributes(self, paths: &[&str], attributes: &[&str]) -> Self {
+        record(self, joined_field_attrs(paths, Some(attributes)))
+    }
+
+    fn with_boxed_fields(self, fields: &[&str]) -> Self {
+        record(self, boxed_attrs(fields))
+    }
+
+    fn with_bytes(self, paths: &[&str]) -> Self {
+        record(self, bytes_attrs(paths))
+    }
+
+    fn with_btree_maps(self, paths: &[&str]) -> Self {
+        record(self, btree_map_attrs(paths))
+    }
+
+    fn with_field_attributes_by_type(
+        self,
+        proto_files: &[&str],
+        proto_type: &str,
+        attribute: &str,
+    ) -> Self {
+        record(
+            self,
+            fields_by_type_attrs(proto_files, proto_type, attribute),
+        )
+    }
+
+    fn with_field_attributes_by_tag(
+        self,
+        proto_files: &[&str],
+        targets: &[(&str, u32, &str)],
+    ) -> Self {
+        record(self, field_attributes_by_tag_attrs(proto_files, targets))
+    }
+
+    fn with_field_attributes_matching(
+        self,
+        proto_files: &[&str],
+        pattern: &str,
+        attribute: &str,
+    ) -> Result<Self, BuilderError> {
+        let attrs = fields_matching_attrs(proto_files, pattern, attribute)?;
+        Ok(record(self, attrs))
+    }
+
+    fn with_optional_type_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self {
+        record(self, joined_type_attrs(paths, attributes))
+    }
+
+    fn with_optional_field_attributes(self, paths: &[&str], attributes: Option<&[&str]>) -> Self {
+        record(self, joined_field_attrs(paths, attributes))
+    }
+
+    fn with_extern_paths(mut self, mappings: &[(&str, &str)]) -> Self {
+        self.builder = self.builder.with_extern_paths(mappings);
+        self
+    }
+
+    fn with_include_file(mut self, name: &str) -> Self {
+        self.builder = self.builder.include_file(name);
+        self
+    }
+
+    fn with_protoc_args(mut self, args: &[&str]) -> Self {
+        self.builder = self.builder.with_protoc_args(args);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tonic_build_with_extra_attributes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .out_dir(path.path())
+            .with_serde(
+                &["todo.Todo", "todo.TodoStatus"],
+                true,
+                true,
+                Some(&[r#"#[serde(rename_all = "camelCase")]"#]),
+            )
+            .with_serde_as(
+                "todo.Todo",
+                &[(
+                    &["status", "created_at"],
+                    r#"#[serde_as(as = "DisplayFromStr")]"#,
+                )],
+            )
+            .with_derive_builder(
+                &["todo.Todo"],
+                Some(&[r#"#[builder(build_fn(name = "private_build"))]"#]),
+            )
+            .with_sqlx_type(&["todo.TodoStatus"], None)
+            .with_strum(
+                &["todo.TodoStatus"],
+                Some(&[r#"#[strum(ascii_case_insensitive, serialize_all = "snake_case")]"#]),
+            )
+            .with_field_attributes(
+                &["todo.Todo.created_at", "todo.Todo.updated_at"],
+                &["#[derive(Copy)]"],
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        insta::assert_snapshot!(fs::read_to_string(filename).unwrap(), @r###"
+        // This file is @generated by prost-build.
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #[serde_with::serde_as]
+        #[serde_with::skip_serializing_none]
+        #[derive(derive_builder::Builder)]
+        #[builder(setter(into, strip_option), default)]
+        #[builder(build_fn(name = "private_build"))]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Todo {
+            #[prost(string, tag = "1")]
+            pub id: ::prost::alloc::string::String,
+            #[prost(string, tag = "2")]
+            pub title: ::prost::alloc::string::String,
+            #[prost(string, tag = "3")]
+            pub description: ::prost::alloc::string::String,
+            #[prost(enumeration = "TodoStatus", tag = "4")]
+            #[serde_as(as = "DisplayFromStr")]
+            pub status: i32,
+            #[prost(message, optional, tag = "5")]
+            #[serde_as(as = "DisplayFromStr")]
+            #[derive(Copy)]
+            pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+            #[prost(message, optional, tag = "6")]
+            #[derive(Copy)]
+            pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct GetTodosRequest {
+            #[prost(string, repeated, tag = "1")]
+            pub id: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct CreateTodoRequest {
+            #[prost(string, tag = "1")]
+            pub title: ::prost::alloc::string::String,
+            #[prost(string, tag = "2")]
+            pub description: ::prost::alloc::string::String,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct DeleteTodoRequest {
+            #[prost(string, tag = "1")]
+            pub id: ::prost::alloc::string::String,
+        }
+        #[derive(Clone, Copy, PartialEq, ::prost::Message)]
+        pub struct DeleteTodoResponse {}
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #[derive(sqlx::Type)]
+        #[derive(strum::EnumString, strum::Display, strum::EnumIter)]
+        #[strum(ascii_case_insensitive, serialize_all = "snake_case")]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+        #[repr(i32)]
+        pub enum TodoStatus {
+            Doing = 0,
+            Done = 1,
+        }
+        impl TodoStatus {
+            /// String value of the enum field names used in the ProtoBuf definition.
+            ///
+            /// The values are not transformed in any way and thus are considered stable
+            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+            pub fn as_str_name(&self) -> &'static str {
+                match self {
+                    Self::Doing => "TODO_STATUS_DOING",
+                    Self::Done => "TODO_STATUS_DONE",
+                }
+            }
+            /// Creates an enum from field names used in the ProtoBuf definition.
+            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+                match value {
+                    "TODO_STATUS_DOING" => Some(Self::Doing),
+                    "TODO_STATUS_DONE" => Some(Self::Done),
+                    _ => None,
+                }
+            }
+        }
+        /// Generated client implementations.
+        pub mod todo_service_client {
+            #![allow(
+                unused_variables,
+                dead_code,
+                missing_docs,
+                clippy::wildcard_imports,
+                clippy::let_unit_value,
+            )]
+            use tonic::codegen::*;
+            use tonic::codegen::http::Uri;
+            #[derive(Debug, Clone)]
+            pub struct TodoServiceClient<T> {
                 inner: tonic::client::Grpc<T>,
             }
             impl TodoServiceClient<tonic::transport::Channel> {
@@ -730,24 +2190,1563 @@ mod tests {
                     }
                 }
             }
-            impl<T> Clone for TodoServiceServer<T> {
-                fn clone(&self) -> Self {
-                    let inner = self.inner.clone();
-                    Self {
-                        inner,
-                        accept_compression_encodings: self.accept_compression_encodings,
-                        send_compression_encodings: self.send_compression_encodings,
-                        max_decoding_message_size: self.max_decoding_message_size,
-                        max_encoding_message_size: self.max_encoding_message_size,
-                    }
-                }
+            impl<T> Clone for TodoServiceServer<T> {
+                fn clone(&self) -> Self {
+                    let inner = self.inner.clone();
+                    Self {
+                        inner,
+                        accept_compression_encodings: self.accept_compression_encodings,
+                        send_compression_encodings: self.send_compression_encodings,
+                        max_decoding_message_size: self.max_decoding_message_size,
+                        max_encoding_message_size: self.max_encoding_message_size,
+                    }
+                }
+            }
+            /// Generated gRPC service name
+            pub const SERVICE_NAME: &str = "todo.TodoService";
+            impl<T> tonic::server::NamedService for TodoServiceServer<T> {
+                const NAME: &'static str = SERVICE_NAME;
+            }
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_tonic_build_with_index_by_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_index_by("todo.Todo", "id")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[derive(serde::Serialize, serde::Deserialize)]"));
+        assert!(content.ends_with(
+            r#"/// Index a list of [`Todo`] by its `id` field.
+pub fn index_todo_by_id(
+    items: ::prost::alloc::vec::Vec<Todo>,
+) -> ::std::collections::HashMap<::prost::alloc::string::String, Todo> {
+    items.into_iter().map(|item| (item.id.clone(), item)).collect()
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_sqlx_from_row_opts_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .out_dir(path.path())
+            .with_sqlx_from_row_opts(&["todo.Todo"], Some("camelCase"), None)
+            .with_sqlx_from_row_opts(&["todo.CreateTodoRequest"], None, None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(sqlx::FromRow)]
+#[sqlx(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Todo {"#
+        ));
+        assert!(content.contains(
+            r#"#[derive(sqlx::FromRow)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateTodoRequest {"#
+        ));
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn test_with_strum_should_match_prost_build() {
+        use crate::prost::BuilderAttributes as _;
+
+        let strum = Some(&[r#"#[strum(ascii_case_insensitive)]"#][..]);
+        let prost_dir = tempdir().unwrap();
+        prost_build::Config::default()
+            .out_dir(prost_dir.path())
+            .with_strum(&["todo.TodoStatus"], strum)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let tonic_dir = tempdir().unwrap();
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(tonic_dir.path())
+            .with_strum(&["todo.TodoStatus"], strum)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+
+        let prost = fs::read_to_string(prost_dir.path().join("todo.rs")).unwrap();
+        let tonic = fs::read_to_string(tonic_dir.path().join("todo.rs")).unwrap();
+        assert!(prost.contains(strum_attr()));
+        assert_eq!(prost, tonic);
+    }
+
+    #[test]
+    fn test_tonic_build_with_arbitrary_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_arbitrary(&["todo.CreateTodoRequest"], None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(arbitrary::Arbitrary)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct CreateTodoRequest {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_proptest_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_proptest(&["todo.Todo"], Some(&["#[proptest(no_params)]"]))
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(proptest_derive::Arbitrary)]\n#[proptest(no_params)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_enums_and_messages_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let protos = ["fixtures/protos/todo.proto"];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde_enums(&protos, "todo", RenameAll::ScreamingSnakeCase)
+            .with_serde_messages(&protos, "todo", RenameAll::CamelCase)
+            .compile_protos(&protos, &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TodoStatus {"#
+        ));
+        assert_eq!(content.matches("SCREAMING_SNAKE_CASE").count(), 1);
+        assert_eq!(
+            content
+                .matches(r#"#[serde(rename_all = "camelCase")]"#)
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_non_exhaustive_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_non_exhaustive(&["todo.TodoStatus"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[non_exhaustive]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]\n#[repr(i32)]\npub enum TodoStatus {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_enum_repr_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_enum_repr(&["todo.TodoStatus"], "u8")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[repr(u8)]\npub enum TodoStatus {"));
+        assert!(!content.contains("#[repr(i32)]"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_deprecated_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_deprecated_types(&["todo.DeleteTodoResponse"], None)
+            .with_deprecated_fields(&["todo.Todo.description"], Some("use `title` instead"))
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "3")]
+    #[deprecated(note = "use `title` instead")]
+    pub description: ::prost::alloc::string::String,"#
+        ));
+        assert!(content.contains("#[deprecated]\n#[derive(Clone, Copy, PartialEq, ::prost::Message)]\npub struct DeleteTodoResponse {}"));
+        assert_eq!(content.matches("deprecated").count(), 2);
+    }
+
+    #[test]
+    fn test_tonic_build_with_must_use_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_must_use(&["todo.Todo"], Some("the todo may have changed"))
+            .with_must_use(&["todo.DeleteTodoResponse"], None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[must_use = \"the todo may have changed\"]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"));
+        assert!(content.contains("#[must_use]\n#[derive(Clone, Copy, PartialEq, ::prost::Message)]\npub struct DeleteTodoResponse {}"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_extern_paths_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_extern_paths(&[
+                (".google.protobuf.Timestamp", "::pbjson_types::Timestamp"),
+                (".todo.TodoStatus", "crate::status::TodoStatus"),
+            ])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(
+            content.contains("pub created_at: ::core::option::Option<::pbjson_types::Timestamp>,")
+        );
+        assert!(
+            content.contains(r#"#[prost(enumeration = "crate::status::TodoStatus", tag = "4")]"#)
+        );
+        assert!(!content.contains("pub enum TodoStatus"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_derive_more_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_derive_more(&["todo.CreateTodoRequest"], &["From", "derive_more::Into"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(derive_more::From, derive_more::Into)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct CreateTodoRequest {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_derive_where_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_derive_where(
+                &["fixtures/protos/todo.proto"],
+                |name| name.ends_with("Request"),
+                &["clap::Parser"],
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for name in ["GetTodosRequest", "CreateTodoRequest", "DeleteTodoRequest"] {
+            assert!(content.contains(&format!(
+                "#[derive(clap::Parser)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct {} {{",
+                name
+            )));
+        }
+        assert_eq!(content.matches("clap::Parser").count(), 3);
+    }
+
+    #[test]
+    fn test_tonic_build_with_field_attributes_by_type_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let protos = ["fixtures/protos/todo.proto"];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_field_attributes_by_type(&protos, "string", "#[doc(alias = \"text\")]")
+            .compile_protos(&protos, &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // id, title and description of `Todo`, the repeated `GetTodosRequest.id`, title and
+        // description of `CreateTodoRequest` and `DeleteTodoRequest.id`
+        assert_eq!(content.matches("#[doc(alias = \"text\")]").count(), 7);
+        assert!(content.contains(
+            "    #[prost(string, repeated, tag = \"1\")]\n    #[doc(alias = \"text\")]\n    pub id: "
+        ));
+        assert!(content.contains(
+            "    #[prost(enumeration = \"TodoStatus\", tag = \"4\")]\n    pub status: i32,"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_field_attributes_by_tag_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let protos = ["fixtures/protos/todo.proto"];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_field_attributes_by_tag(&protos, &[("todo.Todo", 1, "#[doc(alias = \"key\")]")])
+            .compile_protos(&protos, &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "    #[prost(string, tag = \"1\")]\n    #[doc(alias = \"key\")]\n    pub id: "
+        ));
+        assert_eq!(content.matches("#[doc(alias = \"key\")]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_field_attributes_matching_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_field_attributes_matching(
+                &["fixtures/protos/todo.proto"],
+                r".*\.id$",
+                "#[serde(rename = \"ID\")]",
+            )
+            .unwrap()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "    #[prost(string, tag = \"1\")]\n    #[serde(rename = \"ID\")]\n    pub id: ::prost::alloc::string::String,"
+        ));
+        // `Todo`, `GetTodosRequest` and `DeleteTodoRequest`
+        assert_eq!(content.matches("#[serde(rename = \"ID\")]").count(), 3);
+
+        let err = tonic_build::configure()
+            .with_field_attributes_matching(&["fixtures/protos/todo.proto"], "(", "")
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuilderError::InvalidPattern { .. }));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_enum_original_names_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let protos = ["fixtures/protos/todo.proto"];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["todo.TodoStatus"], true, true, None)
+            .with_serde_enum_original_names(&protos, &["todo.TodoStatus"])
+            .compile_protos(&protos, &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"pub enum TodoStatus {
+    #[serde(rename = "TODO_STATUS_DOING")]
+    Doing = 0,
+    #[serde(rename = "TODO_STATUS_DONE")]
+    Done = 1,
+}"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_attribute_map_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let map: Vec<(String, Vec<String>)> = vec![
+            (
+                "todo.Todo".to_string(),
+                vec![
+                    "#[derive(Eq)]".to_string(),
+                    "#[doc(alias = \"task\")]".to_string(),
+                ],
+            ),
+            ("todo.TodoStatus".to_string(), vec![]),
+        ];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_attribute_map(map)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(Eq)]\n#[doc(alias = \"task\")]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_async_graphql_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_async_graphql_object(&["todo.Todo"], Some(&["#[graphql(complex)]"]))
+            .with_async_graphql_enum(&["todo.TodoStatus"], None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(async_graphql::SimpleObject)]\n#[graphql(complex)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        assert!(content.contains(
+            "#[derive(async_graphql::Enum)]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]\n#[repr(i32)]\npub enum TodoStatus {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_as_containers_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("metrics.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["metrics.Sample"], true, true, None)
+            .with_serde_as_vec("metrics.Sample", &[(&["values"], "DisplayFromStr")])
+            .with_serde_as_map("metrics.Sample", &[(&["counters"], "DisplayFromStr")])
+            .compile_protos(&["fixtures/protos/metrics.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(int64, repeated, tag = "2")]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub values: ::prost::alloc::vec::Vec<i64>,"#
+        ));
+        assert!(content.contains(
+            r#"    #[serde_as(as = "::std::collections::HashMap<_, DisplayFromStr>")]
+    pub counters: ::std::collections::HashMap<::prost::alloc::string::String, u64>,"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_as_custom_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("account.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["account.Account"], true, true, None)
+            .with_serde_as_custom(
+                "account.Account",
+                &[("limit", "crate::convert::HexU64")],
+                Some("Option"),
+            )
+            .compile_protos(&["fixtures/protos/account.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[serde_with::serde_as]"));
+        assert!(content.contains(
+            r#"    #[prost(uint64, optional, tag = "3")]
+    #[serde_as(as = "Option<crate::convert::HexU64>")]
+    pub limit: ::core::option::Option<u64>,"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_without_type_attributes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let builder = tonic_build::configure()
+            .build_client(false)
+            .build_server(false);
+        Generator::new(builder)
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo", "todo.TodoStatus"], true, true, None)
+            .with_field_attributes(&["todo.Todo.title"], &["#[serde(default)]"])
+            .without_type_attributes(&["todo.Todo"])
+            .with_strum(&["todo.Todo"], None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(strum::EnumString, strum::Display, strum::EnumIter)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        assert!(content.contains("#[serde(default)]\n    pub title:"));
+        assert_eq!(
+            content
+                .matches("#[derive(serde::Serialize, serde::Deserialize)]")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_ordered_attributes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let ordered = [
+            "#[serde_with::serde_as]",
+            "#[derive(serde::Serialize, serde::Deserialize)]",
+            "#[serde(rename_all = \"camelCase\")]",
+        ];
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_ordered_attributes(&["todo.Todo"], &ordered)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(&format!(
+            "{}\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {{",
+            ordered.join("\n")
+        )));
+    }
+
+    #[test]
+    fn test_tonic_build_with_ordered_attributes_through_generator_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let ordered = [
+            "#[derive(serde::Serialize, serde::Deserialize)]",
+            "#[serde(rename_all = \"camelCase\")]",
+        ];
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_type_attributes(&["todo.Todo"], &["#[derive(Eq)]"])
+            .with_ordered_attributes(&["todo.Todo"], &ordered)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(&format!(
+            "{}\n#[derive(Eq)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {{",
+            ordered.join("\n")
+        )));
+
+        let builder = Generator::new(tonic_build::configure())
+            .with_type_attributes(&["todo.Todo"], &["#[derive(Eq)]"])
+            .with_ordered_attributes(&["todo.Todo"], &ordered)
+            .into_inner();
+        builder
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(path.path().join("todo.rs")).unwrap();
+        assert!(content.contains(&format!("{}\n#[derive(Eq)]\n", ordered.join("\n"))));
+    }
+
+    #[test]
+    fn test_tonic_build_with_fake_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_fake(&["todo.Todo"], &[("todo.Todo.title", "Sentence(3..5)")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(fake::Dummy)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        assert!(content.contains(
+            "    #[prost(string, tag = \"2\")]\n    #[dummy(faker = \"Sentence(3..5)\")]\n    pub title:"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_clap_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_clap(
+                &["todo.CreateTodoRequest"],
+                Some(&["#[command(about = \"Create a todo\")]"]),
+            )
+            .with_clap_args(&[("todo.CreateTodoRequest.title", "long")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(clap::Parser)]
+#[command(about = "Create a todo")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateTodoRequest {
+    #[prost(string, tag = "1")]
+    #[arg(long)]
+    pub title: ::prost::alloc::string::String,"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_all_from_descriptor_should_work() {
+        // fixtures/descriptors/todo.bin is written by
+        // `protoc --include_imports -o fixtures/descriptors/todo.bin -I fixtures/protos todo.proto`
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde_all_from_descriptor("fixtures/descriptors/todo.bin", "todo", true, true)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for ty in [
+            "struct Todo",
+            "struct GetTodosRequest",
+            "struct CreateTodoRequest",
+            "struct DeleteTodoRequest",
+            "struct DeleteTodoResponse",
+            "enum TodoStatus",
+        ] {
+            let derive = "#[derive(serde::Serialize, serde::Deserialize)]\n";
+            assert!(content.contains(&format!("{}#[derive(", derive)), "{}", ty);
+            let item = content.find(&format!("pub {} {{", ty)).unwrap();
+            assert!(content[..item].rfind(derive).is_some(), "{}", ty);
+        }
+        assert_eq!(
+            content
+                .matches("#[derive(serde::Serialize, serde::Deserialize)]")
+                .count(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_diesel_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_diesel(&["todo.Todo"], Some("todos"), true, true, None)
+            .with_diesel(&["todo.CreateTodoRequest"], None, false, true, None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(diesel::Queryable, diesel::Insertable)]
+#[diesel(table_name = "todos")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Todo {"#
+        ));
+        assert!(content.contains(
+            r#"#[derive(diesel::Insertable)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateTodoRequest {"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_sea_orm_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_sea_orm("todo.Todo", "todos", &["id"], None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"#[derive(sea_orm::DeriveEntityModel)]
+#[sea_orm(table_name = "todos")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Todo {
+    #[prost(string, tag = "1")]
+    #[sea_orm(primary_key)]
+    pub id: ::prost::alloc::string::String,"#
+        ));
+        assert_eq!(content.matches("#[sea_orm(primary_key)]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_eq_hash_safe_should_work() {
+        let err = tonic_build::configure()
+            .with_eq_hash_safe(&["fixtures/protos/order.proto"], &["order.Order"])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BuilderError::FloatFieldPreventsEq {
+                type_name: "order.Order".to_string(),
+                field: "discount_rate".to_string(),
+            }
+        );
+
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_eq_hash_safe(&["fixtures/protos/todo.proto"], &["todo.Todo"])
+            .unwrap()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(Eq, Hash)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+
+        let filename = path.path().join("tree.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_eq_hash_safe(&["fixtures/protos/tree.proto"], &["tree.Node"])
+            .unwrap()
+            .compile_protos(&["fixtures/protos/tree.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for name in ["Node", "Leaf"] {
+            assert!(content.contains(&format!(
+                "#[derive(Eq, Hash)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct {} {{",
+                name
+            )));
+        }
+        assert_eq!(content.matches("#[derive(Eq, Hash)]").count(), 2);
+    }
+
+    #[test]
+    fn test_tonic_build_with_ord_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("measure.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_eq_hash_safe(&["fixtures/protos/measure.proto"], &["measure.Counter"])
+            .unwrap()
+            .with_ord(&["measure.Counter"])
+            .compile_protos(&["fixtures/protos/measure.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(Eq, Hash)]\n#[derive(PartialOrd, Ord)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Counter {"
+        ));
+        assert_eq!(content.matches("#[derive(PartialOrd, Ord)]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_comments_as_schema_desc_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("measure.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_comments_as_schema_desc(
+                &["fixtures/protos/measure.proto"],
+                &["measure.Counter", "measure.Reading"],
+            )
+            .compile_protos(&["fixtures/protos/measure.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content
+            .contains(r#"#[schemars(description = "A named counter.\nOnly ever goes up.")]"#));
+        assert!(content.contains(
+            r#"    #[prost(string, tag = "1")]
+    #[schemars(description = "the name it is reported under")]
+    pub name: ::prost::alloc::string::String,"#
+        ));
+        assert_eq!(content.matches("#[schemars(").count(), 2);
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_tag_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("event.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["event.Event.payload"], true, true, None)
+            .with_serde_tag(&["event.Event.payload"], "type")
+            .compile_protos(&["fixtures/protos/event.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "type")]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {"#
+        ));
+
+        let path = tempdir().unwrap();
+        let filename = path.path().join("event.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde_tag_content(&["event.Event.payload"], "type", "data")
+            .compile_protos(&["fixtures/protos/event.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[serde(tag = "type", content = "data")]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_untagged_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("event.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["event.Event.payload"], true, true, None)
+            .with_serde_untagged(&["event.Event.payload"])
+            .compile_protos(&["fixtures/protos/event.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(untagged)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_deny_unknown_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["todo.CreateTodoRequest"], false, true, None)
+            .with_serde_deny_unknown_fields(&["todo.CreateTodoRequest"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[derive(serde::Deserialize)]\n#[serde(deny_unknown_fields)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct CreateTodoRequest {"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_excluding_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde_excluding(
+                &[
+                    "todo.Todo",
+                    "todo.GetTodosRequest",
+                    "todo.CreateTodoRequest",
+                ],
+                &[".todo.Todo"],
+                true,
+                true,
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for name in ["GetTodosRequest", "CreateTodoRequest"] {
+            assert!(content.contains(&format!(
+                "#[derive(serde::Serialize, serde::Deserialize)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct {} {{",
+                name
+            )));
+        }
+        assert!(content.contains("// This file is @generated by prost-build.\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"));
+        assert_eq!(content.matches("serde::Serialize").count(), 2);
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_as_twice_should_add_the_type_attribute_once() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_serde_as(
+                "todo.Todo",
+                &[(&["id"], "#[serde_as(as = \"DisplayFromStr\")]")],
+            )
+            .with_serde_as(
+                "todo.Todo",
+                &[(&["title"], "#[serde_as(as = \"NoneAsEmptyString\")]")],
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert_eq!(content.matches("#[serde_with::serde_as]").count(), 1);
+        assert!(content.contains("#[serde_as(as = \"DisplayFromStr\")]\n    pub id:"));
+        assert!(content.contains("#[serde_as(as = \"NoneAsEmptyString\")]\n    pub title:"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_boxed_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("tree.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_boxed_fields(&["tree.Node.leaf"])
+            .compile_protos(&["fixtures/protos/tree.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(
+            content.contains("pub leaf: ::core::option::Option<::prost::alloc::boxed::Box<Leaf>>,")
+        );
+        // the recursive field is boxed by prost itself
+        assert!(content
+            .contains("pub parent: ::core::option::Option<::prost::alloc::boxed::Box<Node>>,"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_getters_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_getters(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"impl Todo {
+    /// The `id` field.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// The `title` field.
+    pub fn title(&self) -> &str {
+        &self.title
+    }"#
+        ));
+        assert!(content.contains(
+            r#"    pub fn created_at(&self) -> ::core::option::Option<&::prost_types::Timestamp> {
+        self.created_at.as_ref()
+    }"#
+        ));
+        // prost already generates `fn status(&self) -> TodoStatus`
+        assert!(!content.contains("pub fn status(&self) -> i32"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_into_inner_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("wrapper.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_into_inner(
+                &["fixtures/protos/wrapper.proto"],
+                &["wrapper.UserId", "wrapper.Tags"],
+            )
+            .unwrap()
+            .compile_protos(&["fixtures/protos/wrapper.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"impl UserId {
+    /// Unwrap the `value` field.
+    pub fn into_inner(self) -> ::prost::alloc::string::String {
+        self.value
+    }
+}
+impl ::core::convert::From<UserId> for ::prost::alloc::string::String {
+    fn from(value: UserId) -> Self {
+        value.value
+    }
+}"#
+        ));
+        assert!(content.contains(
+            "pub fn into_inner(self) -> ::prost::alloc::vec::Vec<::prost::alloc::string::String> {"
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_into_inner_should_reject_multi_field_messages() {
+        let err = Generator::new(tonic_build::configure())
+            .with_into_inner(&["fixtures/protos/todo.proto"], &["todo.Todo"])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            BuilderError::NotSingleField {
+                type_name: "todo.Todo".to_string(),
+                fields: 6,
             }
-            /// Generated gRPC service name
-            pub const SERVICE_NAME: &str = "todo.TodoService";
-            impl<T> tonic::server::NamedService for TodoServiceServer<T> {
-                const NAME: &'static str = SERVICE_NAME;
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_method_counters_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure().build_client(false))
+            .out_dir(path.path())
+            .with_method_counters("todo")
+            .with_instrument(&["todo.TodoService"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"                            let fut = async move {
+                                metrics::counter!("todo_requests_total", "service" => "todo.TodoService", "method" => "create_todo").increment(1);
+                                tracing::Instrument::instrument(<T as TodoService>::create_todo(&inner, request)"#
+        ));
+        assert_eq!(content.matches("metrics::counter!").count(), 4);
+    }
+
+    #[test]
+    fn test_tonic_build_with_method_path_consts_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_method_path_consts()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("pub mod todo_service_paths {"));
+        assert!(
+            content.contains(r#"pub const CREATE_TODO: &str = "/todo.TodoService/CreateTodo";"#)
+        );
+        assert_eq!(
+            content.matches(r#": &str = "/todo.TodoService/"#).count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_secret_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("account.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_secret_fields(
+                &["fixtures/protos/account.proto"],
+                &["account.Account.token"],
+            )
+            .unwrap()
+            .compile_protos(&["fixtures/protos/account.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // the message keeps its `String` and prost's derives, the copy can't derive `PartialEq`
+        assert!(
+            content.contains("#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Account {")
+        );
+        assert!(content.contains("#[derive(Debug)]\npub struct AccountSecret {"));
+        assert!(
+            content.contains("    pub token: ::secrecy::Secret<::prost::alloc::string::String>,\n")
+        );
+        assert!(content.contains("            token: ::secrecy::Secret::new(value.token),\n"));
+
+        let err = Generator::new(tonic_build::configure())
+            .with_secret_fields(
+                &["fixtures/protos/account.proto"],
+                &["account.Account.limit"],
+            )
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            BuilderError::NotStringField {
+                type_name: "account.Account".to_string(),
+                field: "limit".to_string(),
             }
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_instrument_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure().build_client(false))
+            .out_dir(path.path())
+            .with_instrument(&["todo.TodoService"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"tracing::Instrument::instrument(<T as TodoService>::create_todo(&inner, request), tracing::info_span!("create_todo", service = "todo.TodoService")).await"#
+        ));
+        assert_eq!(content.matches("tracing::info_span!").count(), 4);
+
+        let err = Generator::new(tonic_build::configure().build_server(false))
+            .out_dir(path.path())
+            .with_instrument(&["todo.TodoService"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "server of `todo.TodoService` not found in the generated code, is `build_server` off?"
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_allow_missing_docs_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .allow_missing_docs()
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.starts_with(
+            "// This file is @generated by prost-build.\n#[allow(missing_docs)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        // 5 messages, the enum and its impl, and the client and server modules
+        assert_eq!(content.matches("#[allow(missing_docs)]").count(), 9);
+        assert!(content.contains("#[allow(missing_docs)]\n/// Generated client implementations.\npub mod todo_service_client {"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_flatten_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("tree.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["tree.Node", "tree.Leaf"], true, true, None)
+            .with_serde_flatten(&["tree.Node.leaf"])
+            .compile_protos(&["fixtures/protos/tree.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(message, optional, tag = "4")]
+    #[serde(flatten)]
+    pub leaf: ::core::option::Option<Leaf>,"#
+        ));
+        assert_eq!(content.matches("#[serde(flatten)]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_enum_default_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_enum_default("todo.TodoStatus", "Done")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        // prost's `Enumeration` derive makes the first variant the default
+        assert!(content.contains(
+            r#"pub enum TodoStatus {
+    Done = 1,
+    Doing = 0,
+}"#
+        ));
+
+        let err = Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_enum_default("todo.TodoStatus", "Blocked")
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`Blocked` is not a value of `todo.TodoStatus`"
+        );
+    }
+
+    #[test]
+    fn test_tonic_build_with_bytes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("tree.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_bytes(&[".tree.Leaf"])
+            .compile_protos(&["fixtures/protos/tree.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("pub payload: ::prost::bytes::Bytes,"));
+    }
+
+    #[test]
+    fn test_tonic_build_with_btree_maps_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("metrics.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_btree_maps(&["metrics.Sample.labels"])
+            .compile_protos(&["fixtures/protos/metrics.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "pub labels: ::prost::alloc::collections::BTreeMap<\n        ::prost::alloc::string::String,\n        ::prost::alloc::string::String,\n    >,"
+        ));
+        assert!(content.contains(
+            "pub counters: ::std::collections::HashMap<::prost::alloc::string::String, u64>,"
+        ));
+
+        // a generator sets the paths of every call together
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_btree_maps(&["metrics.Sample.labels"])
+            .with_btree_maps(&["metrics.Sample.counters"])
+            .compile_protos(&["fixtures/protos/metrics.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(path.path().join("metrics.rs")).unwrap();
+        assert_eq!(content.matches("BTreeMap<").count(), 2);
+
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_serde(&["metrics.Sample"], true, true, None)
+            .with_btree_maps(&["metrics.Sample.counters"])
+            .with_serde_as_map(
+                "metrics.Sample",
+                &[(&["counters", "labels"], "serde_with::DisplayFromStr")],
+            )
+            .compile_protos(&["fixtures/protos/metrics.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(path.path().join("metrics.rs")).unwrap();
+        assert!(content.contains(
+            r#"    #[serde_as(as = "::std::collections::BTreeMap<_, serde_with::DisplayFromStr>")]
+    pub counters: ::prost::alloc::collections::BTreeMap<"#
+        ));
+        assert!(content.contains(
+            r#"    #[serde_as(as = "::std::collections::HashMap<_, serde_with::DisplayFromStr>")]
+    pub labels: ::std::collections::HashMap<"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_field_serde_with_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["todo.Todo"], true, true, None)
+            .with_field_serde_with(&[("todo.Todo.status", "my_mod")])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[prost(enumeration = "TodoStatus", tag = "4")]
+    #[serde(with = "my_mod")]
+    pub status: i32,"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_derive_eq_where_possible_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("measure.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .derive_eq_where_possible(&["fixtures/protos/measure.proto"])
+            .compile_protos(&["fixtures/protos/measure.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(Eq, Hash)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Counter {"
+        ));
+        // `Reading` holds a double, and `Series` holds `Reading`s
+        assert_eq!(content.matches("#[derive(Eq, Hash)]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_message_and_enum_attributes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_message_attributes(&[".todo"], &["#[derive(derive_builder::Builder)]"])
+            .with_enum_attributes(&[".todo"], &["#[derive(strum::Display)]"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(derive_builder::Builder)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Todo {"
+        ));
+        assert!(content.contains(
+            "#[derive(strum::Display)]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]\n#[repr(i32)]\npub enum TodoStatus {"
+        ));
+        assert_eq!(
+            content
+                .matches("#[derive(derive_builder::Builder)]")
+                .count(),
+            5
+        );
+        assert_eq!(content.matches("#[derive(strum::Display)]").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_enum_rename_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["todo.TodoStatus"], true, true, None)
+            .with_serde_enum_rename(&[".todo"], RenameAll::ScreamingSnakeCase)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(serde::Serialize, serde::Deserialize)]\n#[serde(rename_all = \"SCREAMING_SNAKE_CASE\")]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]\n#[repr(i32)]\npub enum TodoStatus {"
+        ));
+        // the messages of the package are left alone
+        assert_eq!(content.matches("rename_all").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_serde_rename_all_fields_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("event.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["event.Event.payload"], true, true, None)
+            .with_serde_rename_all_fields(&["event.Event.payload"], RenameAll::CamelCase)
+            .compile_protos(&["fixtures/protos/event.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "    #[serde(rename_all_fields = \"camelCase\")]\n    #[derive(Clone, PartialEq, ::prost::Oneof)]\n    pub enum Payload {"
+        ));
+        assert_eq!(content.matches("rename_all_fields").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_api_preset_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_api_preset(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        let todo = content
+            .split("#[derive(Clone, PartialEq, ::prost::Message)]\npub struct GetTodosRequest")
+            .next()
+            .unwrap();
+        insta::assert_snapshot!(todo, @r###"
+        // This file is @generated by prost-build.
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #[serde_with::serde_as]
+        #[serde_with::skip_serializing_none]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Todo {
+            #[prost(string, tag = "1")]
+            pub id: ::prost::alloc::string::String,
+            #[prost(string, tag = "2")]
+            pub title: ::prost::alloc::string::String,
+            #[prost(string, tag = "3")]
+            pub description: ::prost::alloc::string::String,
+            #[prost(enumeration = "TodoStatus", tag = "4")]
+            pub status: i32,
+            #[prost(message, optional, tag = "5")]
+            pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+            #[prost(message, optional, tag = "6")]
+            pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_tonic_build_with_db_preset_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_db_preset(&["todo.Todo"])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        let todo = content
+            .split("#[derive(Clone, PartialEq, ::prost::Message)]\npub struct GetTodosRequest")
+            .next()
+            .unwrap();
+        insta::assert_snapshot!(todo, @r###"
+        // This file is @generated by prost-build.
+        #[derive(sqlx::FromRow)]
+        #[sqlx(rename_all = "snake_case")]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct Todo {
+            #[prost(string, tag = "1")]
+            pub id: ::prost::alloc::string::String,
+            #[prost(string, tag = "2")]
+            pub title: ::prost::alloc::string::String,
+            #[prost(string, tag = "3")]
+            pub description: ::prost::alloc::string::String,
+            #[prost(enumeration = "TodoStatus", tag = "4")]
+            pub status: i32,
+            #[prost(message, optional, tag = "5")]
+            pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+            #[prost(message, optional, tag = "6")]
+            pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
         }
         "###);
     }
+
+    #[test]
+    fn test_tonic_build_with_profile_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        let profile = Profile::new()
+            .serde(true, true)
+            .sqlx_type()
+            .attribute("#[derive(Eq)]");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_profile(
+                &["todo.CreateTodoRequest", "todo.DeleteTodoRequest"],
+                &profile,
+            )
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        for name in ["CreateTodoRequest", "DeleteTodoRequest"] {
+            assert!(content.contains(&format!(
+                "#[derive(serde::Serialize, serde::Deserialize)]\n#[derive(sqlx::Type)]\n#[derive(Eq)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct {} {{",
+                name
+            )));
+        }
+        assert_eq!(content.matches("#[derive(sqlx::Type)]").count(), 2);
+    }
+
+    #[test]
+    fn test_tonic_build_with_i64_as_string_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("account.rs");
+        Generator::new(tonic_build::configure())
+            .out_dir(path.path())
+            .with_i64_as_string(&["fixtures/protos/account.proto"], &["account.Account"])
+            .with_serde_as("account.Account", &[])
+            .compile_protos(&["fixtures/protos/account.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert_eq!(content.matches("#[serde_with::serde_as]").count(), 1);
+        for field in [
+            r#"#[serde_as(as = "serde_with::DisplayFromStr")]
+    pub balance: i64,"#,
+            r#"#[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+    pub limit: ::core::option::Option<u64>,"#,
+            r#"#[serde_as(as = "Vec<serde_with::DisplayFromStr>")]
+    pub deltas: ::prost::alloc::vec::Vec<i64>,"#,
+            r#"#[serde_as(as = "::std::collections::HashMap<_, serde_with::DisplayFromStr>")]
+    pub totals: ::std::collections::HashMap<::prost::alloc::string::String, u64>,"#,
+        ] {
+            assert!(content.contains(field), "{}", field);
+        }
+        assert_eq!(content.matches("#[serde_as(").count(), 4);
+    }
+
+    #[test]
+    fn test_tonic_build_with_base64_bytes_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("peer.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_serde(&["peer.Peer"], true, true, None)
+            .with_base64_bytes(&["fixtures/protos/peer.proto"], &["peer.Peer"])
+            .compile_protos(&["fixtures/protos/peer.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains("#[serde_with::serde_as]\n#[serde_with::skip_serializing_none]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct Peer {"));
+        assert!(content.contains(
+            r#"    #[serde_as(as = "serde_with::base64::Base64")]
+    pub raw_address: ::prost::alloc::vec::Vec<u8>,"#
+        ));
+        assert_eq!(content.matches("#[serde_as(").count(), 1);
+    }
+
+    #[test]
+    fn test_tonic_build_with_builder_field_default_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_derive_builder(&["todo.CreateTodoRequest"], None)
+            .with_builder_field_default(&[(
+                "todo.CreateTodoRequest.description",
+                "\"draft\".into()",
+            )])
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            r#"    #[builder(default = "\"draft\".into()")]
+    pub description: ::prost::alloc::string::String,"#
+        ));
+    }
+
+    #[test]
+    fn test_tonic_build_with_zeroize_should_work() {
+        let path = tempdir().unwrap();
+        let filename = path.path().join("todo.rs");
+        tonic_build::configure()
+            .build_client(false)
+            .build_server(false)
+            .out_dir(path.path())
+            .with_zeroize(&["todo.CreateTodoRequest"], true, None)
+            .compile_protos(&["fixtures/protos/todo.proto"], &["fixtures/protos"])
+            .unwrap();
+        let content = fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "#[derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop)]\n#[derive(Clone, PartialEq, ::prost::Message)]\npub struct CreateTodoRequest {"
+        ));
+    }
 }
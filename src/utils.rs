@@ -1,3 +1,183 @@
+//! The attribute strings used by the builder helpers.
+//!
+//! Every function returns one or more complete attributes separated by newlines (a `&'static str`
+//! unless the attribute depends on arguments), which can be passed as is to `type_attribute` /
+//! `with_type_attributes`, or tweaked to build a variant of your own:
+//!
+//! ```
+//! # #[cfg(feature = "prost")]
+//! # {
+//! use prost_build::Config;
+//! use proto_builder_trait::{prost::BuilderAttributes, utils::derive_builder_attr};
+//!
+//! let attr = derive_builder_attr().replace("default", r#"default, pattern = "owned""#);
+//! Config::new().with_type_attributes(&["todo.Todo"], &[attr.as_str()]);
+//! # }
+//! ```
+
+use crate::{
+    generator::Attribute,
+    parser::{is_scalar, Label, Protos, Resolved, TypeKind},
+};
+use prost::Message;
+use prost_types::{DescriptorProto, FileDescriptorSet};
+use std::{fmt, fs, io};
+
+/// the case conventions of serde's `rename_all`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameAll {
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameAll {
+    /// the name serde uses for the convention, e.g. `SCREAMING_SNAKE_CASE`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenameAll::LowerCase => "lowercase",
+            RenameAll::UpperCase => "UPPERCASE",
+            RenameAll::PascalCase => "PascalCase",
+            RenameAll::CamelCase => "camelCase",
+            RenameAll::SnakeCase => "snake_case",
+            RenameAll::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+            RenameAll::KebabCase => "kebab-case",
+            RenameAll::ScreamingKebabCase => "SCREAMING-KEBAB-CASE",
+        }
+    }
+}
+
+/// why a builder helper refused to add its attributes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    /// the path isn't a message of the parsed protos
+    UnknownMessage(String),
+    /// `f32`/`f64` don't implement `Eq` or `Hash`, so neither can the message holding them
+    FloatFieldPreventsEq { type_name: String, field: String },
+    /// the message must have exactly one field
+    NotSingleField { type_name: String, fields: usize },
+    /// the message has no field of that name
+    UnknownField { type_name: String, field: String },
+    /// the field must be a singular `string`, not a repeated, map, oneof or non-string field
+    NotStringField { type_name: String, field: String },
+    /// the pattern isn't a valid regex
+    InvalidPattern { pattern: String, error: String },
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderError::UnknownMessage(path) => write!(f, "message `{}` not found", path),
+            BuilderError::FloatFieldPreventsEq { type_name, field } => write!(
+                f,
+                "field `{}` of `{}` is a float, which can't derive `Eq` or `Hash`",
+                field, type_name
+            ),
+            BuilderError::NotSingleField { type_name, fields } => write!(
+                f,
+                "message `{}` has {} fields, expected exactly one",
+                type_name, fields
+            ),
+            BuilderError::UnknownField { type_name, field } => {
+                write!(f, "field `{}` not found in `{}`", field, type_name)
+            }
+            BuilderError::NotStringField { type_name, field } => write!(
+                f,
+                "field `{}` of `{}` is not a singular string",
+                field, type_name
+            ),
+            BuilderError::InvalidPattern { pattern, error } => {
+                write!(f, "invalid pattern `{}`: {}", pattern, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// A named stack of type attributes, defined once and applied to any number of types with
+/// `with_profile`:
+///
+/// ```
+/// # #[cfg(feature = "tonic")]
+/// # {
+/// use proto_builder_trait::{tonic::BuilderAttributes, utils::Profile};
+///
+/// let api = Profile::new().serde(true, true).serde_as().strum();
+/// let _builder = tonic_build::configure().with_profile(&["todo.Todo", "todo.TodoStatus"], &api);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    attributes: Vec<String>,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// [`serde_attr`]
+    pub fn serde(self, ser: bool, de: bool) -> Self {
+        self.attribute(serde_attr(ser, de))
+    }
+
+    /// [`serde_as_attr`]
+    pub fn serde_as(self) -> Self {
+        self.attribute(serde_as_attr())
+    }
+
+    /// [`serde_rename_all_attr`]
+    pub fn serde_rename_all(self, rename: RenameAll) -> Self {
+        self.attribute(&serde_rename_all_attr(rename))
+    }
+
+    /// [`sqlx_type_attr`]
+    pub fn sqlx_type(self) -> Self {
+        self.attribute(sqlx_type_attr())
+    }
+
+    /// [`sqlx_from_row_attr`]
+    pub fn sqlx_from_row(self) -> Self {
+        self.attribute(sqlx_from_row_attr())
+    }
+
+    /// [`strum_attr`]
+    pub fn strum(self) -> Self {
+        self.attribute(strum_attr())
+    }
+
+    /// [`derive_builder_attr`]
+    pub fn derive_builder(self) -> Self {
+        self.attribute(derive_builder_attr())
+    }
+
+    /// [`eq_hash_attr`]
+    pub fn eq_hash(self) -> Self {
+        self.attribute(eq_hash_attr())
+    }
+
+    /// any other attribute, e.g. `#[derive(Copy)]`. Empty attributes are skipped.
+    pub fn attribute(mut self, attr: &str) -> Self {
+        if !attr.is_empty() {
+            self.attributes.push(attr.to_string());
+        }
+        self
+    }
+
+    /// the attributes in the order they were added
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+}
+
+/// `#[derive(serde::Serialize, serde::Deserialize)]`, or only one of them. Empty if neither
+/// `ser` nor `de` is set.
 pub fn serde_attr(ser: bool, de: bool) -> &'static str {
     match (ser, de) {
         (true, true) => "#[derive(serde::Serialize, serde::Deserialize)]",
@@ -7,18 +187,909 @@ pub fn serde_attr(ser: bool, de: bool) -> &'static str {
     }
 }
 
+/// `#[serde_with::serde_as]` followed by `#[serde_with::skip_serializing_none]`
 pub fn serde_as_attr() -> &'static str {
     "#[serde_with::serde_as]\n#[serde_with::skip_serializing_none]"
 }
 
+/// `#[serde(rename_all = "...")]`
+pub fn serde_rename_all_attr(rename: RenameAll) -> String {
+    format!("#[serde(rename_all = \"{}\")]", rename.as_str())
+}
+
+/// `#[serde(rename_all_fields = "...")]`, for the fields of the struct variants of enums
+pub fn serde_rename_all_fields_attr(rename: RenameAll) -> String {
+    format!("#[serde(rename_all_fields = \"{}\")]", rename.as_str())
+}
+
+/// `#[serde(tag = "...")]`, for internally tagged enums
+pub fn serde_tag_attr(tag: &str) -> String {
+    format!("#[serde(tag = {:?})]", tag)
+}
+
+/// `#[serde(tag = "...", content = "...")]`, for adjacently tagged enums
+pub fn serde_tag_content_attr(tag: &str, content: &str) -> String {
+    format!("#[serde(tag = {:?}, content = {:?})]", tag, content)
+}
+
+/// `#[serde(untagged)]`
+pub fn serde_untagged_attr() -> &'static str {
+    "#[serde(untagged)]"
+}
+
+/// `#[serde(deny_unknown_fields)]`
+pub fn serde_deny_unknown_fields_attr() -> &'static str {
+    "#[serde(deny_unknown_fields)]"
+}
+
+/// `#[serde(flatten)]`
+pub fn serde_flatten_attr() -> &'static str {
+    "#[serde(flatten)]"
+}
+
+/// `#[serde(with = "...")]`
+pub fn serde_with_attr(module: &str) -> String {
+    format!("#[serde(with = {:?})]", module)
+}
+
+/// `#[derive(sqlx::Type)]`
 pub fn sqlx_type_attr() -> &'static str {
     "#[derive(sqlx::Type)]"
 }
 
+/// `#[derive(sqlx::FromRow)]`
 pub fn sqlx_from_row_attr() -> &'static str {
     "#[derive(sqlx::FromRow)]"
 }
 
+/// [`sqlx_from_row_attr`], followed by `#[sqlx(rename_all = "...")]` if `rename_all` is set
+pub fn sqlx_from_row_opts_attr(rename_all: Option<&str>) -> String {
+    match rename_all {
+        Some(rename_all) => format!(
+            "{}\n#[sqlx(rename_all = \"{}\")]",
+            sqlx_from_row_attr(),
+            rename_all
+        ),
+        None => sqlx_from_row_attr().to_string(),
+    }
+}
+
+/// `#[derive(strum::EnumString, strum::Display, strum::EnumIter)]`
+pub fn strum_attr() -> &'static str {
+    "#[derive(strum::EnumString, strum::Display, strum::EnumIter)]"
+}
+
+/// `#[derive(arbitrary::Arbitrary)]`
+pub fn arbitrary_attr() -> &'static str {
+    "#[derive(arbitrary::Arbitrary)]"
+}
+
+/// `#[derive(proptest_derive::Arbitrary)]`
+pub fn proptest_attr() -> &'static str {
+    "#[derive(proptest_derive::Arbitrary)]"
+}
+
+/// `#[derive(derivative::Derivative)]` followed by `#[derivative(Debug)]`
+pub fn derivative_debug_attr() -> &'static str {
+    "#[derive(derivative::Derivative)]\n#[derivative(Debug)]"
+}
+
+/// `#[non_exhaustive]`
+pub fn non_exhaustive_attr() -> &'static str {
+    "#[non_exhaustive]"
+}
+
+/// `#[deprecated]`, or `#[deprecated(note = "...")]` if `note` is set
+pub fn deprecated_attr(note: Option<&str>) -> String {
+    match note {
+        Some(note) => format!("#[deprecated(note = {:?})]", note),
+        None => "#[deprecated]".to_string(),
+    }
+}
+
+/// `#[must_use]`, or `#[must_use = "..."]` if `note` is set
+pub fn must_use_attr(note: Option<&str>) -> String {
+    match note {
+        Some(note) => format!("#[must_use = {:?}]", note),
+        None => "#[must_use]".to_string(),
+    }
+}
+
+/// `#[derive(...)]` for the given traits, e.g. `clap::Parser`
+pub fn derive_attr(derives: &[&str]) -> String {
+    format!("#[derive({})]", derives.join(", "))
+}
+
+/// `#[derive(derive_more::From, derive_more::Into, ...)]` for the given traits. Names that are
+/// already qualified (e.g. `derive_more::Display`) are kept as is.
+pub fn derive_more_attr(traits: &[&str]) -> String {
+    let traits: Vec<String> = traits
+        .iter()
+        .map(|t| {
+            if t.contains("::") {
+                t.to_string()
+            } else {
+                format!("derive_more::{}", t)
+            }
+        })
+        .collect();
+    format!("#[derive({})]", traits.join(", "))
+}
+
+/// `#[derive(async_graphql::SimpleObject)]`
+pub fn async_graphql_simple_object_attr() -> &'static str {
+    "#[derive(async_graphql::SimpleObject)]"
+}
+
+/// `#[derive(async_graphql::Enum)]`
+pub fn async_graphql_enum_attr() -> &'static str {
+    "#[derive(async_graphql::Enum)]"
+}
+
+/// `#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]`
+pub fn rkyv_attr() -> &'static str {
+    "#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]"
+}
+
+/// `#[derive(fake::Dummy)]`
+pub fn fake_attr() -> &'static str {
+    "#[derive(fake::Dummy)]"
+}
+
+/// `#[dummy(faker = "...")]`
+pub fn fake_faker_attr(faker: &str) -> String {
+    format!("#[dummy(faker = {:?})]", faker)
+}
+
+/// `#[derive(clap::Parser)]`
+pub fn clap_parser_attr() -> &'static str {
+    "#[derive(clap::Parser)]"
+}
+
+/// `#[arg(...)]` with the given arguments, e.g. `long`
+pub fn clap_arg_attr(args: &str) -> String {
+    format!("#[arg({})]", args)
+}
+
+/// `#[derive(diesel::Queryable, diesel::Insertable)]` for the selected derives, followed by
+/// `#[diesel(table_name = "...")]` if `table_name` is set
+pub fn diesel_attr(table_name: Option<&str>, queryable: bool, insertable: bool) -> String {
+    let derives: Vec<&str> = [
+        (queryable, "diesel::Queryable"),
+        (insertable, "diesel::Insertable"),
+    ]
+    .iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, derive)| *derive)
+    .collect();
+    let derive = (!derives.is_empty()).then(|| format!("#[derive({})]", derives.join(", ")));
+    let table = table_name.map(|t| format!("#[diesel(table_name = {:?})]", t));
+    derive
+        .into_iter()
+        .chain(table)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `#[derive(sea_orm::DeriveEntityModel)]` followed by `#[sea_orm(table_name = "...")]`
+pub fn sea_orm_model_attr(table_name: &str) -> String {
+    format!(
+        "#[derive(sea_orm::DeriveEntityModel)]\n#[sea_orm(table_name = {:?})]",
+        table_name
+    )
+}
+
+/// `#[sea_orm(primary_key)]`
+pub fn sea_orm_primary_key_attr() -> &'static str {
+    "#[sea_orm(primary_key)]"
+}
+
+/// `#[derive(Eq, Hash)]`
+pub fn eq_hash_attr() -> &'static str {
+    "#[derive(Eq, Hash)]"
+}
+
+/// `#[derive(PartialOrd, Ord)]`
+pub fn ord_attr() -> &'static str {
+    "#[derive(PartialOrd, Ord)]"
+}
+
+/// `#[derive(zeroize::Zeroize)]`, with `zeroize::ZeroizeOnDrop` too if `on_drop`
+pub fn zeroize_attr(on_drop: bool) -> &'static str {
+    if on_drop {
+        "#[derive(zeroize::Zeroize, zeroize::ZeroizeOnDrop)]"
+    } else {
+        "#[derive(zeroize::Zeroize)]"
+    }
+}
+
+/// `#[derive(derive_builder::Builder)]` followed by
+/// `#[builder(setter(into, strip_option), default)]`
 pub fn derive_builder_attr() -> &'static str {
     "#[derive(derive_builder::Builder)]\n#[builder(setter(into, strip_option), default)]"
 }
+
+/// `#[builder(default = "...")]` with the given expression
+pub fn builder_default_attr(expr: &str) -> String {
+    format!("#[builder(default = {:?})]", expr)
+}
+
+// The attribute lists behind the `BuilderAttributes` helpers. Both the prost and the tonic impls
+// build their attributes here and only differ in how they apply them to their builder, so the
+// two can't drift apart.
+
+/// [`serde_attr`] on every path that isn't in `exclude`
+pub(crate) fn serde_excluding_attrs(
+    paths: &[&str],
+    exclude: &[&str],
+    ser: bool,
+    de: bool,
+) -> Vec<Attribute> {
+    let excluded = |path: &str| {
+        exclude
+            .iter()
+            .any(|e| e.trim_start_matches('.') == path.trim_start_matches('.'))
+    };
+    let paths: Vec<&str> = paths.iter().copied().filter(|p| !excluded(p)).collect();
+    type_attrs(&paths, serde_attr(ser, de), None)
+}
+
+/// `attr` on every path, each followed by `extra_attrs` if any
+pub(crate) fn type_attrs(
+    paths: &[&str],
+    attr: &str,
+    extra_attrs: Option<&[&str]>,
+) -> Vec<Attribute> {
+    paths
+        .iter()
+        .flat_map(|ty| {
+            let extra = extra_attrs.map(|attrs| Attribute::Type(ty.to_string(), attrs.join("\n")));
+            std::iter::once(Attribute::Type(ty.to_string(), attr.to_string())).chain(extra)
+        })
+        .collect()
+}
+
+/// [`fake_attr`] on every path, and [`fake_faker_attr`] on the fields (full paths, e.g.
+/// `todo.Todo.title`) paired with their faker
+pub(crate) fn fake_attrs(paths: &[&str], field_fakers: &[(&str, &str)]) -> Vec<Attribute> {
+    let fields = field_fakers
+        .iter()
+        .map(|(field, faker)| Attribute::Field(field.to_string(), fake_faker_attr(faker)));
+    type_attrs(paths, fake_attr(), None)
+        .into_iter()
+        .chain(fields)
+        .collect()
+}
+
+/// [`clap_arg_attr`] on each field (full path, e.g. `todo.CreateTodoRequest.title`)
+pub(crate) fn clap_args_attrs(fields: &[(&str, &str)]) -> Vec<Attribute> {
+    fields
+        .iter()
+        .map(|(field, args)| Attribute::Field(field.to_string(), clap_arg_attr(args)))
+        .collect()
+}
+
+/// [`sea_orm_model_attr`] on the path, and [`sea_orm_primary_key_attr`] on its key fields
+pub(crate) fn sea_orm_attrs(
+    path: &str,
+    table_name: &str,
+    primary_keys: &[&str],
+    extra_attrs: Option<&[&str]>,
+) -> Vec<Attribute> {
+    let keys = primary_keys.iter().map(|key| {
+        Attribute::Field(
+            format!("{}.{}", path, key),
+            sea_orm_primary_key_attr().to_string(),
+        )
+    });
+    type_attrs(&[path], &sea_orm_model_attr(table_name), extra_attrs)
+        .into_iter()
+        .chain(keys)
+        .collect()
+}
+
+/// check that each message has exactly one field. Panics if the protos can't be parsed.
+pub(crate) fn single_field_messages(
+    proto_files: &[&str],
+    paths: &[&str],
+) -> Result<(), BuilderError> {
+    let protos = load_protos(proto_files);
+    for path in paths {
+        let path = path.trim_start_matches('.');
+        let msg = protos
+            .message(path)
+            .ok_or_else(|| BuilderError::UnknownMessage(path.to_string()))?;
+        if msg.fields.len() != 1 {
+            return Err(BuilderError::NotSingleField {
+                type_name: path.to_string(),
+                fields: msg.fields.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// each attribute on the field of the message with the given tag number. Panics if the protos
+/// can't be parsed or a message has no field with the tag.
+pub(crate) fn field_attributes_by_tag_attrs(
+    proto_files: &[&str],
+    targets: &[(&str, u32, &str)],
+) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    targets
+        .iter()
+        .map(|(path, tag, attr)| {
+            let path = path.trim_start_matches('.');
+            let msg = protos
+                .message(path)
+                .unwrap_or_else(|| panic!("message `{}` not found in {:?}", path, proto_files));
+            let f = msg
+                .fields
+                .iter()
+                .find(|f| f.number == *tag)
+                .unwrap_or_else(|| panic!("no field with tag {} in `{}`", tag, path));
+            Attribute::Field(format!("{}.{}", path, f.name), attr.to_string())
+        })
+        .collect()
+}
+
+/// check that every field (e.g. `todo.Todo.title`) is a singular `string`: not `optional`,
+/// `repeated` or part of a oneof. Panics if the protos can't be parsed.
+pub(crate) fn string_fields(proto_files: &[&str], fields: &[&str]) -> Result<(), BuilderError> {
+    let protos = load_protos(proto_files);
+    for path in fields {
+        let path = path.trim_start_matches('.');
+        let (type_name, name) = path.rsplit_once('.').unwrap_or(("", path));
+        let msg = protos
+            .message(type_name)
+            .ok_or_else(|| BuilderError::UnknownMessage(type_name.to_string()))?;
+        let f = msg.fields.iter().find(|f| f.name == name).ok_or_else(|| {
+            BuilderError::UnknownField {
+                type_name: type_name.to_string(),
+                field: name.to_string(),
+            }
+        })?;
+        let singular = matches!(f.label, Label::None | Label::Required);
+        if f.ty != "string" || f.key.is_some() || f.oneof.is_some() || !singular {
+            return Err(BuilderError::NotStringField {
+                type_name: type_name.to_string(),
+                field: name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// [`eq_hash_attr`] on the messages and the messages of their fields, unless one of them holds a
+/// `float`/`double` field. Types from outside `proto_files` aren't checked.
+/// Panics if the protos can't be parsed.
+pub(crate) fn eq_hash_safe_attrs(
+    proto_files: &[&str],
+    paths: &[&str],
+) -> Result<Vec<Attribute>, BuilderError> {
+    let protos = load_protos(proto_files);
+    // the messages of the fields are checked along the way and need the derives too
+    let mut checked = Vec::new();
+    for path in paths {
+        let path = path.trim_start_matches('.');
+        if let Some((type_name, field, _)) = eq_hash_blocker(&protos, path, false, &mut checked)? {
+            return Err(BuilderError::FloatFieldPreventsEq { type_name, field });
+        }
+    }
+    let paths: Vec<&str> = checked.iter().map(|p| p.as_str()).collect();
+    Ok(eq_hash_attrs(&protos, &paths))
+}
+
+/// [`eq_hash_attr`] on every message of the protos that can derive them, printing a
+/// `cargo:warning` for the others. Panics if the protos can't be parsed.
+pub(crate) fn eq_where_possible_attrs(proto_files: &[&str]) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    let mut paths = Vec::new();
+    for (path, _) in protos.messages() {
+        let blocker = eq_hash_blocker(&protos, &path, true, &mut Vec::new())
+            .expect("the messages come from the parsed protos");
+        match blocker {
+            None => paths.push(path),
+            Some((type_name, field, blocker)) => {
+                let reason = match blocker {
+                    EqBlocker::Float => "is a float".to_string(),
+                    EqBlocker::Map => "is a map".to_string(),
+                    EqBlocker::External(ty) => format!("has the type `{}` from another file", ty),
+                };
+                println!(
+                    "cargo:warning=not deriving `Eq`/`Hash` on `{}`: field `{}` of `{}` {}",
+                    path, field, type_name, reason
+                );
+            }
+        }
+    }
+    let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+    eq_hash_attrs(&protos, &paths)
+}
+
+/// why a field keeps its message from deriving `Eq` and `Hash`
+enum EqBlocker {
+    Float,
+    Map,
+    External(String),
+}
+
+/// the first field (as message path and field name) keeping the message from deriving `Eq` and
+/// `Hash`, directly or through the messages of its fields. Unless `strict`, only float fields
+/// count: maps and types from other files (except `Timestamp`) are let through.
+fn eq_hash_blocker(
+    protos: &Protos,
+    path: &str,
+    strict: bool,
+    seen: &mut Vec<String>,
+) -> Result<Option<(String, String, EqBlocker)>, BuilderError> {
+    if seen.iter().any(|p| p == path) {
+        return Ok(None);
+    }
+    seen.push(path.to_string());
+    let msg = protos
+        .message(path)
+        .ok_or_else(|| BuilderError::UnknownMessage(path.to_string()))?;
+    for f in &msg.fields {
+        let blocker = match protos.resolve(path, &f.ty) {
+            Resolved::Scalar(ty) if ty == "float" || ty == "double" => Some(EqBlocker::Float),
+            _ if strict && f.key.is_some() => Some(EqBlocker::Map),
+            Resolved::External(ty)
+                if strict && ty.trim_start_matches('.') != "google.protobuf.Timestamp" =>
+            {
+                Some(EqBlocker::External(ty))
+            }
+            Resolved::Message(name) => {
+                if let Some(found) = eq_hash_blocker(protos, &name, strict, seen)? {
+                    return Ok(Some(found));
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some(blocker) = blocker {
+            return Ok(Some((path.to_string(), f.name.clone(), blocker)));
+        }
+    }
+    Ok(None)
+}
+
+/// [`eq_hash_attr`] on the messages and their oneofs, which are enums of their own
+fn eq_hash_attrs(protos: &Protos, paths: &[&str]) -> Vec<Attribute> {
+    let oneofs: Vec<String> = paths
+        .iter()
+        .filter_map(|path| protos.message(path).map(|m| (path, m)))
+        .flat_map(|(path, m)| m.oneofs.iter().map(move |o| format!("{}.{}", path, o)))
+        .collect();
+    let oneofs: Vec<&str> = oneofs.iter().map(|p| p.as_str()).collect();
+    type_attrs(&[paths, &oneofs].concat(), eq_hash_attr(), None)
+}
+
+/// box each field (full path, e.g. `tree.Node.leaf`)
+pub(crate) fn boxed_attrs(fields: &[&str]) -> Vec<Attribute> {
+    fields
+        .iter()
+        .map(|field| Attribute::Boxed(field.to_string()))
+        .collect()
+}
+
+/// use `bytes::Bytes` for the `bytes` fields matching each path
+pub(crate) fn bytes_attrs(paths: &[&str]) -> Vec<Attribute> {
+    paths
+        .iter()
+        .map(|path| Attribute::Bytes(path.to_string()))
+        .collect()
+}
+
+/// use `BTreeMap` for the map fields matching each path
+pub(crate) fn btree_map_attrs(paths: &[&str]) -> Vec<Attribute> {
+    paths
+        .iter()
+        .map(|path| Attribute::BTreeMap(path.to_string()))
+        .collect()
+}
+
+/// [`serde_with_attr`] on each field (full path, e.g. `todo.Todo.status`) with its module
+pub(crate) fn serde_with_attrs(fields: &[(&str, &str)]) -> Vec<Attribute> {
+    fields
+        .iter()
+        .map(|(field, module)| Attribute::Field(field.to_string(), serde_with_attr(module)))
+        .collect()
+}
+
+/// [`builder_default_attr`] on each field (full path, e.g. `todo.CreateTodoRequest.title`)
+pub(crate) fn builder_defaults_attrs(fields: &[(&str, &str)]) -> Vec<Attribute> {
+    fields
+        .iter()
+        .map(|(field, expr)| Attribute::Field(field.to_string(), builder_default_attr(expr)))
+        .collect()
+}
+
+/// [`serde_attr`] on the messages and/or enums of a package, followed by the `rename_all`
+/// attribute if any. Panics if the protos can't be parsed.
+pub(crate) fn serde_package_attrs(
+    proto_files: &[&str],
+    package: &str,
+    kinds: &[TypeKind],
+    ser: bool,
+    de: bool,
+    rename: Option<RenameAll>,
+) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    let paths: Vec<String> = kinds
+        .iter()
+        .flat_map(|kind| protos.types(package, *kind))
+        .collect();
+    let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+    let rename = rename.map(serde_rename_all_attr);
+    let extra = rename.as_ref().map(|attr| [attr.as_str()]);
+    type_attrs(&paths, serde_attr(ser, de), extra.as_ref().map(|e| &e[..]))
+}
+
+/// `attribute` on every field of the protos whose fully qualified path (e.g. `todo.Todo.id`)
+/// matches the regex `pattern`. Panics if the protos can't be parsed.
+pub(crate) fn fields_matching_attrs(
+    proto_files: &[&str],
+    pattern: &str,
+    attribute: &str,
+) -> Result<Vec<Attribute>, BuilderError> {
+    let re = regex::Regex::new(pattern).map_err(|e| BuilderError::InvalidPattern {
+        pattern: pattern.to_string(),
+        error: e.to_string(),
+    })?;
+    let protos = load_protos(proto_files);
+    Ok(protos
+        .messages()
+        .into_iter()
+        .flat_map(|(name, msg)| {
+            msg.fields
+                .iter()
+                .map(move |f| format!("{}.{}", name, f.name))
+        })
+        .filter(|path| re.is_match(path))
+        .map(|path| Attribute::Field(path, attribute.to_string()))
+        .collect())
+}
+
+/// the attributes of `profile` on every path, in order
+pub(crate) fn profile_attrs(paths: &[&str], profile: &Profile) -> Vec<Attribute> {
+    paths
+        .iter()
+        .flat_map(|path| {
+            profile
+                .attributes()
+                .iter()
+                .map(move |attr| Attribute::Type(path.to_string(), attr.clone()))
+        })
+        .collect()
+}
+
+/// the API preset: [`serde_attr`] with both derives, `#[serde(rename_all = "camelCase")]`
+/// and [`serde_as_attr`] on each type
+pub(crate) fn api_preset_attrs(paths: &[&str]) -> Vec<Attribute> {
+    let rename = serde_rename_all_attr(RenameAll::CamelCase);
+    let mut attrs = type_attrs(paths, serde_attr(true, true), Some(&[rename.as_str()]));
+    attrs.extend(
+        paths
+            .iter()
+            .map(|path| Attribute::Type(path.to_string(), serde_as_attr().to_string())),
+    );
+    attrs
+}
+
+/// the database preset: [`sqlx_from_row_attr`] with `#[sqlx(rename_all = "snake_case")]`
+pub(crate) fn db_preset_attrs(paths: &[&str]) -> Vec<Attribute> {
+    type_attrs(paths, &sqlx_from_row_opts_attr(Some("snake_case")), None)
+}
+
+/// [`derive_attr`] on the messages and enums of the protos whose fully qualified name (e.g.
+/// `todo.CreateTodoRequest`) matches `predicate`. Panics if the protos can't be parsed.
+pub(crate) fn derive_where_attrs(
+    proto_files: &[&str],
+    predicate: impl Fn(&str) -> bool,
+    derives: &[&str],
+) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    let messages = protos.messages().into_iter().map(|(name, _)| name);
+    let enums = protos.enums().into_iter().map(|(name, _)| name);
+    let paths: Vec<String> = messages
+        .chain(enums)
+        .filter(|name| predicate(name))
+        .collect();
+    let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+    type_attrs(&paths, &derive_attr(derives), None)
+}
+
+/// [`serde_attr`] on the messages and enums of a package, listed from a `FileDescriptorSet`
+/// (e.g. written by `protoc --descriptor_set_out`). Panics if the file can't be read or decoded.
+pub(crate) fn serde_descriptor_attrs(
+    descriptor_path: &str,
+    package: &str,
+    ser: bool,
+    de: bool,
+) -> Vec<Attribute> {
+    let paths = descriptor_types(descriptor_path, package);
+    let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+    type_attrs(&paths, serde_attr(ser, de), None)
+}
+
+/// the attributes of each path joined into one type attribute, from owned or borrowed data
+pub(crate) fn attribute_map_attrs<I, P, A, S>(map: I) -> Vec<Attribute>
+where
+    I: IntoIterator<Item = (P, A)>,
+    P: AsRef<str>,
+    A: AsRef<[S]>,
+    S: AsRef<str>,
+{
+    map.into_iter()
+        .map(|(path, attrs)| {
+            let attrs: Vec<&str> = attrs.as_ref().iter().map(|a| a.as_ref()).collect();
+            Attribute::Type(path.as_ref().to_string(), attrs.join("\n"))
+        })
+        .collect()
+}
+
+/// `attribute` on every field of the scalar `proto_type` (e.g. `int64`), repeated fields
+/// included. Panics if the protos can't be parsed or `proto_type` is not a scalar type.
+pub(crate) fn fields_by_type_attrs(
+    proto_files: &[&str],
+    proto_type: &str,
+    attribute: &str,
+) -> Vec<Attribute> {
+    assert!(
+        is_scalar(proto_type),
+        "`{}` is not a scalar protobuf type",
+        proto_type
+    );
+    let protos = load_protos(proto_files);
+    protos
+        .messages()
+        .into_iter()
+        .flat_map(|(name, msg)| {
+            msg.fields
+                .iter()
+                .filter(|f| f.ty == proto_type && f.key.is_none())
+                .map(move |f| {
+                    Attribute::Field(format!("{}.{}", name, f.name), attribute.to_string())
+                })
+        })
+        .collect()
+}
+
+/// `#[serde(rename = "...")]` on every variant of the enums, with the value name of the proto.
+/// Panics if the protos can't be parsed or don't define one of the enums.
+pub(crate) fn enum_original_names_attrs(proto_files: &[&str], paths: &[&str]) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    paths
+        .iter()
+        .flat_map(|path| {
+            let path = path.trim_start_matches('.');
+            let e = protos
+                .enumeration(path)
+                .unwrap_or_else(|| panic!("enum `{}` not found in {:?}", path, proto_files));
+            // prost matches field attributes of enum variants against the proto value names
+            e.values.iter().map(move |v| {
+                Attribute::Field(
+                    format!("{}.{}", path, v.name),
+                    format!("#[serde(rename = \"{}\")]", v.name),
+                )
+            })
+        })
+        .collect()
+}
+
+/// parse the protos for the helpers taking `proto_files`, which can't report errors
+fn load_protos(proto_files: &[&str]) -> Protos {
+    Protos::load(proto_files).unwrap_or_else(|e| panic!("failed to parse {:?}: {}", proto_files, e))
+}
+
+/// the fully qualified names of the messages and enums of `package` in a `FileDescriptorSet`,
+/// nested ones included
+fn descriptor_types(descriptor_path: &str, package: &str) -> Vec<String> {
+    fn qualified(prefix: &str, name: &str) -> String {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    }
+
+    fn walk(prefix: &str, msgs: &[DescriptorProto], out: &mut Vec<String>) {
+        for m in msgs {
+            let name = qualified(prefix, m.name());
+            out.push(name.clone());
+            out.extend(m.enum_type.iter().map(|e| qualified(&name, e.name())));
+            walk(&name, &m.nested_type, out);
+        }
+    }
+
+    let set = fs::read(descriptor_path)
+        .and_then(|buf| FileDescriptorSet::decode(buf.as_slice()).map_err(io::Error::from))
+        .unwrap_or_else(|e| panic!("failed to load {:?}: {}", descriptor_path, e));
+    let mut out = Vec::new();
+    for file in set.file.iter().filter(|f| f.package() == package) {
+        walk(package, &file.message_type, &mut out);
+        out.extend(file.enum_type.iter().map(|e| qualified(package, e.name())));
+    }
+    out
+}
+
+/// `#[serde_as(as = "DisplayFromStr")]` on the 64-bit integer fields of the messages (wrapped
+/// for optional, repeated and map fields), with [`serde_as_attr`] on the messages that have
+/// any. Oneof members are left alone. Panics if the protos can't be parsed.
+pub(crate) fn i64_as_string_attrs(proto_files: &[&str], paths: &[&str]) -> Vec<Attribute> {
+    const INT64: &[&str] = &["int64", "uint64", "sint64", "fixed64", "sfixed64"];
+    scalar_serde_as_attrs(proto_files, paths, INT64, "serde_with::DisplayFromStr")
+}
+
+/// `#[serde_as(as = "serde_with::base64::Base64")]` on the `bytes` fields of the messages, like
+/// [`i64_as_string_attrs`]
+pub(crate) fn base64_bytes_attrs(proto_files: &[&str], paths: &[&str]) -> Vec<Attribute> {
+    scalar_serde_as_attrs(proto_files, paths, &["bytes"], "serde_with::base64::Base64")
+}
+
+/// `#[serde_as(as = "<adapter>")]` on the fields of the messages whose type is one of `types`,
+/// wrapped for optional, repeated and map fields, with [`serde_as_attr`] on the messages that
+/// have any. Oneof members are left alone. Panics if the protos can't be parsed.
+fn scalar_serde_as_attrs(
+    proto_files: &[&str],
+    paths: &[&str],
+    types: &[&str],
+    adapter: &str,
+) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    let mut attrs = Vec::new();
+    for path in paths {
+        let path = path.trim_start_matches('.');
+        let msg = protos
+            .message(path)
+            .unwrap_or_else(|| panic!("message `{}` not found in {:?}", path, proto_files));
+        let fields: Vec<Attribute> = msg
+            .fields
+            .iter()
+            .filter(|f| types.contains(&f.ty.as_str()) && f.oneof.is_none())
+            .map(|f| {
+                let ty = match f.label {
+                    _ if f.key.is_some() => format!("::std::collections::HashMap<_, {}>", adapter),
+                    Label::Repeated => format!("Vec<{}>", adapter),
+                    Label::Optional => format!("Option<{}>", adapter),
+                    _ => adapter.to_string(),
+                };
+                let attr = format!("#[serde_as(as = \"{}\")]", ty);
+                Attribute::Field(format!("{}.{}", path, f.name), attr)
+            })
+            .collect();
+        if !fields.is_empty() {
+            attrs.push(Attribute::Type(
+                path.to_string(),
+                serde_as_attr().to_string(),
+            ));
+            attrs.extend(fields);
+        }
+    }
+    attrs
+}
+
+/// `#[schemars(description = "...")]` with the leading comments of the messages and their fields,
+/// for those that have any. Panics if the protos can't be parsed.
+pub(crate) fn comments_as_schema_desc_attrs(
+    proto_files: &[&str],
+    paths: &[&str],
+) -> Vec<Attribute> {
+    let protos = load_protos(proto_files);
+    let desc =
+        |comments: &[String]| format!("#[schemars(description = {:?})]", comments.join("\n"));
+    let mut attrs = Vec::new();
+    for path in paths {
+        let path = path.trim_start_matches('.');
+        let msg = protos
+            .message(path)
+            .unwrap_or_else(|| panic!("message `{}` not found in {:?}", path, proto_files));
+        if !msg.comments.is_empty() {
+            attrs.push(Attribute::Type(path.to_string(), desc(&msg.comments)));
+        }
+        attrs.extend(
+            msg.fields
+                .iter()
+                .filter(|f| !f.comments.is_empty())
+                .map(|f| Attribute::Field(format!("{}.{}", path, f.name), desc(&f.comments))),
+        );
+    }
+    attrs
+}
+
+/// [`serde_as_attr`] on the type, and each attribute on its fields
+pub(crate) fn serde_as_attrs(path: &str, fields: &[(&[&str], &str)]) -> Vec<Attribute> {
+    let fields = fields.iter().flat_map(|(paths, attr)| {
+        paths
+            .iter()
+            .map(move |p| Attribute::Field(format!("{}.{}", path, p), attr.to_string()))
+    });
+    std::iter::once(Attribute::Type(
+        path.to_string(),
+        serde_as_attr().to_string(),
+    ))
+    .chain(fields)
+    .collect()
+}
+
+/// [`serde_as_attrs`] with each `as` expression wrapped in `Vec<...>`, for repeated fields
+pub(crate) fn serde_as_vec_attrs(path: &str, fields: &[(&[&str], &str)]) -> Vec<Attribute> {
+    serde_as_wrapped_attrs(path, fields, |ty| format!("Vec<{}>", ty))
+}
+
+/// [`serde_as_attrs`] with each `as` expression used for the values of a `HashMap`, for map
+/// fields
+pub(crate) fn serde_as_map_attrs(path: &str, fields: &[(&[&str], &str)]) -> Vec<Attribute> {
+    serde_as_wrapped_attrs(path, fields, |ty| {
+        format!("::std::collections::HashMap<_, {}>", ty)
+    })
+}
+
+/// [`serde_as_attrs`] with one custom converter per field, wrapped in `container<...>` if given
+pub(crate) fn serde_as_custom_attrs(
+    path: &str,
+    fields: &[(&str, &str)],
+    container: Option<&str>,
+) -> Vec<Attribute> {
+    let fields: Vec<(&[&str], &str)> = fields
+        .iter()
+        .map(|(field, ty)| (std::slice::from_ref(field), *ty))
+        .collect();
+    serde_as_wrapped_attrs(path, &fields, |ty| match container {
+        Some(container) => format!("{}<{}>", container, ty),
+        None => ty.to_string(),
+    })
+}
+
+fn serde_as_wrapped_attrs(
+    path: &str,
+    fields: &[(&[&str], &str)],
+    wrap: impl Fn(&str) -> String,
+) -> Vec<Attribute> {
+    let attrs: Vec<String> = fields
+        .iter()
+        .map(|(_, ty)| format!("#[serde_as(as = \"{}\")]", wrap(ty)))
+        .collect();
+    let fields: Vec<(&[&str], &str)> = fields
+        .iter()
+        .zip(&attrs)
+        .map(|((paths, _), attr)| (*paths, attr.as_str()))
+        .collect();
+    serde_as_attrs(path, &fields)
+}
+
+/// the attributes joined into one type attribute on every path
+pub(crate) fn joined_type_attrs(paths: &[&str], attributes: Option<&[&str]>) -> Vec<Attribute> {
+    joined(paths, attributes, Attribute::Type)
+}
+
+/// the attributes joined into one message attribute on every path
+pub(crate) fn joined_message_attrs(paths: &[&str], attributes: Option<&[&str]>) -> Vec<Attribute> {
+    joined(paths, attributes, Attribute::Message)
+}
+
+/// the attributes joined into one enum attribute on every path
+pub(crate) fn joined_enum_attrs(paths: &[&str], attributes: Option<&[&str]>) -> Vec<Attribute> {
+    joined(paths, attributes, Attribute::Enum)
+}
+
+/// the attributes joined into one field attribute on every path
+pub(crate) fn joined_field_attrs(paths: &[&str], attributes: Option<&[&str]>) -> Vec<Attribute> {
+    joined(paths, attributes, Attribute::Field)
+}
+
+fn joined(
+    paths: &[&str],
+    attributes: Option<&[&str]>,
+    f: fn(String, String) -> Attribute,
+) -> Vec<Attribute> {
+    let Some(attributes) = attributes else {
+        return Vec::new();
+    };
+    let attr = attributes.join("\n");
+    paths
+        .iter()
+        .map(|p| f(p.to_string(), attr.clone()))
+        .collect()
+}